@@ -72,19 +72,11 @@ pub enum TypeBody {
 
 impl TypeBody {
     pub fn is_struct(&self) -> bool {
-        if let Self::Struct(_) = self {
-            true
-        } else {
-            false
-        }
+        matches!(self, Self::Struct(_))
     }
 
     pub fn is_enum(&self) -> bool {
-        if let Self::Enum(_) = self {
-            true
-        } else {
-            false
-        }
+        matches!(self, Self::Enum(_))
     }
 }
 
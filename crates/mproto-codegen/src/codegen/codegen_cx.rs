@@ -1,7 +1,7 @@
 use crate::{
+    Database,
     ast::{PrimitiveType, QualifiedIdentifier, Type, TypeDef},
     codegen::{MprotoJs, MprotoLang, MprotoRust},
-    Database,
 };
 
 pub struct CodegenCx<'a> {
@@ -89,22 +89,18 @@ impl<'a> CodegenCx<'a> {
     }
 
     pub fn resolve_type(&self, ident: &QualifiedIdentifier) -> Option<ResolvedType<'a>> {
-        if ident.module.is_none() {
-            if let Some(type_param_binding) = self.resolve_type_param_binding(&ident.name) {
-                return match type_param_binding {
-                    TypeParamBinding::Unbound => Some(ResolvedType::UnboundParam),
-                    TypeParamBinding::Bound { value, binding_cx } => {
-                        Some(ResolvedType::BoundParam { value, binding_cx })
-                    }
-                };
-            }
+        if ident.module.is_none()
+            && let Some(type_param_binding) = self.resolve_type_param_binding(&ident.name)
+        {
+            return match type_param_binding {
+                TypeParamBinding::Unbound => Some(ResolvedType::UnboundParam),
+                TypeParamBinding::Bound { value, binding_cx } => {
+                    Some(ResolvedType::BoundParam { value, binding_cx })
+                }
+            };
         }
 
-        if let Some(type_def) = self.db.lookup_type_def(ident) {
-            Some(ResolvedType::Defined(type_def))
-        } else {
-            None
-        }
+        self.db.lookup_type_def(ident).map(ResolvedType::Defined)
     }
 }
 
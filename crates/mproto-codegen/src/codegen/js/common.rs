@@ -2,7 +2,7 @@ use genco::prelude::*;
 
 use crate::{
     ast::NamedField,
-    codegen::{js::js_type_encoder, CodegenCx},
+    codegen::{CodegenCx, js::js_type_encoder},
 };
 
 pub fn js_named_fields_encode(cx: &CodegenCx, fields: &[NamedField]) -> js::Tokens {
@@ -35,7 +35,7 @@ pub fn js_named_fields_scratch_len(cx: &CodegenCx, fields: &[NamedField]) -> js:
     let mut fields_scratch_len_tokens = js::Tokens::new();
     for field in fields {
         quote_in! { fields_scratch_len_tokens =>
-            $(js_type_encoder(cx, &field.ty)).scratchLength(value.$(&field.name)) +$(" ")
+            $(js_type_encoder(cx, &field.ty)).scratchLength(value.$(&field.name), format) +$(" ")
         };
     }
 
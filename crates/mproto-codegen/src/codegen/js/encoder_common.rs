@@ -13,8 +13,9 @@ impl EncoderCommon {
     pub fn new(name: &str, type_params: &[String]) -> Self {
         let encode_interface = &js::import("@modrpc-org/mproto", "Encoder");
         let decode_interface = &js::import("@modrpc-org/mproto", "Decoder");
+        let wire_format = &js::import("@modrpc-org/mproto", "WireFormat");
 
-        let ref type_param_list = if type_params.len() > 0 {
+        let type_param_list = &(if !type_params.is_empty() {
             let mut type_param_list = js::Tokens::new();
             quote_in! { type_param_list => $(&type_params[0]) };
             for type_param_name in &type_params[1..] {
@@ -24,9 +25,9 @@ impl EncoderCommon {
             quote! { <$type_param_list> }
         } else {
             quote! {}
-        };
+        });
 
-        let encoder_fields = if type_params.len() > 0 {
+        let encoder_fields = if !type_params.is_empty() {
             let mut type_param_encoder_fields = js::Tokens::new();
             for type_param_name in type_params {
                 type_param_encoder_fields = quote! {
@@ -40,7 +41,7 @@ impl EncoderCommon {
             quote! {}
         };
 
-        let (encoder_constructor, lazy_constructor) = if type_params.len() > 0 {
+        let (encoder_constructor, lazy_constructor) = if !type_params.is_empty() {
             let mut type_param_encoders = js::Tokens::new();
             type_param_encoders = quote! {
                 $type_param_encoders
@@ -77,10 +78,12 @@ impl EncoderCommon {
                         $type_param_encoders
                         buffer: DataView,
                         offset: number,
+                        format: $wire_format,
                     ) {
                         $type_param_encoder_fields
                         this._buffer = buffer;
                         this._offset = offset;
+                        this._format = format;
                     }
                 },
             )
@@ -91,15 +94,17 @@ impl EncoderCommon {
                     constructor(
                         buffer: DataView,
                         offset: number,
+                        format: $wire_format,
                     ) {
                         this._buffer = buffer;
                         this._offset = offset;
+                        this._format = format;
                     }
                 },
             )
         };
 
-        let (encoder_instance, lazy_encoder_instance) = if type_params.len() > 0 {
+        let (encoder_instance, lazy_encoder_instance) = if !type_params.is_empty() {
             let mut param_type_param_encoders: js::Tokens = quote! {
                 $(&type_params[0])Encoder: $encode_interface<$(&type_params[0])> & $decode_interface<$(&type_params[0])>,
             };
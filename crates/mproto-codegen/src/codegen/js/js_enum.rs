@@ -3,19 +3,19 @@ use genco::prelude::*;
 use crate::{
     ast::{Enum, EnumVariant},
     codegen::{
-        enum_base_len, enum_variant_base_len,
+        CodegenCx, FormatBaseLen, MprotoJs, enum_base_len, enum_variant_base_len,
         js::{
             common::{js_named_fields_decode, js_named_fields_encode, js_named_fields_scratch_len},
             encoder_common::EncoderCommon,
             js_type_tokens,
         },
-        CodegenCx, MprotoJs,
     },
 };
 
 pub fn js_enum(cx: &CodegenCx, name: &str, type_params: &[String], e: &Enum) -> js::Tokens {
     let encode_cursor = &js::import("@modrpc-org/mproto", "EncodeCursor");
     let decode_cursor = &js::import("@modrpc-org/mproto", "DecodeCursor");
+    let wire_format = &js::import("@modrpc-org/mproto", "WireFormat");
     let encode_interface = &js::import("@modrpc-org/mproto", "Encoder");
     let decode_interface = &js::import("@modrpc-org/mproto", "Decoder");
 
@@ -27,7 +27,10 @@ pub fn js_enum(cx: &CodegenCx, name: &str, type_params: &[String], e: &Enum) ->
         ..
     } = EncoderCommon::new(name, type_params);
 
-    let ref full_type_name: js::Tokens = quote! { $(name)$(type_param_list) };
+    let full_type_name: &js::Tokens = &quote! { $(name)$(type_param_list) };
+
+    let enum_base_len = FormatBaseLen::<MprotoJs>::new(|width| enum_base_len(cx, e, width));
+    let enum_base_len = &enum_base_len.as_tokens(&quote!(format));
 
     let mut variants_scratch_len_tokens = js::Tokens::new();
     for (variant_name, variant) in &e.variants {
@@ -54,7 +57,7 @@ pub fn js_enum(cx: &CodegenCx, name: &str, type_params: &[String], e: &Enum) ->
 
     let mut variants_encode_tokens = js::Tokens::new();
     for (i, (variant_name, variant)) in e.variants.iter().enumerate() {
-        let variant_base_len = enum_variant_base_len::<MprotoJs>(cx, variant).as_tokens();
+        let variant_base_len = js_enum_variant_base_len(cx, variant);
         variants_encode_tokens = quote! {
             $variants_encode_tokens
             if (value instanceof $(name).$(variant_name)) {
@@ -63,13 +66,13 @@ pub fn js_enum(cx: &CodegenCx, name: &str, type_params: &[String], e: &Enum) ->
                         EnumVariant::Empty => {
                             quote! {
                                 cursor.buffer.setUint8(cursor.base(1), $i);
-                                cursor.base(this.baseLength() - 1);
+                                cursor.base(this.baseLength(cursor.format) - 1);
                             }
                         }
                         EnumVariant::NamedFields { fields } => {
                             quote! {
                                 cursor.buffer.setUint8(cursor.base(1), $i);
-                                cursor.base(this.baseLength() - 1 - $variant_base_len);
+                                cursor.base(this.baseLength(cursor.format) - 1 - $variant_base_len);
                                 $(js_named_fields_encode(cx, fields))
                             }
                         }
@@ -83,7 +86,7 @@ pub fn js_enum(cx: &CodegenCx, name: &str, type_params: &[String], e: &Enum) ->
         let variant = cursor.buffer.getUint8(cursor.base(1));
     };
     for (i, (variant_name, variant)) in e.variants.iter().enumerate() {
-        let variant_base_len = enum_variant_base_len::<MprotoJs>(cx, variant).as_tokens();
+        let variant_base_len = js_enum_variant_base_len(cx, variant);
         variants_decode_tokens = quote! {
             $variants_decode_tokens
             if (variant == $i) {
@@ -91,7 +94,7 @@ pub fn js_enum(cx: &CodegenCx, name: &str, type_params: &[String], e: &Enum) ->
                     match variant {
                         EnumVariant::Empty => {
                             quote! {
-                                cursor.base(this.baseLength() - 1);
+                                cursor.base(this.baseLength(cursor.format) - 1);
                                 return new $name.$variant_name();
                             }
                         }
@@ -106,7 +109,7 @@ pub fn js_enum(cx: &CodegenCx, name: &str, type_params: &[String], e: &Enum) ->
 
                             quote! {
                                 $decode_fields
-                                cursor.base(this.baseLength() - 1 - $variant_base_len);
+                                cursor.base(this.baseLength(cursor.format) - 1 - $variant_base_len);
                                 return new $name.$variant_name($constructor_fields);
                             }
                         }
@@ -186,9 +189,9 @@ pub fn js_enum(cx: &CodegenCx, name: &str, type_params: &[String], e: &Enum) ->
 
             $encoder_constructor
 
-            baseLength = () => $(enum_base_len::<MprotoJs>(cx, e).as_tokens());
+            baseLength = (format: $wire_format = $wire_format.Absolute) => $enum_base_len;
 
-            scratchLength(value: $full_type_name): number {
+            scratchLength(value: $full_type_name, format: $wire_format = $wire_format.Absolute): number {
                 $variants_scratch_len_tokens
             }
 
@@ -206,3 +209,9 @@ pub fn js_enum(cx: &CodegenCx, name: &str, type_params: &[String], e: &Enum) ->
 
     tokens
 }
+
+/// A variant's base length in the wire format of the `cursor` in scope.
+fn js_enum_variant_base_len(cx: &CodegenCx, variant: &EnumVariant) -> js::Tokens {
+    FormatBaseLen::<MprotoJs>::new(|width| enum_variant_base_len(cx, variant, width))
+        .as_tokens(&quote!(cursor.format))
+}
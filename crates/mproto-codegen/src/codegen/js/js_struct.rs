@@ -3,18 +3,20 @@ use genco::prelude::*;
 use crate::{
     ast::{NamedField, QualifiedIdentifier, Struct, Type},
     codegen::{
+        CodegenCx, FormatBaseLen, MprotoJs,
         js::{
             common::{js_named_fields_decode, js_named_fields_encode, js_named_fields_scratch_len},
             encoder_common::EncoderCommon,
             js_encoder_type_args, js_type_lazy_encoder, js_type_lazy_tokens, js_type_tokens,
         },
-        struct_base_len, type_base_len, CodegenCx, MprotoJs, TypeBaseLen,
+        struct_base_len, type_base_len,
     },
 };
 
 pub fn js_struct(cx: &CodegenCx, name: &str, type_params: &[String], s: &Struct) -> js::Tokens {
     let encode_cursor = &js::import("@modrpc-org/mproto", "EncodeCursor");
     let decode_cursor = &js::import("@modrpc-org/mproto", "DecodeCursor");
+    let wire_format = &js::import("@modrpc-org/mproto", "WireFormat");
 
     let encode_interface = &js::import("@modrpc-org/mproto", "Encoder");
     let decode_interface = &js::import("@modrpc-org/mproto", "Decoder");
@@ -39,8 +41,8 @@ pub fn js_struct(cx: &CodegenCx, name: &str, type_params: &[String], s: &Struct)
         lazy_encoder_instance,
     } = EncoderCommon::new(name, type_params);
 
-    let ref full_type_name: js::Tokens = quote! { $(name)$(type_param_list) };
-    let ref full_lazy_type_name: js::Tokens = quote! { $(name)Lazy$(type_param_list) };
+    let full_type_name: &js::Tokens = &quote! { $(name)$(type_param_list) };
+    let full_lazy_type_name: &js::Tokens = &quote! { $(name)Lazy$(type_param_list) };
 
     let mut owned_field_tokens = js::Tokens::new();
     for field in &s.fields {
@@ -53,17 +55,22 @@ pub fn js_struct(cx: &CodegenCx, name: &str, type_params: &[String], s: &Struct)
     let fields_scratch_len_tokens = js_named_fields_scratch_len(cx, &s.fields);
 
     let mut lazy_method_tokens = js::Tokens::new();
-    let mut field_offset = TypeBaseLen::<MprotoJs>::constant(0);
+    let mut field_offset = FormatBaseLen::<MprotoJs>::constant(0);
     for field in &s.fields {
         lazy_method_tokens = quote! {
             $lazy_method_tokens
 
-            $(js_lazy_decoder_method(cx, field, field_offset.as_tokens()))
+            $(js_lazy_decoder_method(cx, field, field_offset.as_tokens(&quote!(this._format))))
         };
 
-        field_offset = field_offset.merge(type_base_len(cx, &field.ty));
+        field_offset = field_offset.merge(FormatBaseLen::new(|width| {
+            type_base_len(cx, &field.ty, width)
+        }));
     }
 
+    let struct_base_len = FormatBaseLen::<MprotoJs>::new(|width| struct_base_len(cx, s, width));
+    let struct_base_len = &struct_base_len.as_tokens(&quote!(format));
+
     let encode_owned_tokens = js_named_fields_encode(cx, &s.fields);
     let decode_owned_tokens = js_named_fields_decode(cx, &s.fields);
 
@@ -85,9 +92,9 @@ pub fn js_struct(cx: &CodegenCx, name: &str, type_params: &[String], s: &Struct)
 
             $encoder_constructor
 
-            baseLength = () => $(struct_base_len::<MprotoJs>(cx, s).as_tokens());
+            baseLength = (format: $wire_format = $wire_format.Absolute) => $struct_base_len;
 
-            scratchLength(value: $full_type_name): number {
+            scratchLength(value: $full_type_name, format: $wire_format = $wire_format.Absolute): number {
                 return $fields_scratch_len_tokens 0;
             }
 
@@ -108,6 +115,7 @@ pub fn js_struct(cx: &CodegenCx, name: &str, type_params: &[String], s: &Struct)
             $encoder_fields
             private _buffer: DataView;
             private _offset: number;
+            private _format: $wire_format;
 
             $lazy_constructor
 
@@ -119,14 +127,14 @@ pub fn js_struct(cx: &CodegenCx, name: &str, type_params: &[String], s: &Struct)
 
             $encoder_constructor
 
-            baseLength = () => $(struct_base_len::<MprotoJs>(cx, s).as_tokens());
+            baseLength = (format: $wire_format = $wire_format.Absolute) => $struct_base_len;
 
             decode(cursor: $decode_cursor): $full_lazy_type_name {
-                let offset = cursor.base(this.baseLength());
-                $(if type_params.len() == 0 {
-                    return new $(name)Lazy(cursor.buffer, offset);
+                let offset = cursor.base(this.baseLength(cursor.format));
+                $(if type_params.is_empty() {
+                    return new $(name)Lazy(cursor.buffer, offset, cursor.format);
                 } else {
-                    return new $(name)Lazy($(js_encoder_type_args(cx, &type_args, js_type_lazy_encoder)), cursor.buffer, offset);
+                    return new $(name)Lazy($(js_encoder_type_args(cx, &type_args, js_type_lazy_encoder)), cursor.buffer, offset, cursor.format);
                 })
             }
         }
@@ -148,7 +156,7 @@ pub fn js_lazy_decoder_method(
 
     quote! {
         public $(&field.name)(): $(js_type_lazy_tokens(cx, &field.ty)) {
-            return $(decoder).decode(new $decode_cursor(this._buffer, this._offset + $field_offset));
+            return $(decoder).decode(new $decode_cursor(this._buffer, this._offset + $field_offset, this._format));
         }
     }
 }
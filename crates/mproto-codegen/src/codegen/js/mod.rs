@@ -69,14 +69,14 @@ pub fn js_type_tokens(cx: &CodegenCx, ty: &Type) -> js::Tokens {
 
 pub fn js_type_lazy_tokens(cx: &CodegenCx, ty: &Type) -> js::Tokens {
     match ty {
-        Type::Primitive(PrimitiveType::Box(inner_ty)) => js_type_lazy_tokens(cx, &inner_ty),
+        Type::Primitive(PrimitiveType::Box(inner_ty)) => js_type_lazy_tokens(cx, inner_ty),
         Type::Primitive(PrimitiveType::List(item_ty)) => {
             let list_lazy = js::import("@modrpc-org/mproto", "ListLazy");
-            quote! { $list_lazy<$(js_type_lazy_tokens(cx, &item_ty))> }
+            quote! { $list_lazy<$(js_type_lazy_tokens(cx, item_ty))> }
         }
         Type::Primitive(PrimitiveType::Option(inner_ty)) => {
             let option = js::import("@modrpc-org/mproto", "Option");
-            quote! { $option<$(js_type_lazy_tokens(cx, &inner_ty))> }
+            quote! { $option<$(js_type_lazy_tokens(cx, inner_ty))> }
         }
         Type::Primitive(PrimitiveType::Result(ok_ty, err_ty)) => quote! {
             $(js::import("@modrpc-org/mproto", "Result"))<$(js_type_lazy_tokens(cx, ok_ty)), $(js_type_lazy_tokens(cx, err_ty))>
@@ -89,7 +89,7 @@ pub fn js_type_lazy_tokens(cx: &CodegenCx, ty: &Type) -> js::Tokens {
                         // Lazy decoders aren't generated for enum types yet.
                         let args = js_type_args(cx, args, js_type_tokens);
                         let import = cx.js_import_qualified(&QualifiedIdentifier {
-                            name: format!("{}", ident.name),
+                            name: ident.name.to_string(),
                             module: ident.module.clone(),
                         });
                         quote! { $(import)$(args) }
@@ -119,7 +119,7 @@ pub fn js_type_args(
     args: &[Type],
     mut gen_tokens_fn: impl FnMut(&CodegenCx, &Type) -> js::Tokens,
 ) -> js::Tokens {
-    if args.len() > 0 {
+    if !args.is_empty() {
         let arg_tokens = gen_tokens_fn(cx, &args[0]);
         let mut args_items: js::Tokens = quote! { $arg_tokens };
         for arg in &args[1..] {
@@ -134,7 +134,7 @@ pub fn js_type_args(
 }
 
 pub fn js_type_param_list(params: &[String]) -> js::Tokens {
-    if params.len() == 0 {
+    if params.is_empty() {
         Tokens::new()
     } else {
         let mut tokens = quote! { <$(&params[0]) };
@@ -154,7 +154,7 @@ pub fn js_encoder_type_args(
     args: &[Type],
     mut gen_tokens_fn: impl FnMut(&CodegenCx, &Type) -> js::Tokens,
 ) -> js::Tokens {
-    if args.len() > 0 {
+    if !args.is_empty() {
         let arg_tokens = gen_tokens_fn(cx, &args[0]);
         let mut args_items: js::Tokens = quote! { $arg_tokens };
         for arg in &args[1..] {
@@ -173,7 +173,7 @@ pub fn js_encoder_type_args_enclosed(
     args: &[Type],
     gen_tokens_fn: impl FnMut(&CodegenCx, &Type) -> js::Tokens,
 ) -> js::Tokens {
-    if args.len() > 0 {
+    if !args.is_empty() {
         quote! { ($(js_encoder_type_args(cx, args, gen_tokens_fn))) }
     } else {
         quote! {}
@@ -4,10 +4,10 @@ use std::path::Path;
 
 use genco::{self, quote_in};
 
-use crate::{ast::TypeDef, codegen, Database, Module};
+use crate::{Database, Module, ast::TypeDef, codegen};
 
-const PACKAGE_JSON: &'static str = include_str!("templates/package.json");
-const TSCONFIG_JSON: &'static str = include_str!("templates/tsconfig.json");
+const PACKAGE_JSON: &str = include_str!("templates/package.json");
+const TSCONFIG_JSON: &str = include_str!("templates/tsconfig.json");
 
 pub fn js_package_gen(
     root_dir: impl AsRef<Path>,
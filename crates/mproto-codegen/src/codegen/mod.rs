@@ -1,20 +1,23 @@
 use genco::prelude::*;
 
-use crate::{ast::QualifiedIdentifier, Database};
+use crate::{Database, ast::QualifiedIdentifier};
 
 pub use codegen_cx::{
-    type_uses_param, type_uses_type_param, CodegenCx, ResolvedType, TypeParamBinding,
-    TypeParamBindings,
+    CodegenCx, ResolvedType, TypeParamBinding, TypeParamBindings, type_uses_param,
+    type_uses_type_param,
 };
 pub(crate) use type_base_len::{
-    enum_base_len, enum_variant_base_len, struct_base_len, type_base_len, TypeBaseLen,
+    FormatBaseLen, OffsetWidth, TypeBaseLen, enum_base_len, enum_variant_base_len, struct_base_len,
+    type_base_len,
 };
+pub(crate) use type_fingerprint::type_def_fingerprint;
 
 mod codegen_cx;
 pub mod js;
 pub mod name_util;
 pub mod rust;
 mod type_base_len;
+mod type_fingerprint;
 
 pub trait MprotoLang {
     type GencoLang: genco::lang::Lang;
@@ -22,8 +25,14 @@ pub trait MprotoLang {
     fn associated_constant(type_name: &str, constant: &str) -> Tokens<Self::GencoLang>;
 
     // mproto specific concepts
-    fn type_param_base_len(type_name: &str) -> Tokens<Self::GencoLang>;
+    fn type_param_base_len(type_name: &str, width: OffsetWidth) -> Tokens<Self::GencoLang>;
     fn const_fn_max() -> Tokens<Self::GencoLang>;
+    /// `wide` if the wire format held in `format` is the wide one, `narrow` otherwise.
+    fn select_base_len(
+        format: &Tokens<Self::GencoLang>,
+        narrow: Tokens<Self::GencoLang>,
+        wide: Tokens<Self::GencoLang>,
+    ) -> Tokens<Self::GencoLang>;
     fn import_qualified(
         db: &Database,
         local_def_source: Option<&str>,
@@ -43,14 +52,29 @@ impl MprotoLang for MprotoJs {
         quote! { $type_name.$constant }
     }
 
-    fn type_param_base_len(type_name: &str) -> Tokens<Self::GencoLang> {
-        quote! { this.$(type_name)Encoder.baseLength() }
+    fn type_param_base_len(type_name: &str, width: OffsetWidth) -> Tokens<Self::GencoLang> {
+        match width {
+            OffsetWidth::Narrow => quote! { this.$(type_name)Encoder.baseLength() },
+            OffsetWidth::Wide => {
+                let wire_format = &genco::lang::js::import("@modrpc-org/mproto", "WireFormat");
+                quote! { this.$(type_name)Encoder.baseLength($wire_format.Wide) }
+            }
+        }
     }
 
     fn const_fn_max() -> Tokens<Self::GencoLang> {
         quote! { Math.max }
     }
 
+    fn select_base_len(
+        format: &Tokens<Self::GencoLang>,
+        narrow: Tokens<Self::GencoLang>,
+        wide: Tokens<Self::GencoLang>,
+    ) -> Tokens<Self::GencoLang> {
+        let wire_format = &genco::lang::js::import("@modrpc-org/mproto", "WireFormat");
+        quote! { ($format === $wire_format.Wide ? $wide : $narrow) }
+    }
+
     fn import_qualified(
         db: &Database,
         local_def_source: Option<&str>,
@@ -61,7 +85,7 @@ impl MprotoLang for MprotoJs {
             let lib_suffix = db
                 .lookup_module_lib_suffix(module)
                 // TODO error handling
-                .expect(&format!("module '{module}' not found"));
+                .unwrap_or_else(|| panic!("module '{module}' not found"));
             quote! {
                 $(genco::lang::js::import(
                     format!("{module}-{lib_suffix}").as_ref(),
@@ -90,14 +114,25 @@ impl MprotoLang for MprotoRust {
         quote! { $type_name::$constant }
     }
 
-    fn type_param_base_len(type_name: &str) -> Tokens<Self::GencoLang> {
-        Self::associated_constant(type_name, "BASE_LEN")
+    fn type_param_base_len(type_name: &str, width: OffsetWidth) -> Tokens<Self::GencoLang> {
+        match width {
+            OffsetWidth::Narrow => Self::associated_constant(type_name, "BASE_LEN"),
+            OffsetWidth::Wide => Self::associated_constant(type_name, "WIDE_BASE_LEN"),
+        }
     }
 
     fn const_fn_max() -> Tokens<Self::GencoLang> {
         quote! { $(genco::lang::rust::import("mproto", "max")) }
     }
 
+    fn select_base_len(
+        format: &Tokens<Self::GencoLang>,
+        narrow: Tokens<Self::GencoLang>,
+        wide: Tokens<Self::GencoLang>,
+    ) -> Tokens<Self::GencoLang> {
+        quote! { $format.select($narrow, $wide) }
+    }
+
     fn import_qualified(
         db: &Database,
         local_def_source: Option<&str>,
@@ -108,11 +143,11 @@ impl MprotoLang for MprotoRust {
             let lib_suffix = db
                 .lookup_module_lib_suffix(module)
                 // TODO error handling
-                .expect(&format!("module '{module}' not found"));
+                .unwrap_or_else(|| panic!("module '{module}' not found"));
             quote! {
                 $(
                     genco::lang::rust::import(
-                        &format!("{module}_{lib_suffix}"),
+                        format!("{module}_{lib_suffix}"),
                         &qualified_identifier.name,
                     )
                     .qualified()
@@ -1,5 +1,5 @@
 pub fn camel_to_snake_case(s: &str) -> String {
-    if s.len() == 0 {
+    if s.is_empty() {
         return String::new();
     }
 
@@ -45,7 +45,7 @@ pub fn camel_to_kebab_case(s: &str) -> String {
 }
 
 fn _snake_to_camel_case(s: &str, start_upper: bool) -> String {
-    if s.len() == 0 {
+    if s.is_empty() {
         return String::new();
     }
 
@@ -3,15 +3,15 @@ use std::collections::HashSet;
 use genco::prelude::*;
 
 use crate::{
+    Database,
     ast::{
         Enum, EnumVariant, NamedField, PrimitiveType, QualifiedIdentifier, Struct, Type, TypeBody,
     },
     codegen::{
+        CodegenCx, OffsetWidth,
         name_util::camel_to_snake_case,
         rust::{rust_type_lazy_tokens, rust_type_tokens},
-        CodegenCx,
     },
-    Database,
 };
 
 pub fn type_requires_heap(db: &Database, ty: &Type) -> bool {
@@ -65,7 +65,7 @@ pub fn struct_requires_heap(db: &Database, s: &Struct) -> bool {
 }
 
 pub fn enum_requires_heap(db: &Database, e: &Enum) -> bool {
-    for &(_, ref variant) in &e.variants {
+    for (_, variant) in &e.variants {
         match *variant {
             EnumVariant::Empty => {}
             EnumVariant::NamedFields { ref fields } => {
@@ -81,19 +81,101 @@ pub fn enum_requires_heap(db: &Database, e: &Enum) -> bool {
     false
 }
 
+/// A constant expression for whether a value made of the given types can take up scratch space -
+/// see `BaseLen::HAS_SCRATCH`. `None` if it certainly can.
+pub fn rust_types_have_scratch<'t>(
+    cx: &CodegenCx,
+    tys: impl IntoIterator<Item = &'t Type>,
+) -> Option<rust::Tokens> {
+    let mut terms = Vec::new();
+    for ty in tys {
+        if collect_has_scratch_terms(cx, ty, &mut terms) {
+            return None;
+        }
+    }
+
+    if terms.is_empty() {
+        Some(quote! { false })
+    } else {
+        Some(quote! { $(for term in terms join ( || ) => $term) })
+    }
+}
+
+/// Returns true if `ty` certainly has scratch space, otherwise collects the `HAS_SCRATCH`
+/// constants of the types it depends on.
+fn collect_has_scratch_terms(cx: &CodegenCx, ty: &Type, terms: &mut Vec<rust::Tokens>) -> bool {
+    match ty {
+        Type::Primitive(PrimitiveType::String)
+        | Type::Primitive(PrimitiveType::Box(_))
+        | Type::Primitive(PrimitiveType::List(_)) => true,
+        Type::Primitive(PrimitiveType::Option(item_ty)) => {
+            collect_has_scratch_terms(cx, item_ty, terms)
+        }
+        Type::Primitive(PrimitiveType::Result(ok_ty, err_ty)) => {
+            collect_has_scratch_terms(cx, ok_ty, terms)
+                || collect_has_scratch_terms(cx, err_ty, terms)
+        }
+        Type::Primitive(_) => false,
+        Type::Defined { .. } => {
+            // Types that need the heap have scratch space, and may not be defined without it.
+            if type_requires_heap(cx.db, ty) {
+                return true;
+            }
+            let base_len_trait = &rust::import("mproto", "BaseLen");
+            terms.push(quote! { <$(rust_type_tokens(cx, ty)) as $base_len_trait>::HAS_SCRATCH });
+            false
+        }
+    }
+}
+
+/// Whether values of `ty` are always encoded in the same number of bytes, with no scratch space,
+/// so that they can be overwritten in place. Types with type parameters don't count.
+pub fn type_is_fixed_size(db: &Database, ty: &Type) -> bool {
+    match ty {
+        Type::Primitive(PrimitiveType::String)
+        | Type::Primitive(PrimitiveType::Box(_))
+        | Type::Primitive(PrimitiveType::List(_)) => false,
+        Type::Primitive(PrimitiveType::Option(item_ty)) => type_is_fixed_size(db, item_ty),
+        Type::Primitive(PrimitiveType::Result(ok_ty, err_ty)) => {
+            type_is_fixed_size(db, ok_ty) && type_is_fixed_size(db, err_ty)
+        }
+        Type::Primitive(_) => true,
+        Type::Defined { ident, args } => {
+            if !args.is_empty() {
+                return false;
+            }
+            match db.lookup_type_def(ident) {
+                Some(type_def) => match &type_def.body {
+                    TypeBody::Struct(s) => struct_is_fixed_size(db, s),
+                    TypeBody::Enum(e) => e.variants.iter().all(|(_, variant)| match variant {
+                        EnumVariant::Empty => true,
+                        EnumVariant::NamedFields { fields } => {
+                            fields.iter().all(|field| type_is_fixed_size(db, &field.ty))
+                        }
+                    }),
+                },
+                // A type parameter.
+                None => false,
+            }
+        }
+    }
+}
+
+pub fn struct_is_fixed_size(db: &Database, s: &Struct) -> bool {
+    s.fields
+        .iter()
+        .all(|field| type_is_fixed_size(db, &field.ty))
+}
+
 pub fn struct_contains_float(db: &Database, s: &Struct) -> bool {
-    TypeWalker::new().walk_struct(db, s, &mut |leaf_ty| match leaf_ty {
-        PrimitiveType::F32 => true,
-        PrimitiveType::F64 => true,
-        _ => false,
+    TypeWalker::new().walk_struct(db, s, &mut |leaf_ty| {
+        matches!(leaf_ty, PrimitiveType::F32 | PrimitiveType::F64)
     })
 }
 
 pub fn enum_contains_float(db: &Database, e: &Enum) -> bool {
-    TypeWalker::new().walk_enum(db, e, &mut |leaf_ty| match leaf_ty {
-        PrimitiveType::F32 => true,
-        PrimitiveType::F64 => true,
-        _ => false,
+    TypeWalker::new().walk_enum(db, e, &mut |leaf_ty| {
+        matches!(leaf_ty, PrimitiveType::F32 | PrimitiveType::F64)
     })
 }
 
@@ -174,7 +256,7 @@ impl TypeWalker {
         e: &Enum,
         visit_leaf: &mut impl FnMut(&PrimitiveType) -> bool,
     ) -> bool {
-        for &(_, ref variant) in &e.variants {
+        for (_, variant) in &e.variants {
             match *variant {
                 EnumVariant::Empty => {}
                 EnumVariant::NamedFields { ref fields } => {
@@ -235,7 +317,7 @@ pub fn lazy_type_requires_lifetime(db: &Database, ty: &Type) -> bool {
 }
 
 pub fn lazy_enum_requires_lifetime(db: &Database, e: &Enum) -> bool {
-    for &(_, ref variant) in &e.variants {
+    for (_, variant) in &e.variants {
         match variant {
             EnumVariant::Empty => {}
             EnumVariant::NamedFields { fields } => {
@@ -259,11 +341,17 @@ pub fn rust_lazy_field_decode(field: &NamedField, field_offset: rust::Tokens) ->
         // Special handling for boxed types
         quote! {
             $decode_cursor::at_offset(self.buffer, self.offset + $field_offset)
+                .with_format(self.format)
+                .with_limits_at_depth(self.limits, self.depth)
                 .inner_in_scratch(|cursor| $decode_trait::decode(cursor))
         }
     } else {
         quote! {
-            $decode_trait::decode(&$decode_cursor::at_offset(self.buffer, self.offset + $field_offset))
+            $decode_trait::decode(
+                &$decode_cursor::at_offset(self.buffer, self.offset + $field_offset)
+                    .with_format(self.format)
+                    .with_limits_at_depth(self.limits, self.depth)
+            )
         }
     }
 }
@@ -292,27 +380,41 @@ pub fn rust_named_fields_owned(
 }
 
 pub fn rust_named_fields_lazy(cx: &CodegenCx, fields: &[NamedField]) -> rust::Tokens {
-    let box_lazy = &rust::import("mproto", "BoxLazy");
-
     let mut ref_field_tokens = rust::Tokens::new();
     for field in fields {
-        if let Type::Primitive(PrimitiveType::Box(_)) = &field.ty {
-            // special handling for boxed types
-            ref_field_tokens = quote! {
-                $ref_field_tokens
-                $(&field.name): $box_lazy<'a, $(rust_type_lazy_tokens(cx, &field.ty))>,
-            };
-        } else {
-            ref_field_tokens = quote! {
-                $ref_field_tokens
-                $(&field.name): $(rust_type_lazy_tokens(cx, &field.ty)),
-            };
-        }
+        ref_field_tokens = quote! {
+            $ref_field_tokens
+            $(&field.name): $(rust_field_lazy_type_tokens(cx, field)),
+        };
     }
 
     ref_field_tokens
 }
 
+/// The type a field is lazily decoded as when it's stored in a lazy value rather than decoded on
+/// access.
+pub fn rust_field_lazy_type_tokens(cx: &CodegenCx, field: &NamedField) -> rust::Tokens {
+    let box_lazy = &rust::import("mproto", "BoxLazy");
+
+    if let Type::Primitive(PrimitiveType::Box(_)) = &field.ty {
+        // special handling for boxed types
+        quote! { $box_lazy<'a, $(rust_type_lazy_tokens(cx, &field.ty))> }
+    } else {
+        rust_type_lazy_tokens(cx, &field.ty)
+    }
+}
+
+/// The type a field is accessed as in a verified view, see `Lazy::View`.
+pub fn rust_field_view_type_tokens(
+    cx: &CodegenCx,
+    field: &NamedField,
+    lifetime: rust::Tokens,
+) -> rust::Tokens {
+    let lazy_trait = &rust::import("mproto", "Lazy");
+
+    quote! { <$(rust_field_lazy_type_tokens(cx, field)) as $lazy_trait<$(&lifetime)>>::View }
+}
+
 pub fn rust_named_fields_lazy_phantom(type_params: &[String]) -> rust::Tokens {
     let mut phantom_field_tokens = rust::Tokens::new();
     for type_param in type_params {
@@ -340,12 +442,18 @@ pub fn rust_named_fields_lazy_phantom_constructor(type_params: &[String]) -> rus
 pub fn rust_named_fields_scratch_len(
     fields: &[NamedField],
     field_prefix: rust::Tokens,
+    width: OffsetWidth,
 ) -> rust::Tokens {
-    if fields.len() > 0 {
+    let scratch_len_method = match width {
+        OffsetWidth::Narrow => "scratch_len",
+        OffsetWidth::Wide => "wide_scratch_len",
+    };
+
+    if !fields.is_empty() {
         let mut fields_scratch_len_tokens = rust::Tokens::new();
         for (i, field) in fields.iter().enumerate() {
             quote_in! { fields_scratch_len_tokens =>
-                $(&field_prefix)$(&field.name).scratch_len()
+                $(&field_prefix)$(&field.name).$scratch_len_method()
             };
             if i < fields.len() - 1 {
                 quote_in! { fields_scratch_len_tokens => $(" + ") };
@@ -370,7 +478,13 @@ pub fn rust_named_fields_encode(fields: &[NamedField], field_prefix: rust::Token
     encode_owned_tokens
 }
 
-pub fn rust_named_fields_decode(fields: &[NamedField]) -> rust::Tokens {
+/// Decode each field from `cursor`, recording the field (and enum variant, if any) in the path of
+/// any decode errors.
+pub fn rust_named_fields_decode(
+    type_name: &str,
+    variant_name: Option<&str>,
+    fields: &[NamedField],
+) -> rust::Tokens {
     let decode_trait = &rust::import("mproto", "Decode");
 
     let mut decode_owned_tokens = quote! {};
@@ -378,13 +492,50 @@ pub fn rust_named_fields_decode(fields: &[NamedField]) -> rust::Tokens {
     for field in fields {
         decode_owned_tokens = quote! {
             $decode_owned_tokens
-            let $(&field.name) = $decode_trait::decode(cursor)?;
+            let $(&field.name) = $decode_trait::decode(cursor)
+                .map_err(|e| $(rust_field_error_path(type_name, variant_name, field)))?;
         };
     }
 
     decode_owned_tokens
 }
 
+/// Verify each field at `cursor` against its owned type, see `Owned::verify`.
+pub fn rust_named_fields_verify(
+    cx: &CodegenCx,
+    type_name: &str,
+    variant_name: Option<&str>,
+    fields: &[NamedField],
+) -> rust::Tokens {
+    let owned_trait = &rust::import("mproto", "Owned");
+
+    let mut verify_tokens = quote! {};
+
+    for field in fields {
+        verify_tokens = quote! {
+            $verify_tokens
+            <$(rust_type_tokens(cx, &field.ty)) as $owned_trait>::verify(cursor)
+                .map_err(|e| $(rust_field_error_path(type_name, variant_name, field)))?;
+        };
+    }
+
+    verify_tokens
+}
+
+fn rust_field_error_path(
+    type_name: &str,
+    variant_name: Option<&str>,
+    field: &NamedField,
+) -> rust::Tokens {
+    match variant_name {
+        Some(variant_name) => quote! {
+            e.in_field($(quoted(type_name)), $(quoted(&field.name)))
+                .in_variant($(quoted(type_name)), $(quoted(variant_name)))
+        },
+        None => quote! { e.in_field($(quoted(type_name)), $(quoted(&field.name))) },
+    }
+}
+
 pub fn rust_named_fields_constructor(fields: &[NamedField]) -> rust::Tokens {
     let mut constructor_tokens = rust::Tokens::new();
     for field in fields {
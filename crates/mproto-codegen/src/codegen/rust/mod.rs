@@ -1,9 +1,13 @@
 use genco::prelude::*;
 
-use self::{common::lazy_type_requires_lifetime, rust_enum::rust_enum, rust_struct::rust_struct};
+use self::{
+    common::{enum_requires_heap, lazy_type_requires_lifetime, struct_requires_heap},
+    rust_enum::rust_enum,
+    rust_struct::rust_struct,
+};
 use crate::{
     ast,
-    codegen::{CodegenCx, ResolvedType},
+    codegen::{CodegenCx, ResolvedType, type_def_fingerprint},
 };
 
 pub use package::{rust_module_gen, rust_package_gen};
@@ -16,11 +20,56 @@ mod rust_struct;
 pub fn rust_type_def(cx: &CodegenCx, type_def: &ast::TypeDef) -> rust::Tokens {
     let cx = &cx.with_type_params(&type_def.params);
 
-    match &type_def.body {
-        ast::TypeBody::Struct(struct_def) => {
-            rust_struct(cx, &type_def.name, &type_def.params, struct_def)
+    let (type_tokens, requires_heap) = match &type_def.body {
+        ast::TypeBody::Struct(struct_def) => (
+            rust_struct(cx, &type_def.name, &type_def.params, struct_def),
+            struct_requires_heap(cx.db, struct_def),
+        ),
+        ast::TypeBody::Enum(enum_def) => (
+            rust_enum(cx, &type_def.name, &type_def.params, enum_def),
+            enum_requires_heap(cx.db, enum_def),
+        ),
+    };
+
+    quote! {
+        $type_tokens
+
+        $(rust_fingerprint_impl(cx, type_def, requires_heap))
+    }
+}
+
+fn rust_fingerprint_impl(
+    cx: &CodegenCx,
+    type_def: &ast::TypeDef,
+    requires_heap: bool,
+) -> rust::Tokens {
+    let fingerprint_trait = &rust::import("mproto", "Fingerprint");
+
+    let owned_cfg: rust::Tokens = if cx.is_package && requires_heap {
+        quote! { #[cfg(any(feature = "std", feature = "alloc"))] }
+    } else {
+        quote! {}
+    };
+
+    let fingerprint = format!("{:#018x}", type_def_fingerprint(cx, type_def));
+    let fingerprint_tokens = if type_def.params.is_empty() {
+        quote! { $fingerprint }
+    } else {
+        let type_args = type_def
+            .params
+            .iter()
+            .map(|param| quote! { $param::FINGERPRINT })
+            .collect::<Vec<_>>();
+        quote! {
+            $(rust::import("mproto", "fingerprint_with_args"))($fingerprint, &[$(for arg in type_args join (, ) => $arg)])
+        }
+    };
+
+    quote! {
+        $owned_cfg
+        impl$(rust_type_param_list(&type_def.params, None, Some(quote! { $fingerprint_trait }))) $fingerprint_trait for $(&type_def.name)$(rust_type_param_list(&type_def.params, None, None)) {
+            const FINGERPRINT: u64 = $fingerprint_tokens;
         }
-        ast::TypeBody::Enum(enum_def) => rust_enum(cx, &type_def.name, &type_def.params, enum_def),
     }
 }
 
@@ -128,16 +177,14 @@ pub fn rust_type_arg_list(
     args: &[ast::Type],
     lifetimes: Option<rust::Tokens>,
 ) -> rust::Tokens {
-    if args.len() == 0 {
+    if args.is_empty() {
         if let Some(lifetimes) = lifetimes {
             quote! { <$lifetimes> }
         } else {
             quote! {}
         }
     } else {
-        let lifetimes = lifetimes
-            .map(|l| quote! { $l,$(" ") })
-            .unwrap_or(rust::Tokens::new());
+        let lifetimes = lifetimes.map(|l| quote! { $l,$(" ") }).unwrap_or_default();
 
         let mut args_items: rust::Tokens = quote! {
             $(lifetimes)$(rust_type_tokens(cx, &args[0]))
@@ -155,17 +202,15 @@ pub fn rust_type_param_list(
     lifetimes: Option<rust::Tokens>,
     impl_trait: Option<rust::Tokens>,
 ) -> rust::Tokens {
-    if params.len() == 0 {
+    if params.is_empty() {
         if let Some(lifetimes) = lifetimes {
             quote! { <$lifetimes> }
         } else {
             Tokens::new()
         }
     } else {
-        let lifetimes = lifetimes
-            .map(|l| quote! { $l,$(" ") })
-            .unwrap_or(Tokens::new());
-        let impl_trait = impl_trait.map(|i| quote! { : $i }).unwrap_or(Tokens::new());
+        let lifetimes = lifetimes.map(|l| quote! { $l,$(" ") }).unwrap_or_default();
+        let impl_trait = impl_trait.map(|i| quote! { : $i }).unwrap_or_default();
 
         let mut tokens = quote! { <$(lifetimes)$(&params[0])$(&impl_trait) };
 
@@ -207,8 +252,8 @@ pub fn rust_type_default_value(cx: &CodegenCx, ty: &ast::Type) -> rust::Tokens {
         ast::Type::Defined { ident, .. } => {
             if let Some(type_def) = cx.db.lookup_type_def(ident) {
                 match &type_def.body {
-                    ast::TypeBody::Struct(s) => rust_struct_default_value(cx, &ident, s),
-                    ast::TypeBody::Enum(e) => rust_enum_default_value(cx, &ident, e),
+                    ast::TypeBody::Struct(s) => rust_struct_default_value(cx, ident, s),
+                    ast::TypeBody::Enum(e) => rust_enum_default_value(cx, ident, e),
                 }
             } else {
                 quote! { todo!() }
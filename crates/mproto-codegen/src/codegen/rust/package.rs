@@ -3,9 +3,9 @@ use std::path::Path;
 
 use genco::prelude::*;
 
-use crate::{ast::TypeDef, codegen::CodegenCx, Database, Module};
+use crate::{Database, Module, ast::TypeDef, codegen::CodegenCx};
 
-const CARGO_TOML: &'static str = include_str!("templates/cargo.toml");
+const CARGO_TOML: &str = include_str!("templates/cargo.toml");
 
 pub fn rust_package_gen(
     root_dir: impl AsRef<Path>,
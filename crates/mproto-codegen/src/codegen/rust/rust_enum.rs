@@ -3,16 +3,17 @@ use genco::prelude::*;
 use crate::{
     ast,
     codegen::{
-        enum_base_len, enum_variant_base_len,
+        CodegenCx, FormatBaseLen, MprotoRust, OffsetWidth, enum_base_len, enum_variant_base_len,
         rust::{
             common::{
                 enum_contains_float, enum_requires_heap, lazy_enum_requires_lifetime,
-                rust_named_fields_constructor, rust_named_fields_decode, rust_named_fields_encode,
-                rust_named_fields_lazy, rust_named_fields_owned, rust_named_fields_scratch_len,
+                rust_field_view_type_tokens, rust_named_fields_constructor,
+                rust_named_fields_decode, rust_named_fields_encode, rust_named_fields_lazy,
+                rust_named_fields_owned, rust_named_fields_scratch_len, rust_named_fields_verify,
+                rust_types_have_scratch,
             },
-            rust_type_param_list,
+            rust_type_param_list, rust_type_tokens,
         },
-        CodegenCx, MprotoRust,
     },
 };
 
@@ -50,7 +51,36 @@ fn rust_lazy_enum_variant(cx: &CodegenCx, name: &str, variant: &ast::EnumVariant
     }
 }
 
-fn rust_enum_variants_scratch_len(name: &str, e: &ast::Enum) -> rust::Tokens {
+fn rust_view_enum_variant(
+    cx: &CodegenCx,
+    name: &str,
+    variant: &ast::EnumVariant,
+    lifetime: &rust::Tokens,
+) -> rust::Tokens {
+    match variant {
+        ast::EnumVariant::Empty => {
+            quote! {
+                $name,
+            }
+        }
+        ast::EnumVariant::NamedFields { fields } => {
+            let mut view_field_tokens = rust::Tokens::new();
+            for field in fields {
+                view_field_tokens = quote! {
+                    $view_field_tokens
+                    $(&field.name): $(rust_field_view_type_tokens(cx, field, lifetime.clone())),
+                };
+            }
+            quote! {
+                $name {
+                    $view_field_tokens
+                },
+            }
+        }
+    }
+}
+
+fn rust_enum_variants_scratch_len(name: &str, e: &ast::Enum, width: OffsetWidth) -> rust::Tokens {
     let mut variants_scratch_len_tokens = rust::Tokens::new();
     for (variant_name, variant) in e.variants.iter() {
         match variant {
@@ -61,7 +91,7 @@ fn rust_enum_variants_scratch_len(name: &str, e: &ast::Enum) -> rust::Tokens {
                 };
             }
             ast::EnumVariant::NamedFields { fields } => {
-                let mut pattern_fields = quote! { $(fields.get(0).map(|f| &f.name)) };
+                let mut pattern_fields = quote! { $(fields.first().map(|f| &f.name)) };
                 for field in &fields[1..] {
                     pattern_fields = quote! { $pattern_fields, $(&field.name) };
                 }
@@ -69,7 +99,7 @@ fn rust_enum_variants_scratch_len(name: &str, e: &ast::Enum) -> rust::Tokens {
                 variants_scratch_len_tokens = quote! {
                     $variants_scratch_len_tokens
                     $(name)::$(variant_name) { $pattern_fields } => {
-                        $(rust_named_fields_scratch_len(fields, quote! { }))
+                        $(rust_named_fields_scratch_len(fields, quote! { }, width))
                     }
                 };
             }
@@ -88,13 +118,13 @@ fn rust_enum_variants_encode(cx: &CodegenCx, name: &str, e: &ast::Enum) -> rust:
                     $variants_encode_tokens
                     $(name)::$(variant_name) => {
                         cursor.base(1)[0] = $i;
-                        cursor.base(Self::BASE_LEN - 1).fill(0);
+                        cursor.base(cursor.format().base_len::<Self>() - 1).fill(0);
                     }
                 };
             }
             ast::EnumVariant::NamedFields { fields } => {
-                let variant_base_len = enum_variant_base_len::<MprotoRust>(cx, variant).as_tokens();
-                let mut pattern_fields = quote! { $(fields.get(0).map(|f| &f.name)) };
+                let variant_base_len = rust_enum_variant_base_len(cx, variant);
+                let mut pattern_fields = quote! { $(fields.first().map(|f| &f.name)) };
                 for field in &fields[1..] {
                     pattern_fields = quote! { $pattern_fields, $(&field.name) };
                 }
@@ -104,7 +134,7 @@ fn rust_enum_variants_encode(cx: &CodegenCx, name: &str, e: &ast::Enum) -> rust:
                     $(name)::$(variant_name) { $pattern_fields } => {
                         cursor.base(1)[0] = $i;
                         $(rust_named_fields_encode(fields, quote! { }))
-                        cursor.base(Self::BASE_LEN - 1 - ($variant_base_len)).fill(0);
+                        cursor.base(cursor.format().base_len::<Self>() - 1 - ($variant_base_len)).fill(0);
                     }
                 };
             }
@@ -114,6 +144,12 @@ fn rust_enum_variants_encode(cx: &CodegenCx, name: &str, e: &ast::Enum) -> rust:
     variants_encode_tokens
 }
 
+/// The base length of an enum variant in the wire format of the `cursor` in scope.
+fn rust_enum_variant_base_len(cx: &CodegenCx, variant: &ast::EnumVariant) -> rust::Tokens {
+    FormatBaseLen::<MprotoRust>::new(|width| enum_variant_base_len(cx, variant, width))
+        .as_tokens(&quote! { cursor.format() })
+}
+
 /// Generate code for a match statement over a mproto enum. The supplied function produces Rust
 /// tokens for a given enum variant.
 #[allow(unused)] // used to use this, I still think it might be useful later.
@@ -134,7 +170,7 @@ fn rust_enum_match_variant(
                 };
             }
             ast::EnumVariant::NamedFields { fields } => {
-                let mut pattern_fields = quote! { $(fields.get(0).map(|f| &f.name)) };
+                let mut pattern_fields = quote! { $(fields.first().map(|f| &f.name)) };
                 for field in &fields[1..] {
                     pattern_fields = quote! { $pattern_fields, $(&field.name) };
                 }
@@ -152,6 +188,36 @@ fn rust_enum_match_variant(
     out_tokens
 }
 
+fn rust_lazy_enum_variant_shorten(
+    cx: &CodegenCx,
+    name: &str,
+    variant_name: &str,
+    variant: &ast::EnumVariant,
+) -> rust::Tokens {
+    let owned_trait = &rust::import("mproto", "Owned");
+
+    match variant {
+        ast::EnumVariant::Empty => quote! {
+            $(name)Lazy::$(variant_name) => $(name)Lazy::$(variant_name),
+        },
+        ast::EnumVariant::NamedFields { fields } => {
+            let mut pattern_fields = rust::Tokens::new();
+            let mut shortened_fields = rust::Tokens::new();
+            for field in fields {
+                quote_in! { pattern_fields => $(&field.name), };
+                quote_in! { shortened_fields =>
+                    $(&field.name): <$(rust_type_tokens(cx, &field.ty)) as $owned_trait>::shorten_lazy($(&field.name)),
+                };
+            }
+            quote! {
+                $(name)Lazy::$(variant_name) { $pattern_fields } => $(name)Lazy::$(variant_name) {
+                    $shortened_fields
+                },
+            }
+        }
+    }
+}
+
 fn rust_lazy_enum_std_trait_impls(
     cx: &CodegenCx,
     name: &str,
@@ -259,6 +325,7 @@ pub fn rust_enum(
     let encode_cursor = &rust::import("mproto", "EncodeCursor");
     let decode_cursor = &rust::import("mproto", "DecodeCursor");
     let decode_error = &rust::import("mproto", "DecodeError");
+    let decode_error_kind = &rust::import("mproto", "DecodeErrorKind");
     let decode_result = &rust::import("mproto", "DecodeResult");
 
     let base_len_trait = &rust::import("mproto", "BaseLen");
@@ -266,6 +333,7 @@ pub fn rust_enum(
     let decode_trait = &rust::import("mproto", "Decode");
     let owned_trait = &rust::import("mproto", "Owned");
     let lazy_trait = &rust::import("mproto", "Lazy");
+    let verified = &rust::import("mproto", "Verified");
     let compat_trait = &rust::import("mproto", "Compatible");
     let try_from_trait = &rust::import("core::convert", "TryFrom");
 
@@ -297,6 +365,18 @@ pub fn rust_enum(
     let decode_lazy_impl_type_param_use_tokens =
         rust_type_param_list(type_params, lazy_enum_maybe_lifetime.clone(), None);
 
+    let has_scratch_tokens = rust_types_have_scratch(
+        cx,
+        e.variants
+            .iter()
+            .flat_map(|(_, variant)| match variant {
+                ast::EnumVariant::Empty => &[][..],
+                ast::EnumVariant::NamedFields { fields } => &fields[..],
+            })
+            .map(|field| &field.ty),
+    )
+    .unwrap_or_else(|| quote! { true });
+
     let owned_cfg: rust::Tokens = if cx.is_package && enum_requires_heap(cx.db, e) {
         quote! { #[cfg(any(feature = "std", feature = "alloc"))] }
     } else {
@@ -327,23 +407,51 @@ pub fn rust_enum(
         };
     }
 
-    let variants_scratch_len_tokens = rust_enum_variants_scratch_len(name, e);
+    // Lazy enums holding lazy values of their type parameters aren't known to be covariant, so
+    // shorten each of their fields.
+    let shorten_lazy_tokens = if type_params.is_empty() {
+        quote! { lazy }
+    } else {
+        let mut variants_shorten_tokens = rust::Tokens::new();
+        for (variant_name, variant) in &e.variants {
+            variants_shorten_tokens = quote! {
+                $variants_shorten_tokens
+                $(rust_lazy_enum_variant_shorten(cx, name, variant_name, variant))
+            };
+        }
+        quote! {
+            match lazy {
+                $variants_shorten_tokens
+            }
+        }
+    };
+
+    let invalid_tag_error: rust::Tokens = quote! {
+        $decode_error::new(
+            $decode_error_kind::InvalidEnumTag { tag: variant, type_name: $(quoted(name)) },
+            tag_offset,
+        )
+    };
+
+    let variants_scratch_len_tokens = rust_enum_variants_scratch_len(name, e, OffsetWidth::Narrow);
+    let variants_wide_scratch_len_tokens =
+        rust_enum_variants_scratch_len(name, e, OffsetWidth::Wide);
     let variants_encode_tokens = rust_enum_variants_encode(cx, name, e);
 
     let mut variants_decode_tokens = rust::Tokens::new();
     for (i, (variant_name, variant)) in e.variants.iter().enumerate() {
-        let variant_base_len = enum_variant_base_len::<MprotoRust>(cx, variant).as_tokens();
+        let variant_base_len = rust_enum_variant_base_len(cx, variant);
         let variant_decode: rust::Tokens = match variant {
             ast::EnumVariant::Empty => {
                 quote! {
-                    cursor.advance(Self::BASE_LEN - 1);
+                    cursor.advance(cursor.format().base_len::<Self>() - 1)?;
                     Ok($(name)::$(variant_name))
                 }
             }
             ast::EnumVariant::NamedFields { fields } => {
                 quote! {
-                    $(rust_named_fields_decode(fields))
-                    cursor.advance(Self::BASE_LEN - 1 - ($variant_base_len));
+                    $(rust_named_fields_decode(name, Some(variant_name), fields))
+                    cursor.advance(cursor.format().base_len::<Self>() - 1 - ($variant_base_len))?;
                     Ok($(name)::$(variant_name) {
                         $(rust_named_fields_constructor(fields))
                     })
@@ -361,18 +469,18 @@ pub fn rust_enum(
 
     let mut variants_decode_lazy_tokens = rust::Tokens::new();
     for (i, (variant_name, variant)) in e.variants.iter().enumerate() {
-        let variant_base_len = enum_variant_base_len::<MprotoRust>(cx, variant).as_tokens();
+        let variant_base_len = rust_enum_variant_base_len(cx, variant);
         let variant_decode: rust::Tokens = match variant {
             ast::EnumVariant::Empty => {
                 quote! {
-                    cursor.advance(Self::BASE_LEN - 1);
+                    cursor.advance(cursor.format().base_len::<Self>() - 1)?;
                     Ok($(name)Lazy::$(variant_name))
                 }
             }
             ast::EnumVariant::NamedFields { fields } => {
                 quote! {
-                    $(rust_named_fields_decode(fields))
-                    cursor.advance(Self::BASE_LEN - 1 - ($variant_base_len));
+                    $(rust_named_fields_decode(name, Some(variant_name), fields))
+                    cursor.advance(cursor.format().base_len::<Self>() - 1 - ($variant_base_len))?;
                     Ok($(name)Lazy::$(variant_name) {
                         $(rust_named_fields_constructor(fields))
                     })
@@ -388,6 +496,101 @@ pub fn rust_enum(
         };
     }
 
+    let mut variants_verify_tokens = rust::Tokens::new();
+    for (i, (variant_name, variant)) in e.variants.iter().enumerate() {
+        let variant_base_len = rust_enum_variant_base_len(cx, variant);
+        let variant_verify: rust::Tokens = match variant {
+            ast::EnumVariant::Empty => {
+                quote! {
+                    cursor.advance(cursor.format().base_len::<Self>() - 1)
+                }
+            }
+            ast::EnumVariant::NamedFields { fields } => {
+                quote! {
+                    $(rust_named_fields_verify(cx, name, Some(variant_name), fields))
+                    cursor.advance(cursor.format().base_len::<Self>() - 1 - ($variant_base_len))
+                }
+            }
+        };
+
+        variants_verify_tokens = quote! {
+            $variants_verify_tokens
+            $i => {
+                $variant_verify
+            }
+        };
+    }
+
+    // Lazy enums without a lifetime only contain fields whose views don't borrow the buffer.
+    let view_lifetime = lazy_enum_maybe_lifetime
+        .clone()
+        .unwrap_or_else(|| quote! { 'static });
+
+    let mut view_variant_tokens = rust::Tokens::new();
+    let mut variants_view_tokens = rust::Tokens::new();
+    let mut variants_view_debug_tokens = rust::Tokens::new();
+    for (variant_name, variant) in &e.variants {
+        view_variant_tokens = quote! {
+            $view_variant_tokens
+            $(rust_view_enum_variant(cx, variant_name, variant, &view_lifetime))
+        };
+
+        let variant_view: rust::Tokens = match variant {
+            ast::EnumVariant::Empty => quote! {
+                $(name)Lazy::$(variant_name) => $(name)View::$(variant_name),
+            },
+            ast::EnumVariant::NamedFields { fields } => {
+                let mut pattern_fields = rust::Tokens::new();
+                for field in fields {
+                    quote_in! { pattern_fields => $(&field.name), };
+                }
+                quote! {
+                    $(name)Lazy::$(variant_name) { $pattern_fields } => {
+                        $(name)View::$(variant_name) {
+                            $(
+                                fields.iter().fold(rust::Tokens::new(), |t, field| quote! {
+                                    $t
+                                    $(&field.name): $verified::new_unchecked($(&field.name)).view(),
+                                })
+                            )
+                        }
+                    }
+                }
+            }
+        };
+
+        variants_view_tokens = quote! {
+            $variants_view_tokens
+            $variant_view
+        };
+
+        let variant_view_debug: rust::Tokens = match variant {
+            ast::EnumVariant::Empty => quote! {
+                $(name)View::$(variant_name) => f.write_str($(quoted(variant_name))),
+            },
+            ast::EnumVariant::NamedFields { fields } => {
+                let mut pattern_fields = rust::Tokens::new();
+                let mut debug_fields = rust::Tokens::new();
+                for field in fields {
+                    quote_in! { pattern_fields => $(&field.name), };
+                    quote_in! { debug_fields => .field($(quoted(&field.name)), $(&field.name)) };
+                }
+                quote! {
+                    $(name)View::$(variant_name) { $pattern_fields } => {
+                        f.debug_struct($(quoted(variant_name)))
+                            $debug_fields
+                            .finish()
+                    }
+                }
+            }
+        };
+
+        variants_view_debug_tokens = quote! {
+            $variants_view_debug_tokens
+            $variant_view_debug
+        };
+    }
+
     let mut variants_lazy_to_owned_tokens = rust::Tokens::new();
     for (variant_name, variant) in &e.variants {
         let variant_try_from: rust::Tokens = match variant {
@@ -430,12 +633,32 @@ pub fn rust_enum(
                 fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
                     $try_from_trait::try_from(lazy)
                 }
+
+                fn shorten_lazy<'b, 'a: 'b>(lazy: Self::Lazy<'a>) -> Self::Lazy<'b> {
+                    $shorten_lazy_tokens
+                }
+
+                fn verify(cursor: &$decode_cursor<'_>) -> $decode_result<()> {
+                    let tag_offset = cursor.offset();
+                    let variant = cursor.base(1)?[0];
+                    match variant {
+                        $variants_verify_tokens
+                        _ => { Err($(&invalid_tag_error)) }
+                    }
+                }
             }
 
             impl$(
                 rust_type_param_list(type_params, Some(quote! { 'a }), Some(quote! { $owned_trait }))
             ) $lazy_trait<'a> for $(name)Lazy$(&decode_lazy_impl_type_param_use_tokens) {
                 type Owned = $(name)$(owned_type_param_tokens);
+                type View = $(name)View$(&decode_lazy_impl_type_param_use_tokens);
+
+                fn view(verified: $verified<Self>) -> Self::View {
+                    match verified.into_inner() {
+                        $variants_view_tokens
+                    }
+                }
             }
         }
     };
@@ -452,6 +675,26 @@ pub fn rust_enum(
             $buf_variant_tokens
         }
 
+        pub enum $(name)View$(buf_type_param_tokens) {
+            $view_variant_tokens
+        }
+
+        impl$(buf_type_param_tokens) Copy for $(name)View$(buf_type_arg_tokens) { }
+
+        impl$(buf_type_param_tokens) Clone for $(name)View$(buf_type_arg_tokens) {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl$(buf_type_param_tokens) core::fmt::Debug for $(name)View$(buf_type_arg_tokens) {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    $variants_view_debug_tokens
+                }
+            }
+        }
+
         impl$(buf_type_param_tokens) $compat_trait<$(name)Lazy$(buf_type_arg_tokens)> for $(name)Lazy$(buf_type_arg_tokens) { }
         $(&owned_cfg)
         impl$(buf_type_param_tokens) $compat_trait<$(name)Lazy$(buf_type_arg_tokens)> for $(name)$(owned_type_param_tokens) { }
@@ -469,7 +712,9 @@ pub fn rust_enum(
         ) $base_len_trait for $(name)$(
             rust_type_param_list(type_params, None, None)
         ) {
-            const BASE_LEN: usize = $(enum_base_len::<MprotoRust>(cx, e).as_tokens());
+            const BASE_LEN: usize = $(enum_base_len::<MprotoRust>(cx, e, OffsetWidth::Narrow).as_tokens());
+            const WIDE_BASE_LEN: usize = $(enum_base_len::<MprotoRust>(cx, e, OffsetWidth::Wide).as_tokens());
+            const HAS_SCRATCH: bool = $(&has_scratch_tokens);
         }
 
         impl$(&encode_impl_type_param_decl_tokens) $encode_trait for $(name)$(encode_impl_type_param_use_tokens) {
@@ -479,6 +724,12 @@ pub fn rust_enum(
                 }
             }
 
+            fn wide_scratch_len(&self) -> usize {
+                match self {
+                    $variants_wide_scratch_len_tokens
+                }
+            }
+
             fn encode(&self, cursor: &mut $encode_cursor) {
                 match self {
                     $variants_encode_tokens
@@ -489,10 +740,11 @@ pub fn rust_enum(
         $(&owned_cfg)
         impl$(&decode_impl_type_param_decl_tokens) $decode_trait<'a> for $(name)$(&decode_owned_impl_type_param_use_tokens) {
             fn decode(cursor: &$decode_cursor<'a>) -> $decode_result<Self> {
-                let variant = cursor.base(1)[0];
+                let tag_offset = cursor.offset();
+                let variant = cursor.base(1)?[0];
                 match variant {
                     $variants_decode_tokens
-                    _ => { Err($decode_error) }
+                    _ => { Err($(&invalid_tag_error)) }
                 }
             }
         }
@@ -502,7 +754,9 @@ pub fn rust_enum(
         ) $base_len_trait for $(name)Lazy$(
             rust_type_param_list(type_params, lazy_enum_maybe_lifetime.clone(), None)
         ) {
-            const BASE_LEN: usize = $(enum_base_len::<MprotoRust>(cx, e).as_tokens());
+            const BASE_LEN: usize = $(enum_base_len::<MprotoRust>(cx, e, OffsetWidth::Narrow).as_tokens());
+            const WIDE_BASE_LEN: usize = $(enum_base_len::<MprotoRust>(cx, e, OffsetWidth::Wide).as_tokens());
+            const HAS_SCRATCH: bool = $(&has_scratch_tokens);
         }
 
         impl$(
@@ -512,7 +766,13 @@ pub fn rust_enum(
         ) {
             fn scratch_len(&self) -> usize {
                 match self {
-                    $(rust_enum_variants_scratch_len(&format!("{name}Lazy"), e))
+                    $(rust_enum_variants_scratch_len(&format!("{name}Lazy"), e, OffsetWidth::Narrow))
+                }
+            }
+
+            fn wide_scratch_len(&self) -> usize {
+                match self {
+                    $(rust_enum_variants_scratch_len(&format!("{name}Lazy"), e, OffsetWidth::Wide))
                 }
             }
 
@@ -527,10 +787,11 @@ pub fn rust_enum(
             rust_type_param_list(type_params, Some(quote! { 'a }), Some(quote! { $owned_trait }))
         ) $decode_trait<'a> for $(name)Lazy$(&decode_lazy_impl_type_param_use_tokens) {
             fn decode(cursor: &$decode_cursor<'a>) -> $decode_result<Self> {
-                let variant = cursor.base(1)[0];
+                let tag_offset = cursor.offset();
+                let variant = cursor.base(1)?[0];
                 match variant {
                     $variants_decode_lazy_tokens
-                    _ => { Err($decode_error) }
+                    _ => { Err($(&invalid_tag_error)) }
                 }
             }
         }
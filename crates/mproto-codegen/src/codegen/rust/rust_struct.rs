@@ -3,18 +3,20 @@ use genco::prelude::*;
 use crate::{
     ast,
     codegen::{
+        CodegenCx, FormatBaseLen, MprotoLang, MprotoRust, OffsetWidth, TypeBaseLen,
         name_util::snake_to_upper_camel_case,
         rust::{
             common::{
-                rust_lazy_field_decode, rust_named_fields_constructor, rust_named_fields_decode,
-                rust_named_fields_encode, rust_named_fields_lazy_phantom,
-                rust_named_fields_lazy_phantom_constructor, rust_named_fields_owned,
-                rust_named_fields_scratch_len, struct_contains_float, struct_requires_heap,
+                rust_field_lazy_type_tokens, rust_field_view_type_tokens, rust_lazy_field_decode,
+                rust_named_fields_constructor, rust_named_fields_decode, rust_named_fields_encode,
+                rust_named_fields_lazy_phantom, rust_named_fields_lazy_phantom_constructor,
+                rust_named_fields_owned, rust_named_fields_scratch_len, rust_named_fields_verify,
+                rust_types_have_scratch, struct_contains_float, struct_requires_heap,
+                type_is_fixed_size,
             },
             rust_type_lazy_tokens, rust_type_param_list, rust_type_tokens,
         },
-        struct_base_len, type_base_len, type_uses_type_param, CodegenCx, MprotoLang, MprotoRust,
-        TypeBaseLen,
+        struct_base_len, type_base_len, type_uses_type_param,
     },
 };
 
@@ -28,6 +30,8 @@ pub fn rust_struct(
     let decode_cursor = &rust::import("mproto", "DecodeCursor");
     let decode_error = &rust::import("mproto", "DecodeError");
     let decode_result = &rust::import("mproto", "DecodeResult");
+    let wire_format = &rust::import("mproto", "WireFormat");
+    let decode_limits = &rust::import("mproto", "DecodeLimits");
 
     let base_len_trait = &rust::import("mproto", "BaseLen");
     let encode_trait = &rust::import("mproto", "Encode");
@@ -35,12 +39,16 @@ pub fn rust_struct(
     let compat_trait = &rust::import("mproto", "Compatible");
     let owned_trait = &rust::import("mproto", "Owned");
     let lazy_trait = &rust::import("mproto", "Lazy");
+    let verified = &rust::import("mproto", "Verified");
     let try_from_trait = &rust::import("core::convert", "TryFrom");
 
     let owned_field_tokens = rust_named_fields_owned(cx, &s.fields, true);
-    let fields_scratch_len_tokens = rust_named_fields_scratch_len(&s.fields, quote! { self. });
+    let fields_scratch_len_tokens =
+        rust_named_fields_scratch_len(&s.fields, quote! { self. }, OffsetWidth::Narrow);
+    let fields_wide_scratch_len_tokens =
+        rust_named_fields_scratch_len(&s.fields, quote! { self. }, OffsetWidth::Wide);
     let encode_owned_tokens = rust_named_fields_encode(&s.fields, quote! { self. });
-    let decode_owned_tokens = rust_named_fields_decode(&s.fields);
+    let decode_owned_tokens = rust_named_fields_decode(name, None, &s.fields);
 
     let owned_type_param_tokens = &rust_type_param_list(type_params, None, None);
     let buf_type_param_tokens = &rust_type_param_list(type_params, Some(quote! { 'a }), None);
@@ -64,17 +72,53 @@ pub fn rust_struct(
         rust_type_param_list(type_params, Some(quote! { 'a }), None);
 
     let mut buf_method_tokens = rust::Tokens::new();
-    let mut field_offset = TypeBaseLen::<MprotoRust>::constant(0);
+    let mut view_method_tokens = rust::Tokens::new();
+    let mut view_debug_field_tokens = rust::Tokens::new();
+    let mut field_offset = FormatBaseLen::<MprotoRust>::constant(0);
     for field in &s.fields {
         buf_method_tokens = quote! {
             $buf_method_tokens
 
-            $(rust_lazy_decoder_method(cx, field, field_offset.as_tokens()))
+            $(rust_lazy_decoder_method(cx, name, field, field_offset.as_tokens(&quote! { self.format })))
         };
+        view_method_tokens = quote! {
+            $view_method_tokens
 
-        field_offset = field_offset.merge(type_base_len(cx, &field.ty));
+            $(rust_view_accessor_method(cx, field, field_offset.as_tokens(&quote! { self.0.format })))
+        };
+        quote_in! { view_debug_field_tokens =>
+            .field($(quoted(&field.name)), &self.$(&field.name)())
+        };
+
+        field_offset = field_offset.merge(FormatBaseLen::new(|width| {
+            type_base_len(cx, &field.ty, width)
+        }));
     }
 
+    let has_scratch = rust_types_have_scratch(cx, s.fields.iter().map(|field| &field.ty));
+    let has_scratch_tokens = has_scratch.clone().unwrap_or_else(|| quote! { true });
+    // Lazy values without scratch space are re-encoded by copying their base area.
+    let (lazy_copy_scratch_len_tokens, lazy_copy_encode_tokens) =
+        if has_scratch.is_some() && !s.fields.is_empty() {
+            (
+                quote! {
+                    if !Self::HAS_SCRATCH {
+                        return 0;
+                    }
+                },
+                quote! {
+                    if !Self::HAS_SCRATCH {
+                        cursor
+                            .base(Self::BASE_LEN)
+                            .copy_from_slice(&self.buffer[self.offset..][..Self::BASE_LEN]);
+                        return;
+                    }
+                },
+            )
+        } else {
+            (quote! {}, quote! {})
+        };
+
     let owned_cfg: rust::Tokens = if cx.is_package && struct_requires_heap(cx.db, s) {
         quote! { #[cfg(any(feature = "std", feature = "alloc"))] }
     } else {
@@ -92,6 +136,17 @@ pub fn rust_struct(
         owned_derive_impls = quote! { $owned_derive_impls, Default };
     }
 
+    let decode_cursor_param = if !s.fields.is_empty() {
+        &quote! { cursor: &$decode_cursor<'a> }
+    } else {
+        &quote! { _: &$decode_cursor<'a> }
+    };
+    let verify_cursor_param = if !s.fields.is_empty() {
+        &quote! { cursor: &$decode_cursor<'_> }
+    } else {
+        &quote! { _: &$decode_cursor<'_> }
+    };
+
     let owned_impl = {
         quote! {
             impl$(
@@ -102,12 +157,56 @@ pub fn rust_struct(
                 fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
                     $try_from_trait::try_from(lazy)
                 }
+
+                fn shorten_lazy<'b, 'a: 'b>(lazy: Self::Lazy<'a>) -> Self::Lazy<'b> {
+                    lazy
+                }
+
+                fn verify($verify_cursor_param) -> $decode_result<()> {
+                    $(rust_named_fields_verify(cx, name, None, &s.fields))
+                    Ok(())
+                }
             }
 
             impl$(
                 rust_type_param_list(type_params, Some(quote! { 'a }), Some(quote! { $owned_trait }))
             ) $lazy_trait<'a> for $(name)Lazy$(&decode_lazy_impl_type_param_use_tokens) {
                 type Owned = $(name)$(owned_type_param_tokens);
+                type View = $(name)View$(&decode_lazy_impl_type_param_use_tokens);
+
+                fn view(verified: $verified<Self>) -> Self::View {
+                    $(name)View(verified.into_inner())
+                }
+            }
+
+            pub struct $(name)View$(buf_type_param_tokens)($(name)Lazy$(buf_type_param_tokens));
+
+            impl$(
+                rust_type_param_list(type_params, Some(quote! { 'a }), Some(quote! { $owned_trait }))
+            ) $(name)View$(&decode_lazy_impl_type_param_use_tokens) {
+                $view_method_tokens
+
+                pub fn lazy(&self) -> $verified<$(name)Lazy$(&decode_lazy_impl_type_param_use_tokens)> {
+                    $verified::new_unchecked(self.0)
+                }
+            }
+
+            impl$(buf_type_param_tokens) Copy for $(name)View$(buf_type_param_tokens) { }
+
+            impl$(buf_type_param_tokens) Clone for $(name)View$(buf_type_param_tokens) {
+                fn clone(&self) -> Self {
+                    *self
+                }
+            }
+
+            impl$(
+                rust_type_param_list(type_params, Some(quote! { 'a }), Some(quote! { $owned_trait }))
+            ) core::fmt::Debug for $(name)View$(&decode_lazy_impl_type_param_use_tokens) {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.debug_struct($(quoted(format!("{name}View"))))
+                        $view_debug_field_tokens
+                        .finish()
+                }
             }
         }
     };
@@ -121,11 +220,6 @@ pub fn rust_struct(
     } else {
         &quote! { _: &mut $encode_cursor }
     };
-    let decode_cursor_param = if !s.fields.is_empty() {
-        &quote! { cursor: &$decode_cursor<'a> }
-    } else {
-        &quote! { _: &$decode_cursor<'a> }
-    };
 
     quote! {
         $(register(encode_trait))
@@ -140,6 +234,9 @@ pub fn rust_struct(
         pub struct $(name)Lazy$(buf_type_param_tokens) {
             buffer: &'a [u8],
             offset: usize,
+            format: $wire_format,
+            limits: $decode_limits,
+            depth: usize,
             $(rust_named_fields_lazy_phantom(type_params))
         }
 
@@ -159,14 +256,19 @@ pub fn rust_struct(
         impl<
             $(&generic_fields.type_params)
         > $base_len_trait for $(name)Gen<$(&generic_fields.type_args)> {
-            const BASE_LEN: usize = $(generic_struct_base_len.as_tokens());
+            const BASE_LEN: usize = $(generic_struct_base_len.get(OffsetWidth::Narrow).as_tokens());
+            const WIDE_BASE_LEN: usize = $(generic_struct_base_len.get(OffsetWidth::Wide).as_tokens());
         }
 
         impl<
             $(&generic_fields.type_params)
         > $encode_trait for $(name)Gen<$(&generic_fields.type_args)> {
             fn scratch_len(&self) -> usize {
-                $(rust_named_fields_scratch_len(&s.fields, quote! { self. }))
+                $(&fields_scratch_len_tokens)
+            }
+
+            fn wide_scratch_len(&self) -> usize {
+                $(&fields_wide_scratch_len_tokens)
             }
 
             fn encode(&self, $encode_cursor_param) {
@@ -199,7 +301,9 @@ pub fn rust_struct(
         ) $base_len_trait for $(name)$(
             rust_type_param_list(type_params, None, None)
         ) {
-            const BASE_LEN: usize = $(struct_base_len::<MprotoRust>(cx, s).as_tokens());
+            const BASE_LEN: usize = $(struct_base_len::<MprotoRust>(cx, s, OffsetWidth::Narrow).as_tokens());
+            const WIDE_BASE_LEN: usize = $(struct_base_len::<MprotoRust>(cx, s, OffsetWidth::Wide).as_tokens());
+            const HAS_SCRATCH: bool = $(&has_scratch_tokens);
         }
 
         impl$(encode_impl_type_param_decl_tokens) $encode_trait for $(name)$(encode_impl_type_param_use_tokens) {
@@ -207,6 +311,10 @@ pub fn rust_struct(
                 $(&fields_scratch_len_tokens)
             }
 
+            fn wide_scratch_len(&self) -> usize {
+                $(&fields_wide_scratch_len_tokens)
+            }
+
             fn encode(&self, $encode_cursor_param) {
                 $(&encode_owned_tokens)
             }
@@ -228,7 +336,9 @@ pub fn rust_struct(
         ) $base_len_trait for $(name)Lazy$(
             rust_type_param_list(type_params, Some(quote! { 'a }), None)
         ) {
-            const BASE_LEN: usize = $(struct_base_len::<MprotoRust>(cx, s).as_tokens());
+            const BASE_LEN: usize = $(struct_base_len::<MprotoRust>(cx, s, OffsetWidth::Narrow).as_tokens());
+            const WIDE_BASE_LEN: usize = $(struct_base_len::<MprotoRust>(cx, s, OffsetWidth::Wide).as_tokens());
+            const HAS_SCRATCH: bool = $(&has_scratch_tokens);
         }
 
         impl$(
@@ -237,10 +347,17 @@ pub fn rust_struct(
             rust_type_param_list(type_params, Some(quote! { 'a }), None)
         ) {
             fn scratch_len(&self) -> usize {
-                $(rust_named_fields_lazy_scratch_len(cx, &s.fields))
+                $(&lazy_copy_scratch_len_tokens)
+                $(rust_named_fields_lazy_scratch_len(cx, &s.fields, OffsetWidth::Narrow))
+            }
+
+            fn wide_scratch_len(&self) -> usize {
+                $(&lazy_copy_scratch_len_tokens)
+                $(rust_named_fields_lazy_scratch_len(cx, &s.fields, OffsetWidth::Wide))
             }
 
             fn encode(&self, $encode_cursor_param) {
+                $(&lazy_copy_encode_tokens)
                 $(rust_named_fields_lazy_encode(cx, &s.fields))
             }
         }
@@ -250,10 +367,13 @@ pub fn rust_struct(
         ) $decode_trait<'a> for $(name)Lazy$(&decode_lazy_impl_type_param_use_tokens) {
             fn decode(cursor: &$decode_cursor<'a>) -> $decode_result<Self> {
                 let offset = cursor.offset();
-                cursor.advance(Self::BASE_LEN);
+                cursor.advance(cursor.format().base_len::<Self>())?;
                 Ok($(name)Lazy {
                     buffer: cursor.buffer(),
                     offset,
+                    format: cursor.format(),
+                    limits: *cursor.limits(),
+                    depth: cursor.depth(),
                     $(rust_named_fields_lazy_phantom_constructor(type_params))
                 })
             }
@@ -268,81 +388,270 @@ pub fn rust_struct(
             type Error = $decode_error;
 
             fn try_from(other: $(name)Lazy$(rust_type_param_list(type_params, Some(quote! { 'a }), None))) -> Result<Self, Self::Error> {
-                let cursor = $decode_cursor::at_offset(other.buffer, other.offset);
+                let cursor = $decode_cursor::at_offset(other.buffer, other.offset)
+                    .with_format(other.format)
+                    .with_limits_at_depth(other.limits, other.depth);
                 $decode_trait::decode(&cursor)
             }
         }
 
         $(rust_lazy_struct_std_trait_impls(name, type_params, s))
+
+        $(if type_params.is_empty() {
+            $(rust_struct_mut(cx, name, s))
+        })
+    }
+}
+
+/// A `FooMut` view over an encoded struct, for changing its fixed-size fields in place.
+fn rust_struct_mut(cx: &CodegenCx, name: &str, s: &ast::Struct) -> rust::Tokens {
+    let decode_cursor = &rust::import("mproto", "DecodeCursor");
+    let decode_result = &rust::import("mproto", "DecodeResult");
+    let wire_format = &rust::import("mproto", "WireFormat");
+    let decode_limits = &rust::import("mproto", "DecodeLimits");
+
+    let mut method_tokens = rust::Tokens::new();
+    let mut field_offset = FormatBaseLen::<MprotoRust>::constant(0);
+    for field in &s.fields {
+        let field_method_tokens = rust_mut_field_methods(
+            cx,
+            name,
+            field,
+            field_offset.as_tokens(&quote! { self.format }),
+        );
+        if !field_method_tokens.is_empty() {
+            quote_in! { method_tokens =>
+                $['\n']
+                $field_method_tokens
+            };
+        }
+        field_offset = field_offset.merge(FormatBaseLen::new(|width| {
+            type_base_len(cx, &field.ty, width)
+        }));
+    }
+    let struct_base_len = FormatBaseLen::<MprotoRust>::new(|width| struct_base_len(cx, s, width));
+
+    quote! {
+        pub struct $(name)Mut<'a> {
+            buffer: &'a mut [u8],
+            offset: usize,
+            format: $wire_format,
+        }
+
+        impl<'a> $(name)Mut<'a> {
+            pub fn new(buffer: &'a mut [u8]) -> $decode_result<Self> {
+                Self::at_offset(buffer, 0, $wire_format::Absolute)
+            }
+
+            pub fn at_offset(buffer: &'a mut [u8], offset: usize, format: $wire_format) -> $decode_result<Self> {
+                $decode_cursor::at_offset(buffer, offset)
+                    .advance($(struct_base_len.as_tokens(&quote! { format })))?;
+                Ok(Self { buffer, offset, format })
+            }
+
+            pub fn as_lazy(&self) -> $(name)Lazy<'_> {
+                $(name)Lazy {
+                    buffer: self.buffer,
+                    offset: self.offset,
+                    format: self.format,
+                    limits: $decode_limits::DEFAULT,
+                    depth: 0,
+                }
+            }
+            $method_tokens
+        }
+    }
+}
+
+fn rust_mut_field_methods(
+    cx: &CodegenCx,
+    type_name: &str,
+    field: &ast::NamedField,
+    field_offset: rust::Tokens,
+) -> rust::Tokens {
+    let encode_trait = &rust::import("mproto", "Encode");
+    let encode_cursor = &rust::import("mproto", "EncodeCursor");
+    let decode_result = &rust::import("mproto", "DecodeResult");
+    let list_mut = &rust::import("mproto", "ListMut");
+
+    if type_is_fixed_size(cx.db, &field.ty) {
+        let field_ty = &rust_type_tokens(cx, &field.ty);
+        let nested_mut_method = match &field.ty {
+            ast::Type::Defined { ident, .. }
+                if matches!(
+                    cx.db.lookup_type_def(ident).map(|type_def| &type_def.body),
+                    Some(ast::TypeBody::Struct(_))
+                ) =>
+            {
+                let mut_ident = ast::QualifiedIdentifier {
+                    name: format!("{}Mut", ident.name),
+                    module: ident.module.clone(),
+                };
+                quote! {
+                    pub fn $(&field.name)_mut(&mut self) -> $(cx.rust_import_qualified(&mut_ident))<'_> {
+                        $(cx.rust_import_qualified(&mut_ident))::at_offset(
+                            self.buffer,
+                            self.offset + $(&field_offset),
+                            self.format,
+                        )
+                        .expect("field lies within the struct's base area")
+                    }
+                }
+            }
+            _ => quote! {},
+        };
+
+        quote! {
+            pub fn set_$(&field.name)(&mut self, value: $field_ty) {
+                let base_len = $(type_base_len::<MprotoRust>(cx, &field.ty, OffsetWidth::Narrow).as_tokens());
+                let base = &mut self.buffer[self.offset + $(&field_offset)..][..base_len];
+                $encode_trait::encode(&value, &mut $encode_cursor::new::<$field_ty>(base));
+            }
+            $(if !nested_mut_method.is_empty() {
+                $['\n']
+                $nested_mut_method
+            })
+        }
+    } else if let ast::Type::Primitive(ast::PrimitiveType::List(item_ty)) = &field.ty {
+        if !type_is_fixed_size(cx.db, item_ty) {
+            return quote! {};
+        }
+
+        quote! {
+            pub fn $(&field.name)_mut(&mut self) -> $decode_result<$list_mut<'_, $(rust_type_tokens(cx, item_ty))>> {
+                $list_mut::at_offset(self.buffer, self.offset + $field_offset, self.format)
+                    .map_err(|e| e.in_field($(quoted(type_name)), $(quoted(&field.name))))
+            }
+        }
+    } else {
+        quote! {}
     }
 }
 
 pub fn rust_lazy_decoder_method(
     cx: &CodegenCx,
+    type_name: &str,
     field: &ast::NamedField,
     field_offset: rust::Tokens,
 ) -> rust::Tokens {
     let decode_result = &rust::import("mproto", "DecodeResult");
 
+    let spliced_method = if let ast::Type::Primitive(ast::PrimitiveType::Box(inner_ty)) = &field.ty
+    {
+        let decode_trait = &rust::import("mproto", "Decode");
+        let decode_cursor = &rust::import("mproto", "DecodeCursor");
+        let box_lazy = &rust::import("mproto", "BoxLazy");
+        let spliced_box = &rust::import("mproto", "SplicedBox");
+        let inner_ty = rust_type_tokens(cx, inner_ty);
+
+        quote! {
+            pub fn $(&field.name)_spliced(&self) -> $decode_result<$spliced_box<'a, $(&inner_ty)>> {
+                $decode_trait::decode(
+                    &$decode_cursor::at_offset(self.buffer, self.offset + $(&field_offset))
+                        .with_format(self.format)
+                        .with_limits_at_depth(self.limits, self.depth)
+                )
+                .and_then(|boxed: $box_lazy<'a, $(&inner_ty)>| boxed.spliced())
+                .map_err(|e| e.in_field($(quoted(type_name)), $(quoted(&field.name))))
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     quote! {
         pub fn $(&field.name)(&self) -> $decode_result<$(rust_type_lazy_tokens(cx, &field.ty))> {
             $(rust_lazy_field_decode(field, field_offset))
+                .map_err(|e| e.in_field($(quoted(type_name)), $(quoted(&field.name))))
         }
+
+        $spliced_method
     }
 }
 
-// TODO unwrapping the decoded fields is not ideal. We could change scratch_len and encode method
+fn rust_view_accessor_method(
+    cx: &CodegenCx,
+    field: &ast::NamedField,
+    field_offset: rust::Tokens,
+) -> rust::Tokens {
+    let decode_trait = &rust::import("mproto", "Decode");
+    let decode_cursor = &rust::import("mproto", "DecodeCursor");
+    let verified = &rust::import("mproto", "Verified");
+
+    quote! {
+        pub fn $(&field.name)(&self) -> $(rust_field_view_type_tokens(cx, field, quote! { 'a })) {
+            $verified::<$(rust_field_lazy_type_tokens(cx, field))>::assume_ok($decode_trait::decode(
+                &$decode_cursor::at_offset(self.0.buffer, self.0.offset + $field_offset)
+                    .with_format(self.0.format)
+                    .with_limits_at_depth(self.0.limits, self.0.depth),
+            ))
+            .view()
+        }
+    }
+}
+
+// TODO unwrapping the copied fields is not ideal. We could change scratch_len and encode method
 // signatures to return a new `EncodeResult<()>` type but this would be a big change to the API.
 // And most uses of these methods are infallible, so it would be an annoyance.
+/// Re-encode each of a lazy struct's fields with `Owned::copy_encoded`, which copies their scratch
+/// regions rather than decoding them.
 fn rust_named_fields_lazy_encode(cx: &CodegenCx, fields: &[ast::NamedField]) -> rust::Tokens {
-    let mut out_tokens = quote! {};
-    let mut field_offset = TypeBaseLen::<MprotoRust>::constant(0);
+    let owned_trait = &rust::import("mproto", "Owned");
+    if fields.is_empty() {
+        return quote! {};
+    }
 
+    let mut out_tokens = rust_lazy_fields_cursor();
     for field in fields {
-        let field_ty = rust_type_lazy_tokens(cx, &field.ty);
-
         out_tokens = quote! {
             $out_tokens
-            let $(&field.name): $field_ty = $(
-                rust_lazy_field_decode(field, field_offset.as_tokens())
-            ).unwrap();
+            <$(rust_type_tokens(cx, &field.ty)) as $owned_trait>::copy_encoded(&fields_cursor, cursor).unwrap();
         };
-
-        field_offset = field_offset.merge(type_base_len(cx, &field.ty));
     }
 
-    out_tokens = quote! {
-        $out_tokens
-        $(rust_named_fields_encode(fields, quote! { }))
-    };
-
     out_tokens
 }
 
-// TODO unwrapping the decoded fields is not ideal - see comment at rust_named_fields_lazy_encode
-fn rust_named_fields_lazy_scratch_len(cx: &CodegenCx, fields: &[ast::NamedField]) -> rust::Tokens {
-    let mut out_tokens = quote! {};
-    let mut field_offset = TypeBaseLen::<MprotoRust>::constant(0);
+// TODO unwrapping the copied fields is not ideal - see comment at rust_named_fields_lazy_encode
+fn rust_named_fields_lazy_scratch_len(
+    cx: &CodegenCx,
+    fields: &[ast::NamedField],
+    width: OffsetWidth,
+) -> rust::Tokens {
+    let owned_trait = &rust::import("mproto", "Owned");
+    let wire_format = &rust::import("mproto", "WireFormat");
+    if fields.is_empty() {
+        return quote! { 0 };
+    }
+
+    let format = match width {
+        OffsetWidth::Narrow => quote! { $wire_format::Absolute },
+        OffsetWidth::Wide => quote! { $wire_format::Wide },
+    };
 
+    let mut field_scratch_len_tokens = quote! { 0 };
     for field in fields {
-        let field_ty = rust_type_lazy_tokens(cx, &field.ty);
-
-        out_tokens = quote! {
-            $out_tokens
-            let $(&field.name): $field_ty = $(
-                rust_lazy_field_decode(field, field_offset.as_tokens())
-            ).unwrap();
+        field_scratch_len_tokens = quote! {
+            $field_scratch_len_tokens
+                + <$(rust_type_tokens(cx, &field.ty)) as $owned_trait>::copy_scratch_len(&fields_cursor, $(&format)).unwrap()
         };
+    }
 
-        field_offset = field_offset.merge(type_base_len(cx, &field.ty));
+    quote! {
+        $(rust_lazy_fields_cursor())
+        $field_scratch_len_tokens
     }
+}
 
-    out_tokens = quote! {
-        $out_tokens
-        $(rust_named_fields_scratch_len(&fields, quote! { }))
-    };
+/// A cursor over a lazy struct's fields, to copy them one after another.
+fn rust_lazy_fields_cursor() -> rust::Tokens {
+    let decode_cursor = &rust::import("mproto", "DecodeCursor");
 
-    out_tokens
+    quote! {
+        let fields_cursor = $decode_cursor::at_offset(self.buffer, self.offset)
+            .with_format(self.format)
+            .with_limits_at_depth(self.limits, self.depth);
+    }
 }
 
 struct RustGenericNamedFields {
@@ -381,7 +690,7 @@ impl RustGenericNamedFields {
         cx: &CodegenCx,
         param_name_prefix: &str,
         named_fields: &[ast::NamedField],
-    ) -> (TypeBaseLen<MprotoRust>, rust::Tokens) {
+    ) -> (FormatBaseLen<MprotoRust>, rust::Tokens) {
         let maybe_pub = &if self.pub_fields {
             quote! { pub }
         } else {
@@ -389,7 +698,7 @@ impl RustGenericNamedFields {
         };
 
         let mut fields = rust::Tokens::new();
-        let mut base_len = TypeBaseLen::constant(0);
+        let mut base_len = FormatBaseLen::constant(0);
         for field in named_fields {
             let mut param_name = format!(
                 "{param_name_prefix}{}",
@@ -440,15 +749,17 @@ impl RustGenericNamedFields {
                     $maybe_pub $(&field.name): $param_name,
                 };
 
-                base_len = base_len.merge(TypeBaseLen::tokens(MprotoRust::type_param_base_len(
-                    param_name,
-                )));
+                base_len = base_len.merge(FormatBaseLen::new(|width| {
+                    TypeBaseLen::tokens(MprotoRust::type_param_base_len(param_name, width))
+                }));
             } else {
                 fields = quote! {
                     $fields
                     $maybe_pub $(&field.name): $(rust_type_tokens(cx, &field.ty)),
                 };
-                base_len = base_len.merge(type_base_len(cx, &field.ty));
+                base_len = base_len.merge(FormatBaseLen::new(|width| {
+                    type_base_len(cx, &field.ty, width)
+                }));
             }
 
             // Add field's type bound for the struct's `Compatible` trait impls
@@ -532,6 +843,9 @@ fn rust_lazy_struct_std_trait_impls(
                 Self {
                     buffer: self.buffer,
                     offset: self.offset,
+                    format: self.format,
+                    limits: self.limits,
+                    depth: self.depth,
                     $(rust_named_fields_lazy_phantom_constructor(type_params))
                 }
             }
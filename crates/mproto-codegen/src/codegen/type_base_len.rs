@@ -5,6 +5,23 @@ use crate::{
     codegen::{CodegenCx, MprotoLang, ResolvedType},
 };
 
+/// How big offsets and lengths are in the wire format a base length is for - 8 bytes in
+/// `WireFormat::Wide`, 4 otherwise.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OffsetWidth {
+    Narrow,
+    Wide,
+}
+
+impl OffsetWidth {
+    pub fn offset_len(self) -> usize {
+        match self {
+            Self::Narrow => 4,
+            Self::Wide => 8,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TypeBaseLen<L: MprotoLang> {
     constant: usize,
@@ -50,7 +67,55 @@ impl<L: MprotoLang> TypeBaseLen<L> {
     }
 }
 
-pub fn type_base_len<L: MprotoLang>(cx: &CodegenCx, ty: &Type) -> TypeBaseLen<L> {
+/// A base length in both offset widths, for generated code that picks one by the wire format it's
+/// given at runtime.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FormatBaseLen<L: MprotoLang> {
+    narrow: TypeBaseLen<L>,
+    wide: TypeBaseLen<L>,
+}
+
+impl<L: MprotoLang> FormatBaseLen<L> {
+    pub fn new(base_len: impl Fn(OffsetWidth) -> TypeBaseLen<L>) -> Self {
+        Self {
+            narrow: base_len(OffsetWidth::Narrow),
+            wide: base_len(OffsetWidth::Wide),
+        }
+    }
+
+    pub fn constant(constant: usize) -> Self {
+        Self::new(|_| TypeBaseLen::constant(constant))
+    }
+
+    pub fn get(&self, width: OffsetWidth) -> &TypeBaseLen<L> {
+        match width {
+            OffsetWidth::Narrow => &self.narrow,
+            OffsetWidth::Wide => &self.wide,
+        }
+    }
+
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            narrow: self.narrow.merge(other.narrow),
+            wide: self.wide.merge(other.wide),
+        }
+    }
+
+    /// The base length in the wire format held in `format`.
+    pub fn as_tokens(&self, format: &Tokens<L::GencoLang>) -> Tokens<L::GencoLang> {
+        if self.narrow.constant == self.wide.constant && self.narrow.tokens == self.wide.tokens {
+            self.narrow.as_tokens()
+        } else {
+            L::select_base_len(format, self.narrow.as_tokens(), self.wide.as_tokens())
+        }
+    }
+}
+
+pub fn type_base_len<L: MprotoLang>(
+    cx: &CodegenCx,
+    ty: &Type,
+    width: OffsetWidth,
+) -> TypeBaseLen<L> {
     match ty {
         Type::Primitive(PrimitiveType::Void)    => TypeBaseLen::constant(0),
         Type::Primitive(PrimitiveType::U8)      => TypeBaseLen::constant(1),
@@ -66,33 +131,33 @@ pub fn type_base_len<L: MprotoLang>(cx: &CodegenCx, ty: &Type) -> TypeBaseLen<L>
         Type::Primitive(PrimitiveType::F32)     => TypeBaseLen::constant(4),
         Type::Primitive(PrimitiveType::F64)     => TypeBaseLen::constant(8),
         Type::Primitive(PrimitiveType::Bool)    => TypeBaseLen::constant(1),
-        Type::Primitive(PrimitiveType::String)  => TypeBaseLen::constant(8),
-        Type::Primitive(PrimitiveType::Box(_))  => TypeBaseLen::constant(4),
-        Type::Primitive(PrimitiveType::List(_)) => TypeBaseLen::constant(8),
+        Type::Primitive(PrimitiveType::String)  => TypeBaseLen::constant(2 * width.offset_len()),
+        Type::Primitive(PrimitiveType::Box(_))  => TypeBaseLen::constant(width.offset_len()),
+        Type::Primitive(PrimitiveType::List(_)) => TypeBaseLen::constant(2 * width.offset_len()),
         Type::Primitive(PrimitiveType::Option(item_ty)) => {
-            TypeBaseLen::constant(1).merge(type_base_len(cx, item_ty))
+            TypeBaseLen::constant(1).merge(type_base_len(cx, item_ty, width))
         },
         Type::Primitive(PrimitiveType::Result(ok_ty, err_ty)) => {
             TypeBaseLen::constant(1).merge(
                 TypeBaseLen::tokens(quote! {
-                    $(L::const_fn_max())($(type_base_len::<L>(cx, ok_ty).as_tokens()), $(type_base_len::<L>(cx, err_ty).as_tokens()))
+                    $(L::const_fn_max())($(type_base_len::<L>(cx, ok_ty, width).as_tokens()), $(type_base_len::<L>(cx, err_ty, width).as_tokens()))
                 })
             )
         }
         Type::Defined { ident, args } => {
             match cx.resolve_type(ident) {
                 Some(ResolvedType::Defined(type_def)) => {
-                    let inner_cx = cx.with_type_args(&type_def.params, &args);
+                    let inner_cx = cx.with_type_args(&type_def.params, args);
                     match type_def.body {
-                        TypeBody::Struct(ref s) => struct_base_len(&inner_cx, s),
-                        TypeBody::Enum(ref e) => enum_base_len(&inner_cx, e),
+                        TypeBody::Struct(ref s) => struct_base_len(&inner_cx, s, width),
+                        TypeBody::Enum(ref e) => enum_base_len(&inner_cx, e, width),
                     }
                 }
                 Some(ResolvedType::UnboundParam) => {
-                    TypeBaseLen::tokens(L::type_param_base_len(&ident.name))
+                    TypeBaseLen::tokens(L::type_param_base_len(&ident.name, width))
                 }
                 Some(ResolvedType::BoundParam { value, binding_cx }) => {
-                    type_base_len(&cx.with_type_param_bindings(binding_cx), value)
+                    type_base_len(&cx.with_type_param_bindings(binding_cx), value, width)
                 }
                 None => {
                     panic!("type_base_len failed to resolve type: {:?}", ident);
@@ -102,21 +167,29 @@ pub fn type_base_len<L: MprotoLang>(cx: &CodegenCx, ty: &Type) -> TypeBaseLen<L>
     }
 }
 
-pub fn struct_base_len<L: MprotoLang>(cx: &CodegenCx, s: &Struct) -> TypeBaseLen<L> {
+pub fn struct_base_len<L: MprotoLang>(
+    cx: &CodegenCx,
+    s: &Struct,
+    width: OffsetWidth,
+) -> TypeBaseLen<L> {
     let mut base_len = TypeBaseLen::constant(0);
 
     for field in &s.fields {
-        base_len = base_len.merge(type_base_len(cx, &field.ty));
+        base_len = base_len.merge(type_base_len(cx, &field.ty, width));
     }
 
     base_len
 }
 
-pub fn enum_base_len<L: MprotoLang>(cx: &CodegenCx, e: &Enum) -> TypeBaseLen<L> {
+pub fn enum_base_len<L: MprotoLang>(
+    cx: &CodegenCx,
+    e: &Enum,
+    width: OffsetWidth,
+) -> TypeBaseLen<L> {
     let mut base_len = TypeBaseLen::constant(0);
 
-    for &(_, ref variant) in &e.variants {
-        let variant_base_len = enum_variant_base_len::<L>(cx, variant);
+    for (_, variant) in &e.variants {
+        let variant_base_len = enum_variant_base_len::<L>(cx, variant, width);
 
         base_len = TypeBaseLen::tokens(quote! {
             $(L::const_fn_max())($(base_len.as_tokens()), $(variant_base_len.as_tokens()))
@@ -130,6 +203,7 @@ pub fn enum_base_len<L: MprotoLang>(cx: &CodegenCx, e: &Enum) -> TypeBaseLen<L>
 pub fn enum_variant_base_len<L: MprotoLang>(
     cx: &CodegenCx,
     variant: &EnumVariant,
+    width: OffsetWidth,
 ) -> TypeBaseLen<L> {
     let mut variant_base_len = TypeBaseLen::<L>::constant(0);
 
@@ -137,7 +211,7 @@ pub fn enum_variant_base_len<L: MprotoLang>(
         EnumVariant::Empty => {}
         EnumVariant::NamedFields { ref fields } => {
             for field in fields {
-                variant_base_len = variant_base_len.merge(type_base_len(cx, &field.ty));
+                variant_base_len = variant_base_len.merge(type_base_len(cx, &field.ty, width));
             }
         }
     }
@@ -148,9 +222,9 @@ pub fn enum_variant_base_len<L: MprotoLang>(
 #[cfg(test)]
 mod test {
     use crate::{
+        Database, Module,
         ast::{PrimitiveType, QualifiedIdentifier, Type},
         codegen::MprotoRust,
-        Database, Module,
     };
 
     use super::*;
@@ -161,7 +235,7 @@ mod test {
 
         let (_, type_defs) = crate::parse::root(s).unwrap();
 
-        let local_module = Module::from_type_defs(type_defs.into());
+        let local_module = Module::from_type_defs(type_defs);
         let db = Database::new(local_module);
 
         let foo_base_len = super::type_base_len::<MprotoRust>(
@@ -170,8 +244,35 @@ mod test {
                 ident: QualifiedIdentifier::local("Foo"),
                 args: vec![Type::Primitive(PrimitiveType::U64)],
             },
+            OffsetWidth::Narrow,
         );
 
         assert_eq!(foo_base_len, TypeBaseLen::constant(8 + 1 + 4),);
     }
+
+    #[test]
+    fn test_type_base_len_wide() {
+        let s = "struct Foo { a: string, b: [u8], c: box<u8>, d: option<Bar> }
+struct Bar { x: u16, y: string }
+";
+
+        let (_, type_defs) = crate::parse::root(s).unwrap();
+
+        let local_module = Module::from_type_defs(type_defs);
+        let db = Database::new(local_module);
+        let cx = CodegenCx::new(&db, None, false);
+        let foo = Type::Defined {
+            ident: QualifiedIdentifier::local("Foo"),
+            args: vec![],
+        };
+
+        assert_eq!(
+            super::type_base_len::<MprotoRust>(&cx, &foo, OffsetWidth::Narrow),
+            TypeBaseLen::constant(8 + 8 + 4 + 1 + 2 + 8),
+        );
+        assert_eq!(
+            super::type_base_len::<MprotoRust>(&cx, &foo, OffsetWidth::Wide),
+            TypeBaseLen::constant(16 + 16 + 8 + 1 + 2 + 16),
+        );
+    }
 }
@@ -0,0 +1,209 @@
+use std::fmt::Write;
+
+use crate::{
+    ast::{EnumVariant, NamedField, PrimitiveType, Type, TypeBody, TypeDef},
+    codegen::{CodegenCx, ResolvedType},
+};
+
+/// A stable fingerprint of a type definition's structure: the names and types of its fields and
+/// the names and order of its variants, with the types they use expanded in place. Type names
+/// aren't part of it, so renaming a type keeps its fingerprint.
+///
+/// Type parameters are fingerprinted by position - the runtime mixes in the fingerprints of the
+/// type arguments, see `mproto::fingerprint_with_args`.
+pub fn type_def_fingerprint(cx: &CodegenCx, type_def: &TypeDef) -> u64 {
+    let cx = cx.with_type_params(&type_def.params);
+    let mut signature = Signature {
+        params: &type_def.params,
+        stack: vec![type_def],
+        out: String::new(),
+    };
+    signature.type_body(&cx, &type_def.body);
+
+    fnv1a(signature.out.as_bytes())
+}
+
+/// 64-bit FNV-1a, the same as the runtime's.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Builds a canonical string describing a type definition, e.g.
+/// `struct{x:$0,y:[u8],next:option<box<#0>>}`.
+struct Signature<'p> {
+    params: &'p [String],
+    /// The definitions being expanded, to refer back to recursive uses by depth instead.
+    stack: Vec<*const TypeDef>,
+    out: String,
+}
+
+impl Signature<'_> {
+    fn type_body(&mut self, cx: &CodegenCx, body: &TypeBody) {
+        match body {
+            TypeBody::Struct(s) => {
+                self.out.push_str("struct");
+                self.named_fields(cx, &s.fields);
+            }
+            TypeBody::Enum(e) => {
+                self.out.push_str("enum{");
+                for (i, (name, variant)) in e.variants.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    self.out.push_str(name);
+                    match variant {
+                        EnumVariant::Empty => {}
+                        EnumVariant::NamedFields { fields } => self.named_fields(cx, fields),
+                    }
+                }
+                self.out.push('}');
+            }
+        }
+    }
+
+    fn named_fields(&mut self, cx: &CodegenCx, fields: &[NamedField]) {
+        self.out.push('{');
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            self.out.push_str(&field.name);
+            self.out.push(':');
+            self.ty(cx, &field.ty);
+        }
+        self.out.push('}');
+    }
+
+    fn ty(&mut self, cx: &CodegenCx, ty: &Type) {
+        match ty {
+            Type::Primitive(PrimitiveType::Void) => self.out.push_str("void"),
+            Type::Primitive(PrimitiveType::U8) => self.out.push_str("u8"),
+            Type::Primitive(PrimitiveType::U16) => self.out.push_str("u16"),
+            Type::Primitive(PrimitiveType::U32) => self.out.push_str("u32"),
+            Type::Primitive(PrimitiveType::U64) => self.out.push_str("u64"),
+            Type::Primitive(PrimitiveType::U128) => self.out.push_str("u128"),
+            Type::Primitive(PrimitiveType::I8) => self.out.push_str("i8"),
+            Type::Primitive(PrimitiveType::I16) => self.out.push_str("i16"),
+            Type::Primitive(PrimitiveType::I32) => self.out.push_str("i32"),
+            Type::Primitive(PrimitiveType::I64) => self.out.push_str("i64"),
+            Type::Primitive(PrimitiveType::I128) => self.out.push_str("i128"),
+            Type::Primitive(PrimitiveType::Bool) => self.out.push_str("bool"),
+            Type::Primitive(PrimitiveType::F32) => self.out.push_str("f32"),
+            Type::Primitive(PrimitiveType::F64) => self.out.push_str("f64"),
+            Type::Primitive(PrimitiveType::String) => self.out.push_str("string"),
+            Type::Primitive(PrimitiveType::Box(inner_ty)) => {
+                self.out.push_str("box<");
+                self.ty(cx, inner_ty);
+                self.out.push('>');
+            }
+            Type::Primitive(PrimitiveType::List(item_ty)) => {
+                self.out.push('[');
+                self.ty(cx, item_ty);
+                self.out.push(']');
+            }
+            Type::Primitive(PrimitiveType::Option(inner_ty)) => {
+                self.out.push_str("option<");
+                self.ty(cx, inner_ty);
+                self.out.push('>');
+            }
+            Type::Primitive(PrimitiveType::Result(ok_ty, err_ty)) => {
+                self.out.push_str("result<");
+                self.ty(cx, ok_ty);
+                self.out.push(',');
+                self.ty(cx, err_ty);
+                self.out.push('>');
+            }
+            Type::Defined { ident, args } => match cx.resolve_type(ident) {
+                Some(ResolvedType::Defined(type_def)) => {
+                    let type_def_ptr = type_def as *const TypeDef;
+                    if let Some(depth) = self.stack.iter().rev().position(|&d| d == type_def_ptr) {
+                        write!(self.out, "#{depth}").unwrap();
+                        return;
+                    }
+
+                    self.stack.push(type_def_ptr);
+                    self.type_body(&cx.with_type_args(&type_def.params, args), &type_def.body);
+                    self.stack.pop();
+                }
+                Some(ResolvedType::UnboundParam) => {
+                    let index = self
+                        .params
+                        .iter()
+                        .position(|param| *param == ident.name)
+                        .expect("unbound type parameter of the fingerprinted type");
+                    write!(self.out, "${index}").unwrap();
+                }
+                Some(ResolvedType::BoundParam { value, binding_cx }) => {
+                    self.ty(&cx.with_type_param_bindings(binding_cx), value);
+                }
+                None => {
+                    panic!("type_def_fingerprint failed to resolve type: {:?}", ident);
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Database, Module};
+
+    use super::*;
+
+    fn fingerprints(s: &str) -> Vec<u64> {
+        let (_, type_defs) = crate::parse::root(s).unwrap();
+        let db = Database::new(Module::from_type_defs(type_defs));
+        let cx = CodegenCx::new(&db, None, false);
+        db.local()
+            .type_defs()
+            .map(|type_def| type_def_fingerprint(&cx, type_def))
+            .collect()
+    }
+
+    #[test]
+    fn test_type_def_fingerprint() {
+        let base = fingerprints("struct Foo { a: u32, b: [Bar] }\nstruct Bar { x: string }\n")[0];
+
+        // Type names don't matter, but everything about the structure does.
+        assert_eq!(
+            fingerprints("struct Renamed { a: u32, b: [Other] }\nstruct Other { x: string }\n")[0],
+            base,
+        );
+        for changed in [
+            "struct Foo { a: u64, b: [Bar] }\nstruct Bar { x: string }\n",
+            "struct Foo { renamed: u32, b: [Bar] }\nstruct Bar { x: string }\n",
+            "struct Foo { b: [Bar], a: u32 }\nstruct Bar { x: string }\n",
+            "struct Foo { a: u32, b: [Bar] }\nstruct Bar { x: option<string> }\n",
+            "struct Foo { a: u32, b: [Bar] }\nenum Bar { x }\n",
+        ] {
+            assert_ne!(fingerprints(changed)[0], base, "{changed}");
+        }
+
+        let [a, b] = fingerprints("enum A { X, Y }\nenum B { Y, X }\n")[..] else {
+            unreachable!()
+        };
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_type_def_fingerprint_generics_and_recursion() {
+        // Type parameters are fingerprinted by position.
+        let [a, b, c] = fingerprints(
+            "struct A<T, U> { x: T, y: U }\nstruct B<U, T> { x: U, y: T }\nstruct C<T, U> { x: U, y: T }\n",
+        )[..] else {
+            unreachable!()
+        };
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        // Recursive types refer back to themselves.
+        let [list, tree] = fingerprints(
+            "struct List<T> { head: T, tail: option<box<List<T>>> }\nenum Tree { Leaf, Node { children: [Tree] } }\n",
+        )[..] else {
+            unreachable!()
+        };
+        assert_ne!(list, tree);
+    }
+}
@@ -7,6 +7,12 @@ pub struct Module {
     type_defs_by_name: HashMap<String, TypeDefId>,
 }
 
+impl Default for Module {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Module {
     pub fn new() -> Self {
         Self {
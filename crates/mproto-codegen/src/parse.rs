@@ -1,12 +1,12 @@
 use nom::{
+    IResult,
     branch::alt,
     bytes::complete::{tag, take_while1},
     character::complete::{char, multispace0},
     combinator::{cut, map, opt},
-    error::{context, ParseError},
+    error::{ParseError, context},
     multi::separated_list0,
     sequence::{preceded, separated_pair, terminated},
-    IResult,
 };
 
 use crate::ast::{
@@ -146,7 +146,7 @@ pub fn type_args_list(i: &str) -> IResult<&str, Vec<Type>> {
     let (i, _) = multispace0(i)?;
     let (i, _) = tag(">")(i)?;
 
-    let args = args.into_iter().map(|p| p.into()).collect();
+    let args = args.into_iter().collect();
 
     Ok((i, args))
 }
@@ -171,7 +171,7 @@ pub fn struct_def(i: &str) -> IResult<&str, TypeDef> {
     Ok((i, type_def))
 }
 
-fn enum_def<'a>(i: &'a str) -> IResult<&'a str, TypeDef> {
+fn enum_def(i: &str) -> IResult<&str, TypeDef> {
     let (i, _) = tag("enum")(i)?;
     let (i, _) = multispace0(i)?;
     let (i, name) = identifier(i)?;
@@ -191,7 +191,7 @@ fn enum_def<'a>(i: &'a str) -> IResult<&'a str, TypeDef> {
     Ok((i, type_def))
 }
 
-fn enum_variants<'a>(i: &'a str) -> IResult<&'a str, Vec<(String, EnumVariant)>> {
+fn enum_variants(i: &str) -> IResult<&str, Vec<(String, EnumVariant)>> {
     context(
         "map",
         preceded(
@@ -215,7 +215,7 @@ fn enum_variants<'a>(i: &'a str) -> IResult<&'a str, Vec<(String, EnumVariant)>>
     )(i)
 }
 
-fn enum_variant<'a>(i: &'a str) -> IResult<&'a str, (&'a str, EnumVariant)> {
+fn enum_variant(i: &str) -> IResult<&str, (&str, EnumVariant)> {
     alt((
         separated_pair(
             identifier,
@@ -226,7 +226,7 @@ fn enum_variant<'a>(i: &'a str) -> IResult<&'a str, (&'a str, EnumVariant)> {
     ))(i)
 }
 
-pub fn defined_ty<'a>(i: &'a str) -> IResult<&'a str, Type> {
+pub fn defined_ty(i: &str) -> IResult<&str, Type> {
     let (i, ident) = qualified_identifier(i)?;
     let (i, _) = multispace0(i)?;
     let (i, maybe_args) = opt(type_args_list)(i)?;
@@ -237,15 +237,15 @@ pub fn defined_ty<'a>(i: &'a str) -> IResult<&'a str, Type> {
     Ok((i, defined_type))
 }
 
-pub fn ty<'a>(i: &'a str) -> IResult<&'a str, Type> {
-    alt((map(builtin_ty, |x| Type::Primitive(x)), defined_ty))(i)
+pub fn ty(i: &str) -> IResult<&str, Type> {
+    alt((map(builtin_ty, Type::Primitive), defined_ty))(i)
 }
 
-pub fn type_def<'a>(i: &'a str) -> IResult<&'a str, TypeDef> {
+pub fn type_def(i: &str) -> IResult<&str, TypeDef> {
     alt((struct_def, enum_def))(i)
 }
 
-fn named_field<'a>(i: &'a str) -> IResult<&'a str, (&'a str, Type)> {
+fn named_field(i: &str) -> IResult<&str, (&str, Type)> {
     separated_pair(
         identifier,
         cut(preceded(multispace0, char(':'))),
@@ -253,7 +253,7 @@ fn named_field<'a>(i: &'a str) -> IResult<&'a str, (&'a str, Type)> {
     )(i)
 }
 
-fn named_fields<'a>(i: &'a str) -> IResult<&'a str, Vec<NamedField>> {
+fn named_fields(i: &str) -> IResult<&str, Vec<NamedField>> {
     context(
         "map",
         preceded(
@@ -281,18 +281,19 @@ fn named_fields<'a>(i: &'a str) -> IResult<&'a str, Vec<NamedField>> {
 }
 
 pub fn strip_comments(s: &str) -> String {
-    s.lines().map(|line| {
-        if let Some(index) = line.find("//") {
-            &line[..index]
-        } else {
-            line
-        }
-    })
-    .collect::<Vec<&str>>()
-    .join("\n")
+    s.lines()
+        .map(|line| {
+            if let Some(index) = line.find("//") {
+                &line[..index]
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
 }
 
-pub fn root<'a>(i: &'a str) -> IResult<&'a str, Vec<TypeDef>> {
+pub fn root(i: &str) -> IResult<&str, Vec<TypeDef>> {
     separated_list0(multispace0, type_def)(i)
 }
 
@@ -313,7 +314,8 @@ pub fn parse_file(path: impl AsRef<std::path::Path>) -> Result<Vec<TypeDef>, Str
     }
 
     let uncommented_schema = strip_comments(&file_str) + "\n";
-    let (_, type_defs) = root(&uncommented_schema).map_err(|e| format!("mproto schema parse error: {e}"))?;
+    let (_, type_defs) =
+        root(&uncommented_schema).map_err(|e| format!("mproto schema parse error: {e}"))?;
 
     Ok(type_defs)
 }
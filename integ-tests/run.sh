@@ -20,6 +20,11 @@ cd test-mproto/rust/
 cargo build
 cd -
 
+# Round-trip the generated types through this runtime
+cd rust-tests/
+cargo test
+cd -
+
 # Attempt to compile typescript package
 cd test-mproto/typescript/
 npm install .
@@ -0,0 +1,75 @@
+//! Compression of the generated `integ-tests/proto/test.mproto` types. Run with `--features lz4`
+//! to include LZ4.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use mproto::{
+    Owned,
+    compression::{Compression, decode_compressed},
+    encode_value_vec,
+};
+use test_mproto::{JustASimpleStruct, SimpleEnum, StructWithDouble, Telemetry};
+
+fn bench_compression<T: Owned>(c: &mut Criterion, name: &str, value: &T) {
+    let encoded = encode_value_vec(value);
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(encoded.len() as u64));
+
+    let compressions = [
+        ("zrle", Compression::Zrle),
+        #[cfg(feature = "lz4")]
+        ("lz4", Compression::Lz4),
+    ];
+
+    for (compression_name, compression) in compressions {
+        let compressed = compression.compress(&encoded);
+
+        group.bench_function(format!("{compression_name}_compress"), |b| {
+            b.iter(|| compression.compress(&encoded))
+        });
+        group.bench_function(format!("{compression_name}_decompress"), |b| {
+            b.iter(|| decode_compressed::<T>(&compressed, compression, encoded.len()).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn compression(c: &mut Criterion) {
+    let simple_structs = (0..1_000)
+        .map(|i| JustASimpleStruct {
+            a: i,
+            b: -(i as i64) * 1000,
+            c: format!("simple struct {i}"),
+            d: vec![i as u8; (i % 16) as usize],
+            e: (i % 3 != 0).then_some(i % 2 == 0),
+            f: i as f32 * 0.5,
+            g: i as f64 / 3.0,
+            y: if i % 4 == 0 {
+                Err(())
+            } else {
+                Ok(format!("ok {i}"))
+            },
+            z: Box::new(Ok(String::new())),
+        })
+        .collect::<Vec<_>>();
+    bench_compression(c, "just_a_simple_struct", &simple_structs);
+
+    let telemetry = (0..1_000)
+        .map(|i| Telemetry {
+            count: i,
+            enabled: i % 2 == 0,
+            position: StructWithDouble { x: i as f64 * 1.25 },
+            status: (i % 5 == 0).then_some(if i % 2 == 0 {
+                SimpleEnum::Fizz
+            } else {
+                SimpleEnum::Buzz
+            }),
+            samples: (0..(i % 8) as i32).map(|j| j * 100 - 50).collect(),
+            source: "sensor".to_string(),
+        })
+        .collect::<Vec<_>>();
+    bench_compression(c, "telemetry", &telemetry);
+}
+
+criterion_group!(benches, compression);
+criterion_main!(benches);
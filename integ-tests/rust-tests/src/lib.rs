@@ -0,0 +1 @@
+//! Tests of the Rust code generated from `integ-tests/proto/test.mproto`, see `tests/`.
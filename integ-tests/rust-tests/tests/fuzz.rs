@@ -0,0 +1,289 @@
+//! Feeds random, truncated and bit-flipped buffers to the generated decoders, lazy accessors and
+//! verifiers. Decoding may fail, but it must never panic, and anything that verifies must decode.
+
+use mproto::{
+    Owned, WireFormat, decode_value, decode_value_with_format, encode_value_vec,
+    encode_value_vec_with_format, verify_value,
+};
+use test_mproto::{
+    Foo, JustASimpleStruct, JustASimpleStructLazy, MyTimestampedResponse, NestedEnum,
+    NestedEnumLazy, NodeMatch, NodeMatchLazy, SimpleEnum, StructWithDouble, Telemetry,
+    TelemetryLazy, WalkFilter, WalkFilterLazy,
+};
+
+/// Small deterministic xorshift PRNG so the tests are reproducible without extra dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|_| match self.below(4) {
+                // Bias towards small values so that lengths, offsets and tags are often in range.
+                0 => 0,
+                1 => self.below(16) as u8,
+                _ => self.next() as u8,
+            })
+            .collect()
+    }
+}
+
+/// Decode `buf` as each of the generated types, touching every lazily decoded part as well.
+fn decode_all(buf: &[u8]) {
+    for format in [WireFormat::Absolute, WireFormat::Relative, WireFormat::Wide] {
+        let _ = decode_value_with_format::<Telemetry>(buf, format);
+        let _ = decode_value_with_format::<JustASimpleStruct>(buf, format);
+        let _ = decode_value_with_format::<NodeMatch<String>>(buf, format);
+        let _ = decode_value_with_format::<NestedEnum>(buf, format);
+    }
+    let _ = decode_value::<NodeMatch<u32>>(buf);
+    let _ = decode_value::<WalkFilter<String>>(buf);
+    let _ = decode_value::<Foo<Vec<u8>, String>>(buf);
+    let _ = decode_value::<MyTimestampedResponse<Vec<String>>>(buf);
+    let _ = decode_value::<Vec<Telemetry>>(buf);
+
+    if let Ok(lazy) = decode_value::<TelemetryLazy>(buf) {
+        touch_telemetry(lazy);
+    }
+    if let Ok(lazy) = decode_value::<JustASimpleStructLazy>(buf) {
+        touch_simple_struct(lazy);
+    }
+    if let Ok(lazy) = decode_value::<NodeMatchLazy<String>>(buf) {
+        touch_node_match(lazy);
+    }
+    if let Ok(lazy) = decode_value::<WalkFilterLazy<String>>(buf) {
+        let _ = format!("{lazy:?}");
+        match lazy {
+            WalkFilterLazy::Omit { node_match } | WalkFilterLazy::Include { node_match } => {
+                touch_node_match(node_match)
+            }
+        }
+        let _ = WalkFilter::<String>::try_from(lazy);
+    }
+    if let Ok(lazy) = decode_value::<NestedEnumLazy>(buf) {
+        touch_nested_enum(lazy);
+    }
+    if let Ok(list) = decode_value::<mproto::ListLazy<Telemetry>>(buf) {
+        for item in list.try_iter().flatten() {
+            touch_telemetry(item);
+        }
+        let _ = Vec::<Telemetry>::try_from(list);
+    }
+
+    verify_all(buf);
+}
+
+fn touch_telemetry(lazy: TelemetryLazy) {
+    let _ = format!("{lazy:?}");
+    let _ = lazy.count();
+    let _ = lazy.enabled();
+    let _ = lazy.position().and_then(|position| position.x());
+    let _ = lazy.status();
+    if let Ok(samples) = lazy.samples() {
+        for _ in &samples {}
+    }
+    let _ = lazy.source();
+    let _ = Telemetry::try_from(lazy);
+    let _ = Telemetry::lazy_to_owned(lazy);
+}
+
+fn touch_simple_struct(lazy: JustASimpleStructLazy) {
+    let _ = format!("{lazy:?}");
+    let _ = (
+        lazy.a(),
+        lazy.b(),
+        lazy.c(),
+        lazy.e(),
+        lazy.f(),
+        lazy.g(),
+        lazy.y(),
+    );
+    if let Ok(d) = lazy.d() {
+        for _ in &d {}
+    }
+    let _ = lazy.z();
+    let _ = lazy.z_spliced();
+    let _ = JustASimpleStruct::try_from(lazy);
+}
+
+fn touch_node_match(lazy: NodeMatchLazy<String>) {
+    let _ = format!("{lazy:?}");
+    match lazy {
+        NodeMatchLazy::HasTag { tag } => {
+            let _ = tag.len();
+        }
+        NodeMatchLazy::HasTagValue { tag, value: bytes }
+        | NodeMatchLazy::HasTagPrefix { tag, prefix: bytes } => {
+            let _ = tag.len();
+            let _ = Vec::<u8>::try_from(bytes);
+        }
+    }
+    let _ = NodeMatch::<String>::try_from(lazy);
+}
+
+fn touch_nested_enum(mut lazy: NestedEnumLazy) {
+    let _ = format!("{lazy:?}");
+    let _ = NestedEnum::try_from(lazy);
+    // A box can point back at itself, so only follow so many.
+    for _ in 0..100 {
+        let NestedEnumLazy::A { nested } = lazy;
+        match nested.get() {
+            Ok(inner) => lazy = inner,
+            Err(_) => break,
+        }
+    }
+}
+
+/// Anything that verifies must decode successfully, both lazily and owned, and its views must be
+/// accessible without errors.
+fn verify_all(buf: &[u8]) {
+    fn check<T: Owned>(buf: &[u8]) -> Option<()> {
+        let verified = verify_value::<T>(buf).ok()?;
+        T::lazy_to_owned(verified.into_inner()).expect("verified value failed to decode");
+        decode_value::<T>(buf).expect("verified value failed to decode");
+        Some(())
+    }
+
+    check::<Telemetry>(buf);
+    check::<JustASimpleStruct>(buf);
+    check::<NodeMatch<String>>(buf);
+    check::<WalkFilter<String>>(buf);
+    check::<NestedEnum>(buf);
+    check::<Foo<Vec<u8>, String>>(buf);
+    check::<MyTimestampedResponse<Vec<String>>>(buf);
+    check::<Vec<Telemetry>>(buf);
+
+    if let Ok(verified) = verify_value::<Telemetry>(buf) {
+        let view = verified.view();
+        let _ = format!("{view:?}");
+        let _ = (view.count(), view.enabled(), view.position(), view.status());
+        let _ = (view.samples().iter().count(), view.source().len());
+    }
+    if let Ok(verified) = verify_value::<JustASimpleStruct>(buf) {
+        let view = verified.view();
+        let _ = format!("{view:?}");
+        let _ = (view.c().len(), view.d().iter().count(), view.y(), view.z());
+    }
+    if let Ok(verified) = verify_value::<NodeMatch<String>>(buf) {
+        let _ = format!("{:?}", verified.view());
+    }
+}
+
+/// Valid encodings of the generated types, to truncate and corrupt.
+fn samples() -> Vec<Vec<u8>> {
+    let telemetry = Telemetry {
+        count: 3,
+        enabled: true,
+        position: StructWithDouble { x: 1.25 },
+        status: Some(SimpleEnum::Buzz),
+        samples: vec![-1, 0, 1],
+        source: "sensor".into(),
+    };
+    let simple_struct = JustASimpleStruct {
+        a: 1,
+        b: -2,
+        c: "c".into(),
+        d: vec![4, 5],
+        e: Some(true),
+        f: 0.5,
+        g: -0.25,
+        y: Ok("y".into()),
+        z: Box::new(Ok("z".into())),
+    };
+
+    vec![
+        encode_value_vec(&telemetry),
+        encode_value_vec_with_format(&telemetry, WireFormat::Wide),
+        encode_value_vec(vec![telemetry.clone(), telemetry]),
+        encode_value_vec(&simple_struct),
+        encode_value_vec_with_format(&simple_struct, WireFormat::Relative),
+        encode_value_vec(NodeMatch::HasTagValue {
+            tag: "tag".to_string(),
+            value: vec![1, 2, 3],
+        }),
+        encode_value_vec(WalkFilter::Include {
+            node_match: NodeMatch::HasTagPrefix {
+                tag: "t".to_string(),
+                prefix: vec![9],
+            },
+        }),
+        encode_value_vec(Foo::<Vec<u8>, String> {
+            x: vec![1],
+            y: "y".into(),
+            z: Err(test_mproto::Bar {
+                x: Some("bar".into()),
+            }),
+        }),
+        nested_enum_chain(8),
+    ]
+}
+
+/// A `NestedEnum` that can't be built as an owned value, as the schema has no way to end the
+/// recursion: a chain of `links` boxes, whose last one points back at the root.
+fn nested_enum_chain(links: usize) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for link in 0..links {
+        buf.push(0);
+        let next = if link + 1 == links { 0 } else { (link + 1) * 5 };
+        buf.extend_from_slice(&(next as u32).to_le_bytes());
+    }
+    buf
+}
+
+#[test]
+fn fuzz_random_buffers() {
+    let mut rng = Rng(0x9e3779b97f4a7c15);
+    for _ in 0..20_000 {
+        let len = rng.below(96);
+        decode_all(&rng.bytes(len));
+    }
+}
+
+#[test]
+fn fuzz_truncated_buffers() {
+    for buf in samples() {
+        for len in 0..=buf.len() {
+            decode_all(&buf[..len]);
+        }
+    }
+}
+
+#[test]
+fn fuzz_bit_flipped_buffers() {
+    let mut rng = Rng(0x2545f4914f6cdd1d);
+    for buf in samples() {
+        for i in 0..buf.len() * 8 {
+            let mut flipped = buf.clone();
+            flipped[i / 8] ^= 1 << (i % 8);
+            decode_all(&flipped);
+
+            // And a few random corruptions at once.
+            for _ in 0..3 {
+                let byte = rng.below(flipped.len());
+                flipped[byte] = rng.next() as u8;
+            }
+            decode_all(&flipped);
+        }
+    }
+}
+
+#[test]
+fn cyclic_nested_enum() {
+    // Following the boxes by hand goes round the cycle, decoding the owned value hits the depth
+    // limit rather than overflowing the stack.
+    let buf = nested_enum_chain(3);
+    let NestedEnumLazy::A { nested } = decode_value::<NestedEnumLazy>(&buf).unwrap();
+    assert!(nested.get().is_ok());
+    assert!(decode_value::<NestedEnum>(&buf).is_err());
+    assert!(verify_value::<NestedEnum>(&buf).is_err());
+}
@@ -0,0 +1,199 @@
+use core::fmt::Debug;
+
+use mproto::{
+    BaseLen, Owned, WireFormat, decode_value_with_format, encode_value_vec_with_format,
+    is_canonical, verify_value_with_format,
+};
+use test_mproto::{
+    Bar, EmptyStruct, Foo, JustASimpleStruct, MySuccessfulResponse, MyTimestampedResponse,
+    NodeMatch, SimpleEnum, StructWithDouble, Telemetry, WalkFilter,
+};
+
+const FORMATS: [WireFormat; 3] = [WireFormat::Absolute, WireFormat::Relative, WireFormat::Wide];
+
+/// Round-trips `value` through each wire format, both owned and re-encoded from its lazy value.
+fn round_trip<T: Owned + PartialEq + Debug>(value: T) {
+    for format in FORMATS {
+        let buf = encode_value_vec_with_format(&value, format);
+        verify_value_with_format::<T>(&buf, format).unwrap();
+        if format == WireFormat::Absolute {
+            assert!(
+                is_canonical::<T>(&buf),
+                "{value:?} encoding isn't canonical"
+            );
+        }
+        assert_eq!(
+            decode_value_with_format::<T>(&buf, format),
+            Ok(value.clone())
+        );
+
+        // Re-encoding the lazy value gives the same bytes, whichever format it's encoded into.
+        let lazy: T::Lazy<'_> = decode_value_with_format(&buf, format).unwrap();
+        for other_format in FORMATS {
+            assert_eq!(
+                encode_value_vec_with_format(lazy, other_format),
+                encode_value_vec_with_format(&value, other_format),
+                "{value:?} re-encoded from {format:?} into {other_format:?}",
+            );
+        }
+        assert_eq!(T::lazy_to_owned(lazy), Ok(value.clone()));
+    }
+}
+
+fn simple_struct(i: u32) -> JustASimpleStruct {
+    JustASimpleStruct {
+        a: i,
+        b: -(i as i64),
+        c: format!("struct {i}"),
+        d: (0..i as u8).collect(),
+        e: (!i.is_multiple_of(3)).then_some(i.is_multiple_of(2)),
+        f: i as f32 / 2.0,
+        g: -(i as f64),
+        y: if i.is_multiple_of(2) {
+            Ok("y".repeat(i as usize))
+        } else {
+            Err(())
+        },
+        z: Box::new(if i.is_multiple_of(3) {
+            Err(())
+        } else {
+            Ok(format!("boxed {i}"))
+        }),
+    }
+}
+
+#[test]
+fn structs() {
+    round_trip(simple_struct(0));
+    round_trip(simple_struct(7));
+    round_trip(EmptyStruct {});
+    round_trip(Telemetry {
+        count: 3,
+        enabled: true,
+        position: StructWithDouble { x: 1.25 },
+        status: Some(SimpleEnum::Buzz),
+        samples: vec![-1, 0, 1],
+        source: "sensor".into(),
+    });
+}
+
+#[test]
+fn non_canonical_structs() {
+    let value = simple_struct(7);
+    let buf = encode_value_vec_with_format(&value, WireFormat::Absolute);
+    assert!(is_canonical::<JustASimpleStruct>(&buf));
+
+    // Unreachable trailing bytes.
+    let mut trailing = buf.clone();
+    trailing.push(0);
+    assert!(!is_canonical::<JustASimpleStruct>(&trailing));
+
+    // Non-zero padding after the boxed field's `Err` tag. `z` is the last field, so its offset
+    // ends the struct's base.
+    let value = simple_struct(6);
+    let mut padded = encode_value_vec_with_format(&value, WireFormat::Absolute);
+    let base_len = JustASimpleStruct::BASE_LEN;
+    let z_offset = u32::from_le_bytes(padded[base_len - 4..base_len].try_into().unwrap()) as usize;
+    assert_eq!(padded[z_offset..z_offset + 5], [1, 0, 0, 0, 0]);
+    padded[z_offset + 4] = 1;
+    assert_eq!(
+        decode_value_with_format::<JustASimpleStruct>(&padded, WireFormat::Absolute),
+        Ok(value)
+    );
+    assert!(!is_canonical::<JustASimpleStruct>(&padded));
+
+    // Non-zero padding in list items without scratch, which a lazy list copies as is.
+    let value = vec![Bar { x: Some(1u32) }, Bar { x: None }];
+    let mut padded = encode_value_vec_with_format(&value, WireFormat::Absolute);
+    let none_offset = 8 + Bar::<u32>::BASE_LEN;
+    assert_eq!(padded[none_offset..], [0, 0, 0, 0, 0]);
+    padded[none_offset + 1] = 1;
+    assert_eq!(
+        decode_value_with_format::<Vec<Bar<u32>>>(&padded, WireFormat::Absolute),
+        Ok(value)
+    );
+    assert!(!is_canonical::<Vec<Bar<u32>>>(&padded));
+}
+
+#[test]
+fn lists_of_structs() {
+    round_trip((0..20).map(simple_struct).collect::<Vec<_>>());
+    round_trip(
+        (0..5)
+            .map(|i| Box::new(simple_struct(i)))
+            .collect::<Vec<_>>(),
+    );
+    round_trip(vec![SimpleEnum::Fizz, SimpleEnum::Buzz]);
+}
+
+#[test]
+fn generic_types() {
+    round_trip(Foo::<u32, String> {
+        x: 7,
+        y: "y".into(),
+        z: Err(Bar {
+            x: Some("bar".into()),
+        }),
+    });
+    round_trip(Foo::<Vec<u8>, Option<JustASimpleStruct>> {
+        x: vec![1, 2, 3],
+        y: Some(simple_struct(4)),
+        z: Ok("z".into()),
+    });
+    round_trip(MyTimestampedResponse::<Vec<String>> {
+        timestamp: 5,
+        response: Ok(MySuccessfulResponse::AnotherVariant {
+            value: vec!["a".into(), "b".into()],
+        }),
+    });
+    round_trip(MyTimestampedResponse::<u8> {
+        timestamp: 6,
+        response: Err("failed".into()),
+    });
+    round_trip(vec![
+        WalkFilter::Include {
+            node_match: NodeMatch::HasTagValue {
+                tag: "tag".to_string(),
+                value: vec![1, 2],
+            },
+        },
+        WalkFilter::Omit {
+            node_match: NodeMatch::HasTag { tag: String::new() },
+        },
+    ]);
+}
+
+#[test]
+fn view_debug() {
+    let value = Telemetry {
+        count: 3,
+        enabled: true,
+        position: StructWithDouble { x: 1.25 },
+        status: Some(SimpleEnum::Buzz),
+        samples: vec![-1, 2],
+        source: "sensor".into(),
+    };
+    let buf = encode_value_vec_with_format(&value, WireFormat::Absolute);
+    let view = verify_value_with_format::<Telemetry>(&buf, WireFormat::Absolute)
+        .unwrap()
+        .view();
+    assert_eq!(
+        format!("{view:?}"),
+        "TelemetryView { count: 3, enabled: true, position: StructWithDoubleView { x: 1.25 }, \
+         status: Some(Buzz), samples: Verified([Ok(-1), Ok(2)]), source: \"sensor\" }"
+    );
+
+    let value = Foo::<u32, String> {
+        x: 7,
+        y: "y".into(),
+        z: Ok("z".into()),
+    };
+    let buf = encode_value_vec_with_format(&value, WireFormat::Absolute);
+    let view = verify_value_with_format::<Foo<u32, String>>(&buf, WireFormat::Absolute)
+        .unwrap()
+        .view();
+    assert_eq!(
+        format!("{view:?}"),
+        "FooView { x: 7, y: \"y\", z: Ok(\"z\") }"
+    );
+}
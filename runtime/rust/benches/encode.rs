@@ -0,0 +1,67 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use mproto::{Encode, ListLazy, decode_value, encode_value_extend, encode_value_vec, encoded_len};
+
+fn bench_value<E: Encode>(c: &mut Criterion, name: &str, value: E) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(encoded_len(&value) as u64));
+
+    group.bench_function("two_pass", |b| b.iter(|| encode_value_vec(&value)));
+    group.bench_function("growable", |b| {
+        b.iter(|| {
+            let mut buf = Vec::new();
+            encode_value_extend(&value, &mut buf);
+            buf
+        })
+    });
+    group.bench_function("growable_reused", |b| {
+        let mut buf = Vec::new();
+        b.iter(|| {
+            buf.clear();
+            encode_value_extend(&value, &mut buf)
+        })
+    });
+
+    group.finish();
+}
+
+fn encode(c: &mut Criterion) {
+    let u64s = (0..10_000u64).collect::<Vec<_>>();
+    // Re-encoding a lazy list of values without scratch space is a single copy.
+    let u64s_buf = encode_value_vec(&u64s);
+    let lazy_u64s: ListLazy<u64> = decode_value(&u64s_buf).unwrap();
+    bench_value(c, "u64s", u64s);
+    bench_value(c, "lazy_u64s", lazy_u64s);
+    bench_value(
+        c,
+        "strings",
+        (0..1_000).map(|i| format!("string {i}")).collect::<Vec<_>>(),
+    );
+    bench_value(
+        c,
+        "nested",
+        (0..1_000)
+            .map(|i| {
+                (0..i % 8)
+                    .map(|j| (j % 2 == 0).then(|| format!("item {i} {j}")))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>(),
+    );
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+
+    let u64s_buf = encode_value_vec((0..10_000u64).collect::<Vec<_>>());
+    group.throughput(Throughput::Bytes(u64s_buf.len() as u64));
+    group.bench_function("u64s", |b| b.iter(|| decode_value::<Vec<u64>>(&u64s_buf)));
+
+    let f32s_buf = encode_value_vec((0..10_000).map(|i| i as f32).collect::<Vec<_>>());
+    group.throughput(Throughput::Bytes(f32s_buf.len() as u64));
+    group.bench_function("f32s", |b| b.iter(|| decode_value::<Vec<f32>>(&f32s_buf)));
+
+    group.finish();
+}
+
+criterion_group!(benches, encode, decode);
+criterion_main!(benches);
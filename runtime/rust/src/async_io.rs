@@ -0,0 +1,295 @@
+//! Reading and writing framed messages over runtime-agnostic `futures-io` streams.
+//!
+//! Messages are framed as described in the [`framing`](crate::framing) module docs, so the
+//! [`FrameConfig`] of both ends must match.
+
+use core::{
+    future::poll_fn,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+use std::io;
+
+use bytes::{Buf, Bytes, BytesMut};
+use futures_io::{AsyncRead, AsyncWrite};
+
+use crate::{
+    Encode, LazyBuf, Owned,
+    framing::{FrameConfig, FrameError, decode_frame, encode_frame},
+};
+
+/// How much is read from the underlying reader at a time, at most.
+const READ_CHUNK_LEN: usize = 16 * 1024;
+
+/// Default for [`AsyncMessageWriter::with_max_buffered_len`].
+pub const DEFAULT_MAX_BUFFERED_LEN: usize = 64 * 1024;
+
+/// Reads framed `T`s from an [`AsyncRead`].
+///
+/// Messages are read into a single buffer and returned as [`LazyBuf`]s sharing its memory, so
+/// reading doesn't allocate once the buffer has grown to fit the largest message - as long as the
+/// returned `LazyBuf`s are dropped before the buffer fills up again.
+pub struct AsyncMessageReader<T, R> {
+    reader: R,
+    config: FrameConfig,
+    buf: BytesMut,
+    value_ty: PhantomData<fn() -> T>,
+}
+
+impl<T: Owned, R: AsyncRead + Unpin> AsyncMessageReader<T, R> {
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        Self::with_config(reader, FrameConfig::DEFAULT)
+    }
+
+    #[inline]
+    pub fn with_config(reader: R, config: FrameConfig) -> Self {
+        Self {
+            reader,
+            config,
+            buf: BytesMut::new(),
+            value_ty: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn config(&self) -> &FrameConfig {
+        &self.config
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Reading from the underlying reader directly will corrupt the stream of messages, unless
+    /// nothing is buffered.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Any data read but not yet returned as a message is lost.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Bytes read from the underlying reader but not yet returned as a message.
+    #[inline]
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Read the next message, or `None` if the reader ended cleanly between messages.
+    ///
+    /// Cancellation safe: if the returned future is dropped before completing, whatever part of
+    /// a message was read stays buffered and the next call picks up where it left off.
+    ///
+    /// A message that fails to decode is skipped, so reading can go on with the next one. Any
+    /// other error leaves the stream in an unknown state.
+    pub async fn read(&mut self) -> Result<Option<LazyBuf<T, Bytes>>, FrameError> {
+        poll_fn(|cx| self.poll_read(cx)).await
+    }
+
+    /// Poll-based version of [`Self::read`].
+    pub fn poll_read(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<LazyBuf<T, Bytes>>, FrameError>> {
+        loop {
+            if let Some(message) = decode_frame(&self.config, &mut self.buf)? {
+                return Poll::Ready(Ok(Some(message)));
+            }
+
+            let start = self.buf.len();
+            self.buf.resize(start + READ_CHUNK_LEN, 0);
+            let result = Pin::new(&mut self.reader).poll_read(cx, &mut self.buf[start..]);
+            // Only ever keep what was actually read, so there's nothing to clean up if this
+            // future is dropped.
+            let n = match result {
+                Poll::Ready(Ok(n)) => n,
+                Poll::Ready(Err(e)) => {
+                    self.buf.truncate(start);
+                    if e.kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    return Poll::Ready(Err(e.into()));
+                }
+                Poll::Pending => {
+                    self.buf.truncate(start);
+                    return Poll::Pending;
+                }
+            };
+            self.buf.truncate(start + n);
+
+            if n == 0 {
+                if start == 0 {
+                    return Poll::Ready(Ok(None));
+                }
+                return Poll::Ready(Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()));
+            }
+        }
+    }
+}
+
+impl<T, R: core::fmt::Debug> core::fmt::Debug for AsyncMessageReader<T, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncMessageReader")
+            .field("reader", &self.reader)
+            .field("config", &self.config)
+            .field("buffered_len", &self.buf.len())
+            .finish()
+    }
+}
+
+/// Writes framed messages to an [`AsyncWrite`].
+///
+/// Messages are encoded into a write buffer which is reused for every message. Writing a message
+/// only waits for the underlying writer when the buffer is full, so call [`Self::flush`] (or use
+/// [`Self::send`]) to make sure messages actually go out.
+pub struct AsyncMessageWriter<W> {
+    writer: W,
+    config: FrameConfig,
+    buf: BytesMut,
+    max_buffered_len: usize,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncMessageWriter<W> {
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        Self::with_config(writer, FrameConfig::DEFAULT)
+    }
+
+    #[inline]
+    pub fn with_config(writer: W, config: FrameConfig) -> Self {
+        Self {
+            writer,
+            config,
+            buf: BytesMut::new(),
+            max_buffered_len: DEFAULT_MAX_BUFFERED_LEN,
+        }
+    }
+
+    /// Set how many bytes can be buffered before writing a message waits for the buffer to be
+    /// written out. A message is always buffered whole, so the buffer can grow past this by up to
+    /// one message.
+    #[inline]
+    pub fn with_max_buffered_len(mut self, max_buffered_len: usize) -> Self {
+        self.max_buffered_len = max_buffered_len;
+        self
+    }
+
+    #[inline]
+    pub fn config(&self) -> &FrameConfig {
+        &self.config
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Writing to the underlying writer directly will corrupt the stream of messages, unless
+    /// nothing is buffered.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Any buffered messages are lost - flush first to avoid that.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Bytes of encoded messages not yet written to the underlying writer.
+    #[inline]
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Buffer a message, first waiting for room in the buffer if it's full.
+    ///
+    /// Cancellation safe: if the returned future is dropped before completing, `value` wasn't
+    /// buffered, and no partially written data is lost.
+    pub async fn write<E: Encode>(&mut self, value: E) -> Result<(), FrameError> {
+        poll_fn(|cx| self.poll_ready(cx)).await?;
+        self.start_write(value)
+    }
+
+    /// Write a message and flush it, along with any messages buffered before it.
+    ///
+    /// If the returned future is dropped before completing, `value` may or may not have been
+    /// buffered, but is never partially buffered.
+    pub async fn send<E: Encode>(&mut self, value: E) -> Result<(), FrameError> {
+        self.write(value).await?;
+        self.flush().await
+    }
+
+    /// Write out all buffered messages and flush the underlying writer.
+    ///
+    /// Cancellation safe: whatever wasn't written stays buffered.
+    pub async fn flush(&mut self) -> Result<(), FrameError> {
+        poll_fn(|cx| self.poll_flush(cx)).await
+    }
+
+    /// Flush and close the underlying writer.
+    pub async fn close(&mut self) -> Result<(), FrameError> {
+        poll_fn(|cx| self.poll_close(cx)).await
+    }
+
+    /// Wait until there's room in the buffer for another message, writing out buffered messages
+    /// if needed.
+    pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), FrameError>> {
+        while self.buf.len() >= self.max_buffered_len {
+            ready!(self.poll_write_buf(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Buffer a message without waiting for room in the buffer - see [`Self::poll_ready`]. Nothing
+    /// is buffered on error.
+    #[inline]
+    pub fn start_write<E: Encode>(&mut self, value: E) -> Result<(), FrameError> {
+        encode_frame(&self.config, value, &mut self.buf)
+    }
+
+    pub fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), FrameError>> {
+        while !self.buf.is_empty() {
+            ready!(self.poll_write_buf(cx))?;
+        }
+        Poll::Ready(Ok(ready!(Pin::new(&mut self.writer).poll_flush(cx))?))
+    }
+
+    pub fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), FrameError>> {
+        ready!(self.poll_flush(cx))?;
+        Poll::Ready(Ok(ready!(Pin::new(&mut self.writer).poll_close(cx))?))
+    }
+
+    /// Write some of the buffer to the underlying writer.
+    fn poll_write_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match ready!(Pin::new(&mut self.writer).poll_write(cx, &self.buf)) {
+            Ok(0) => Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+            Ok(n) => {
+                // Once everything's written, the next message reuses the start of the buffer.
+                self.buf.advance(n);
+                Poll::Ready(Ok(()))
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => Poll::Ready(Ok(())),
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+impl<W: core::fmt::Debug> core::fmt::Debug for AsyncMessageWriter<W> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncMessageWriter")
+            .field("writer", &self.writer)
+            .field("config", &self.config)
+            .field("buffered_len", &self.buf.len())
+            .field("max_buffered_len", &self.max_buffered_len)
+            .finish()
+    }
+}
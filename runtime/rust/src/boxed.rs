@@ -1,7 +1,10 @@
-use crate::{BaseLen, Compatible, Decode, DecodeCursor, DecodeResult, Encode, EncodeCursor, Owned};
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::boxed::Box;
 
-#[cfg(any(feature = "std", feature = "alloc"))]
-use crate::Lazy;
+use crate::{
+    BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeErrorKind, DecodeLimits,
+    DecodeResult, Encode, EncodeCursor, Lazy, Owned, Verified, WireFormat,
+};
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<T: Owned> Owned for Box<T> {
@@ -10,16 +13,68 @@ impl<T: Owned> Owned for Box<T> {
     fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
         Ok(Box::new(T::lazy_to_owned(lazy.get()?)?))
     }
+
+    #[inline]
+    fn shorten_lazy<'b, 'a: 'b>(lazy: Self::Lazy<'a>) -> Self::Lazy<'b> {
+        lazy
+    }
+
+    fn verify(cursor: &DecodeCursor<'_>) -> DecodeResult<()> {
+        cursor.inner_in_scratch(T::verify)
+    }
+
+    fn copy_encoded(cursor: &DecodeCursor<'_>, out: &mut EncodeCursor) -> DecodeResult<()> {
+        cursor.inner_in_scratch(|cursor| copy_encoded_boxed::<T>(cursor, out))
+    }
+
+    fn copy_scratch_len(cursor: &DecodeCursor<'_>, format: WireFormat) -> DecodeResult<usize> {
+        cursor.inner_in_scratch(|cursor| copy_boxed_scratch_len::<T>(cursor, format))
+    }
+}
+
+/// Re-encode the boxed value at the cursor into the scratch space of `out`, see
+/// [`Owned::copy_encoded`]. A value without scratch space is copied as is.
+fn copy_encoded_boxed<T: Owned>(cursor: &DecodeCursor, out: &mut EncodeCursor) -> DecodeResult<()> {
+    if !T::HAS_SCRATCH {
+        out.scratch(T::BASE_LEN)
+            .copy_from_slice(cursor.base(T::BASE_LEN)?);
+        return Ok(());
+    }
+
+    let mut result = Ok(());
+    out.inner_in_scratch(out.format().base_len::<T>(), |out| {
+        result = T::copy_encoded(cursor, out);
+    });
+    result
+}
+
+/// The scratch space [`copy_encoded_boxed`] takes in `format`.
+fn copy_boxed_scratch_len<T: Owned>(
+    cursor: &DecodeCursor,
+    format: WireFormat,
+) -> DecodeResult<usize> {
+    if !T::HAS_SCRATCH {
+        return Ok(T::BASE_LEN);
+    }
+    Ok(format.base_len::<T>() + T::copy_scratch_len(cursor, format)?)
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<'a, T: Owned> Lazy<'a> for BoxLazy<'a, T> {
     type Owned = Box<T>;
+    // Not the boxed value's view itself, as recursive types would then have infinitely sized views.
+    type View = Verified<BoxLazy<'a, T>>;
+
+    #[inline]
+    fn view(verified: Verified<Self>) -> Self::View {
+        verified
+    }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<T: BaseLen> BaseLen for Box<T> {
     const BASE_LEN: usize = 4;
+    const WIDE_BASE_LEN: usize = 8;
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
@@ -29,16 +84,24 @@ impl<T: Encode> Encode for Box<T> {
         T::BASE_LEN + self.deref().scratch_len()
     }
 
+    fn wide_scratch_len(&self) -> usize {
+        use core::ops::Deref;
+        T::WIDE_BASE_LEN + self.deref().wide_scratch_len()
+    }
+
     fn encode(&self, cursor: &mut EncodeCursor) {
         use core::ops::Deref;
-        cursor.inner_in_scratch(T::BASE_LEN, |cursor| self.deref().encode(cursor));
+        let base_len = cursor.format().base_len::<T>();
+        cursor.inner_in_scratch(base_len, |cursor| self.deref().encode(cursor));
     }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<'a, T: BaseLen + Decode<'a>> Decode<'a> for Box<T> {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        let inner = cursor.inner_in_scratch(|cursor| T::decode(cursor))?;
+        // Many boxes can point at the same value, so charge for each of them.
+        cursor.reserve_owned(core::mem::size_of::<T>())?;
+        let inner = cursor.inner_in_scratch(T::decode)?;
         Ok(Box::new(inner))
     }
 }
@@ -46,6 +109,10 @@ impl<'a, T: BaseLen + Decode<'a>> Decode<'a> for Box<T> {
 pub struct BoxLazy<'a, T: Owned> {
     buffer: &'a [u8],
     offset: usize,
+    format: WireFormat,
+    // Limits and depth of the cursor the boxed value is decoded with.
+    limits: DecodeLimits,
+    depth: usize,
     inner_ty: core::marker::PhantomData<T>,
 }
 
@@ -59,32 +126,75 @@ impl<T: Owned> Compatible<BoxLazy<'_, T>> for Box<T> {}
 
 impl<'a, T: Owned> BaseLen for BoxLazy<'a, T> {
     const BASE_LEN: usize = 4;
+    const WIDE_BASE_LEN: usize = 8;
 }
 
+/// The boxed value is re-encoded with [`Owned::copy_encoded`], and this panics if that fails. It
+/// can't for a value decoded from a buffer that passed [`verify_value`](crate::verify_value), so
+/// verify untrusted buffers before re-encoding values from them.
 impl<'a, T: Owned> Encode for BoxLazy<'a, T> {
     fn scratch_len(&self) -> usize {
-        T::BASE_LEN
+        copy_boxed_scratch_len::<T>(&self.cursor(), WireFormat::Absolute).unwrap()
+    }
+
+    fn wide_scratch_len(&self) -> usize {
+        copy_boxed_scratch_len::<T>(&self.cursor(), WireFormat::Wide).unwrap()
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
-        cursor.inner_in_scratch(T::BASE_LEN, |cursor| {
-            self.get().unwrap().encode(cursor);
-        });
+        copy_encoded_boxed::<T>(&self.cursor(), cursor).unwrap();
     }
 }
 
 impl<'a, T: Owned> BoxLazy<'a, T> {
     pub fn get(&self) -> DecodeResult<T::Lazy<'a>> {
+        Decode::decode(&self.cursor())
+    }
+
+    fn cursor(&self) -> DecodeCursor<'a> {
         DecodeCursor::at_offset(self.buffer, self.offset)
-            .inner_in_scratch(|cursor| Decode::decode(cursor))
+            .with_format(self.format)
+            .with_limits_at_depth(self.limits, self.depth)
+    }
+
+    /// The encoded boxed value, to be forwarded as part of another message without re-encoding
+    /// it - see [`SplicedBox`].
+    ///
+    /// Only a value in [`WireFormat::Relative`] can be moved, so in other formats this fails with
+    /// [`DecodeErrorKind::UnsupportedFormat`]. Offsets in other errors are from the start of the
+    /// boxed value.
+    pub fn spliced(&self) -> DecodeResult<SplicedBox<'a, T>> {
+        if self.format != WireFormat::Relative {
+            return Err(DecodeError::new(
+                DecodeErrorKind::UnsupportedFormat {
+                    format: self.format,
+                },
+                self.offset,
+            ));
+        }
+        SplicedBox::new(&self.buffer[self.offset..])
+    }
+}
+
+/// Infallible access to a verified boxed value, see [`verify_value`](crate::verify_value).
+impl<'a, T: Owned> Verified<BoxLazy<'a, T>> {
+    #[inline]
+    pub fn get(&self) -> <T::Lazy<'a> as Lazy<'a>>::View {
+        Verified::assume_ok(self.inner().get()).view()
     }
 }
 
 impl<'a, T: Owned> Decode<'a> for BoxLazy<'a, T> {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        // Resolve the boxed value's offset up front so that `get` doesn't need to re-read it.
+        let (offset, depth) =
+            cursor.inner_in_scratch(|cursor| Ok((cursor.offset(), cursor.depth())))?;
         Ok(BoxLazy {
             buffer: cursor.buffer(),
-            offset: cursor.offset(),
+            offset,
+            format: cursor.format(),
+            limits: *cursor.limits(),
+            depth,
             inner_ty: core::marker::PhantomData,
         })
     }
@@ -93,11 +203,7 @@ impl<'a, T: Owned> Decode<'a> for BoxLazy<'a, T> {
 impl<T: Owned> Copy for BoxLazy<'_, T> {}
 impl<T: Owned> Clone for BoxLazy<'_, T> {
     fn clone(&self) -> Self {
-        Self {
-            buffer: self.buffer,
-            offset: self.offset,
-            inner_ty: core::marker::PhantomData,
-        }
+        *self
     }
 }
 impl<T> core::fmt::Debug for BoxLazy<'_, T>
@@ -149,3 +255,99 @@ where
     for<'a> T::Lazy<'a>: Eq,
 {
 }
+
+/// The encoding of a `T` in [`WireFormat::Relative`], which is encoded as a `Box<T>` by copying
+/// it into the buffer verbatim. This lets a relay forward a boxed part of a message it received,
+/// from [`BoxLazy::spliced`], without decoding and re-encoding it.
+///
+/// Encoding into a [`WireFormat::Absolute`] or [`WireFormat::Wide`] buffer falls back to
+/// re-encoding the value.
+pub struct SplicedBox<'a, T> {
+    /// The verified buffer the value was decoded from, and the value's encoded length in it.
+    bytes: &'a [u8],
+    len: usize,
+    inner_ty: core::marker::PhantomData<fn() -> T>,
+}
+
+impl<'a, T: Owned> SplicedBox<'a, T> {
+    /// Wrap the start of `bytes`, which must be a `T` encoded in [`WireFormat::Relative`] - e.g.
+    /// by [`encode_value_vec_with_format`](crate::encode_value_vec_with_format) - with nothing
+    /// outside of `bytes` reachable from it. Anything after the encoded value is left out.
+    ///
+    /// The value is taken to end after its encoded length, which holds for values laid out by the
+    /// encoder. For a buffer laid out otherwise, the spliced bytes may leave out part of the value
+    /// and fail to decode on the other end.
+    pub fn new(bytes: &'a [u8]) -> DecodeResult<Self> {
+        let verified = crate::verify_value_with_format::<T>(bytes, WireFormat::Relative)?;
+        let len = T::BASE_LEN + verified.inner().scratch_len();
+        if len > bytes.len() {
+            // Only possible if parts of the value are shared, so it's smaller than its encoding.
+            return Err(DecodeError::new(
+                DecodeErrorKind::UnexpectedEnd,
+                bytes.len(),
+            ));
+        }
+
+        Ok(Self {
+            bytes,
+            len,
+            inner_ty: core::marker::PhantomData,
+        })
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        &self.bytes[..self.len]
+    }
+
+    pub fn get(&self) -> DecodeResult<T::Lazy<'a>> {
+        Decode::decode(&self.cursor())
+    }
+
+    fn cursor(&self) -> DecodeCursor<'a> {
+        DecodeCursor::new(self.bytes).with_format(WireFormat::Relative)
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<T: Owned> Compatible<Box<T>> for SplicedBox<'_, T> {}
+impl<T: Owned> Compatible<BoxLazy<'_, T>> for SplicedBox<'_, T> {}
+
+impl<T> BaseLen for SplicedBox<'_, T> {
+    const BASE_LEN: usize = 4;
+    const WIDE_BASE_LEN: usize = 8;
+}
+
+impl<T: Owned> Encode for SplicedBox<'_, T> {
+    #[inline]
+    fn scratch_len(&self) -> usize {
+        self.len
+    }
+
+    fn wide_scratch_len(&self) -> usize {
+        copy_boxed_scratch_len::<T>(&self.cursor(), WireFormat::Wide).unwrap()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        match cursor.format() {
+            WireFormat::Relative => cursor.scratch(self.len).copy_from_slice(self.as_bytes()),
+            WireFormat::Absolute | WireFormat::Wide => {
+                copy_encoded_boxed::<T>(&self.cursor(), cursor).unwrap()
+            }
+        }
+    }
+}
+
+impl<T> Copy for SplicedBox<'_, T> {}
+impl<T> Clone for SplicedBox<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> core::fmt::Debug for SplicedBox<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("SplicedBox")
+            .field(&&self.bytes[..self.len])
+            .finish()
+    }
+}
@@ -0,0 +1,123 @@
+//! Integration with the `bytes` crate.
+
+use core::slice;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use bytes::{BufMut, Bytes};
+
+use crate::{
+    BaseLen, Compatible, Decode, DecodeCursor, DecodeResult, Encode, EncodeCursor, Lazy, ListLazy,
+    Owned, StableBuf, Verified, encode_value, encode_value_vec, encoded_len,
+};
+
+// SAFETY: `Bytes` points into static or reference-counted memory, which doesn't move along with it
+// and isn't modified while shared.
+unsafe impl StableBuf for Bytes {}
+
+/// Encode `v` at the end of `buf`, returning the encoded length.
+///
+/// The value is encoded in place if `buf`'s next chunk is big enough to hold it - for a `BytesMut`,
+/// [`reserve`](bytes::BytesMut::reserve) [`encoded_len`] bytes up front to make sure - and copied
+/// in from a temporary buffer otherwise.
+///
+/// Panics if `buf` doesn't have enough remaining capacity for the value.
+pub fn encode_to_buf<E: Encode>(v: E, buf: &mut impl BufMut) -> usize {
+    let len = encoded_len(&v);
+
+    let chunk = buf.chunk_mut();
+    if chunk.len() >= len {
+        let dst = chunk.as_mut_ptr();
+        // SAFETY: `chunk` is valid for writes of `len` bytes, and they're initialized before making
+        // a slice of them.
+        let dst = unsafe {
+            dst.write_bytes(0, len);
+            slice::from_raw_parts_mut(dst, len)
+        };
+        encode_value(v, dst);
+        // SAFETY: the first `len` bytes of `chunk` were initialized above.
+        unsafe { buf.advance_mut(len) };
+    } else {
+        buf.put_slice(&encode_value_vec(v));
+    }
+
+    len
+}
+
+/// Like [`decode_value`](crate::decode_value), but owned [`Bytes`] values in the buffer are
+/// decoded as cheap sub-slices of `bytes` instead of being copied out of it.
+#[inline]
+pub fn decode_value_from_bytes<'a, D: Decode<'a>>(bytes: &'a Bytes) -> DecodeResult<D> {
+    D::decode(&DecodeCursor::from_bytes(bytes))
+}
+
+/// `Bytes` can be used in place of `Vec<u8>` for `[u8]` values.
+impl Owned for Bytes {
+    type Lazy<'a> = &'a [u8];
+
+    fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
+        Ok(Bytes::copy_from_slice(lazy))
+    }
+
+    #[inline]
+    fn shorten_lazy<'b, 'a: 'b>(lazy: Self::Lazy<'a>) -> Self::Lazy<'b> {
+        lazy
+    }
+
+    fn verify(cursor: &DecodeCursor<'_>) -> DecodeResult<()> {
+        <&[u8]>::decode(cursor).map(|_| ())
+    }
+}
+
+impl<'a> Lazy<'a> for &'a [u8] {
+    type Owned = Bytes;
+    type View = &'a [u8];
+
+    #[inline]
+    fn view(verified: Verified<Self>) -> Self::View {
+        verified.into_inner()
+    }
+}
+
+impl BaseLen for Bytes {
+    const BASE_LEN: usize = 4 + 4;
+    const WIDE_BASE_LEN: usize = 8 + 8;
+}
+
+impl Encode for Bytes {
+    fn scratch_len(&self) -> usize {
+        self.len()
+    }
+
+    fn wide_scratch_len(&self) -> usize {
+        self.len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        cursor.write_len(self.len());
+        cursor.scratch_bytes(self);
+    }
+}
+
+impl<'a> Decode<'a> for Bytes {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let slice = <&[u8]>::decode(cursor)?;
+        match cursor.source_bytes() {
+            // `slice` was taken from the cursor's buffer, which is `source`.
+            Some(source) => Ok(source.slice_ref(slice)),
+            None => {
+                cursor.reserve_owned(slice.len())?;
+                Ok(Bytes::copy_from_slice(slice))
+            }
+        }
+    }
+}
+
+impl Compatible<Bytes> for Bytes {}
+impl Compatible<Bytes> for Vec<u8> {}
+impl Compatible<Vec<u8>> for Bytes {}
+impl Compatible<Bytes> for [u8] {}
+impl Compatible<[u8]> for Bytes {}
+impl Compatible<Bytes> for ListLazy<'_, u8> {}
+impl Compatible<ListLazy<'_, u8>> for Bytes {}
@@ -0,0 +1,41 @@
+use crate::{DecodeLimits, Owned, decode_value_with_limits, encode_value_vec, encoded_len};
+
+/// How many times its size a canonical buffer may take up once decoded. Scratch regions can't be
+/// shared in a canonical buffer, so every allocation is backed by bytes of its own, and a value
+/// takes at most a few times as much memory as its encoding - a `String` is 24 bytes for an
+/// 8-byte base area.
+const MAX_OWNED_BYTES_PER_BYTE: usize = 8;
+
+/// The limits a buffer is decoded with to check that it's canonical. Hostile buffers can point
+/// many values at the same scratch region to decode to many times their size, which a canonical
+/// buffer can't.
+pub(crate) fn canonical_limits(buf_len: usize) -> DecodeLimits {
+    DecodeLimits {
+        max_owned_bytes: buf_len.saturating_mul(MAX_OWNED_BYTES_PER_BYTE),
+        ..DecodeLimits::DEFAULT
+    }
+}
+
+/// Check that `buf` is exactly the canonical encoding of a `T`, i.e. the bytes that
+/// [`encode_value`](crate::encode_value) would produce for the value it holds.
+///
+/// Besides being a valid `T` (see [`verify_value`](crate::verify_value)), a canonical buffer has
+/// zeroed padding, no trailing or unreachable scratch bytes, and its scratch regions laid out in
+/// the order the encoder writes them. Equal values always have the same canonical encoding, so
+/// canonical buffers can be hashed, signed and compared byte-for-byte.
+///
+/// The check decodes the owned value and re-encodes it into a temporary buffer to compare to
+/// `buf`. Re-encoding the lazy value wouldn't do, as that copies parts of the buffer as they are,
+/// padding and all. Decoding allocates at most a small multiple of `buf.len()` bytes, so it's
+/// safe to check untrusted buffers.
+pub fn is_canonical<T: Owned>(buf: &[u8]) -> bool {
+    let Ok(value) = decode_value_with_limits::<T>(buf, canonical_limits(buf.len())) else {
+        return false;
+    };
+    // Cheap rejection of trailing bytes before re-encoding.
+    if encoded_len(&value) != buf.len() {
+        return false;
+    }
+
+    encode_value_vec(&value) == buf
+}
@@ -0,0 +1,147 @@
+//! Checksums that don't need an allocator or any dependencies.
+
+/// 64-bit FNV-1a, continuing from `hash` - start from [`FNV1A_OFFSET_BASIS`].
+pub(crate) const fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = (hash ^ bytes[i] as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+pub(crate) const FNV1A_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// CRC32C (Castagnoli) of `bytes`, computed eight bytes at a time with lookup tables built at
+/// compile time.
+pub(crate) fn crc32c(bytes: &[u8]) -> u32 {
+    let (words, rest) = bytes.as_chunks::<8>();
+    let mut crc = !0u32;
+    for word in words {
+        let [a, b, c, d, e, f, g, h] = *word;
+        let low = crc ^ u32::from_le_bytes([a, b, c, d]);
+        crc = CRC32C_TABLES[7][low as u8 as usize]
+            ^ CRC32C_TABLES[6][(low >> 8) as u8 as usize]
+            ^ CRC32C_TABLES[5][(low >> 16) as u8 as usize]
+            ^ CRC32C_TABLES[4][(low >> 24) as usize]
+            ^ CRC32C_TABLES[3][e as usize]
+            ^ CRC32C_TABLES[2][f as usize]
+            ^ CRC32C_TABLES[1][g as usize]
+            ^ CRC32C_TABLES[0][h as usize];
+    }
+    for &byte in rest {
+        crc = (crc >> 8) ^ CRC32C_TABLES[0][(crc as u8 ^ byte) as usize];
+    }
+    !crc
+}
+
+/// The reversed Castagnoli polynomial.
+const CRC32C_POLY: u32 = 0x82f6_3b78;
+
+/// `CRC32C_TABLES[0]` is the CRC of each byte, and `CRC32C_TABLES[k]` the CRC of each byte
+/// followed by `k` zero bytes.
+static CRC32C_TABLES: [[u32; 256]; 8] = crc32c_tables();
+
+const fn crc32c_tables() -> [[u32; 256]; 8] {
+    let mut tables = [[0u32; 256]; 8];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = (crc >> 1) ^ (CRC32C_POLY & (crc & 1).wrapping_neg());
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+
+    let mut k = 1;
+    while k < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let prev = tables[k - 1][i];
+            tables[k][i] = (prev >> 8) ^ tables[0][prev as u8 as usize];
+            i += 1;
+        }
+        k += 1;
+    }
+    tables
+}
+
+const XXH_PRIME64_1: u64 = 0x9e37_79b1_85eb_ca87;
+const XXH_PRIME64_2: u64 = 0xc2b2_ae3d_27d4_eb4f;
+const XXH_PRIME64_3: u64 = 0x1656_67b1_9e37_79f9;
+const XXH_PRIME64_4: u64 = 0x85eb_ca77_c2b2_ae63;
+const XXH_PRIME64_5: u64 = 0x27d4_eb2f_1656_67c5;
+
+/// xxHash64 of `bytes` with the given seed.
+pub(crate) fn xxhash64(bytes: &[u8], seed: u64) -> u64 {
+    let (stripes, rest) = bytes.as_chunks::<32>();
+    let mut hash = if stripes.is_empty() {
+        seed.wrapping_add(XXH_PRIME64_5)
+    } else {
+        let mut acc = [
+            seed.wrapping_add(XXH_PRIME64_1).wrapping_add(XXH_PRIME64_2),
+            seed.wrapping_add(XXH_PRIME64_2),
+            seed,
+            seed.wrapping_sub(XXH_PRIME64_1),
+        ];
+        for stripe in stripes {
+            let (lanes, _) = stripe.as_chunks::<8>();
+            for (acc, lane) in acc.iter_mut().zip(lanes) {
+                *acc = xxh64_round(*acc, u64::from_le_bytes(*lane));
+            }
+        }
+
+        let mut hash = acc[0]
+            .rotate_left(1)
+            .wrapping_add(acc[1].rotate_left(7))
+            .wrapping_add(acc[2].rotate_left(12))
+            .wrapping_add(acc[3].rotate_left(18));
+        for acc in acc {
+            hash = (hash ^ xxh64_round(0, acc))
+                .wrapping_mul(XXH_PRIME64_1)
+                .wrapping_add(XXH_PRIME64_4);
+        }
+        hash
+    };
+    hash = hash.wrapping_add(bytes.len() as u64);
+
+    let (lanes, rest) = rest.as_chunks::<8>();
+    for lane in lanes {
+        hash ^= xxh64_round(0, u64::from_le_bytes(*lane));
+        hash = hash
+            .rotate_left(27)
+            .wrapping_mul(XXH_PRIME64_1)
+            .wrapping_add(XXH_PRIME64_4);
+    }
+    let rest = match rest.split_first_chunk::<4>() {
+        Some((half, rest)) => {
+            hash ^= (u32::from_le_bytes(*half) as u64).wrapping_mul(XXH_PRIME64_1);
+            hash = hash
+                .rotate_left(23)
+                .wrapping_mul(XXH_PRIME64_2)
+                .wrapping_add(XXH_PRIME64_3);
+            rest
+        }
+        None => rest,
+    };
+    for &byte in rest {
+        hash ^= (byte as u64).wrapping_mul(XXH_PRIME64_5);
+        hash = hash.rotate_left(11).wrapping_mul(XXH_PRIME64_1);
+    }
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(XXH_PRIME64_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(XXH_PRIME64_3);
+    hash ^ (hash >> 32)
+}
+
+#[inline]
+fn xxh64_round(acc: u64, lane: u64) -> u64 {
+    acc.wrapping_add(lane.wrapping_mul(XXH_PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(XXH_PRIME64_1)
+}
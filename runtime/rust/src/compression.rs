@@ -0,0 +1,310 @@
+//! Compression of encoded messages.
+//!
+//! Base areas are padded - integers are always full width and enums take up as much space as
+//! their largest variant - so encoded messages tend to contain lots of zero bytes.
+//! [`Compression::Zrle`] squeezes those out cheaply, and with the `lz4` feature
+//! [`Compression::Lz4`] also compresses repeated data.
+//!
+//! # ZRLE
+//!
+//! Zero-run-length encoding works like Cap'n Proto's packing, on 8-byte words. A ZRLE buffer
+//! starts with a byte holding how many zero bytes were added to make the input a whole number of
+//! words, followed by the packed words. Each packed word starts with a tag byte whose bits say
+//! which of the word's bytes are non-zero, followed by those bytes. Then:
+//! - After a `0x00` tag, a count byte of how many more zero words follow.
+//! - After a `0xff` tag, a count byte of how many more words follow as-is, 8 bytes each.
+//!
+//! The ZRLE functions work on plain slices, so they can be used without an allocator.
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::{vec, vec::Vec};
+
+use crate::{DecodeError, DecodeErrorKind, DecodeResult};
+#[cfg(any(feature = "std", feature = "alloc"))]
+use crate::{Encode, LazyBuf, Owned, encode_value_vec};
+
+/// A compression scheme for encoded messages.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Zero-run-length encoding, see the [module docs](self).
+    Zrle,
+    /// LZ4 block compression, with the decompressed length prepended as a little-endian `u32`.
+    #[cfg(feature = "lz4")]
+    Lz4,
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Compression {
+    pub fn compress(self, input: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Zrle => {
+                let mut out = vec![0; zrle_max_compressed_len(input.len())];
+                let len = zrle_compress(input, &mut out);
+                out.truncate(len);
+                out
+            }
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => lz4_flex::compress_prepend_size(input),
+        }
+    }
+
+    /// Decompress `input`, failing with
+    /// [`DecompressedLenLimitExceeded`](DecodeErrorKind::DecompressedLenLimitExceeded) before
+    /// allocating if it would decompress to more than `max_len` bytes. A small buffer can
+    /// decompress to a huge one - ZRLE packs 2048 zero bytes into 2 - so `max_len` should be the
+    /// largest message expected.
+    pub fn decompress(self, input: &[u8], max_len: usize) -> DecodeResult<Vec<u8>> {
+        match self {
+            Self::Zrle => {
+                let len = check_decompressed_len(zrle_decompressed_len(input)?, max_len)?;
+                let mut out = vec![0; len];
+                zrle_decompress(input, &mut out)?;
+                Ok(out)
+            }
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => lz4_decompress(input, max_len),
+        }
+    }
+}
+
+/// Encode `v` and compress it.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn encode_value_compressed<E: Encode>(v: E, compression: Compression) -> Vec<u8> {
+    compression.compress(&encode_value_vec(v))
+}
+
+/// Decompress a message compressed by [`encode_value_compressed`], and lazily decode it. Messages
+/// that decompress to more than `max_len` bytes are rejected, see [`Compression::decompress`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn decode_compressed<T: Owned>(
+    buf: &[u8],
+    compression: Compression,
+    max_len: usize,
+) -> DecodeResult<LazyBuf<T, Vec<u8>>> {
+    LazyBuf::try_new(compression.decompress(buf, max_len)?)
+}
+
+/// The most bytes [`zrle_compress`] can write for `len` bytes of input.
+pub const fn zrle_max_compressed_len(len: usize) -> usize {
+    // A word takes at most 10 bytes: a `0xff` tag, the word and a count of 0.
+    1 + len.div_ceil(8) * 10
+}
+
+/// Compress `input` into `out`, which must be at least [`zrle_max_compressed_len`] bytes long.
+/// Returns the compressed length.
+pub fn zrle_compress(input: &[u8], out: &mut [u8]) -> usize {
+    assert!(
+        out.len() >= zrle_max_compressed_len(input.len()),
+        "buffer too small to compress into"
+    );
+
+    let (words, rest) = input.as_chunks::<8>();
+    let mut last = [0u8; 8];
+    last[..rest.len()].copy_from_slice(rest);
+    let last = (!rest.is_empty()).then_some(&last);
+    let mut words = words.iter().chain(last).peekable();
+
+    out[0] = ((8 - rest.len()) % 8) as u8;
+    let mut len = 1;
+    while let Some(word) = words.next() {
+        let tag_offset = len;
+        len += 1;
+        let mut tag = 0u8;
+        for (i, &byte) in word.iter().enumerate() {
+            if byte != 0 {
+                tag |= 1 << i;
+                out[len] = byte;
+                len += 1;
+            }
+        }
+        out[tag_offset] = tag;
+
+        match tag {
+            0x00 => {
+                let mut count = 0u8;
+                while count < u8::MAX && words.next_if(|word| **word == [0; 8]).is_some() {
+                    count += 1;
+                }
+                out[len] = count;
+                len += 1;
+            }
+            0xff => {
+                // Copy words as-is until one has at least two zero bytes, which is worth packing.
+                let count_offset = len;
+                len += 1;
+                let mut count = 0u8;
+                while count < u8::MAX
+                    && let Some(word) =
+                        words.next_if(|word| word.iter().filter(|&&byte| byte == 0).count() < 2)
+                {
+                    out[len..len + 8].copy_from_slice(word);
+                    len += 8;
+                    count += 1;
+                }
+                out[count_offset] = count;
+            }
+            _ => {}
+        }
+    }
+
+    len
+}
+
+/// The length of the data compressed in `input`, or an error if it isn't valid ZRLE.
+pub fn zrle_decompressed_len(input: &[u8]) -> DecodeResult<usize> {
+    let mut words = 0usize;
+    let mut reader = ZrleReader::new(input)?;
+    while let Some(run) = reader.next_run()? {
+        words += run.words();
+    }
+    reader.finish(words)
+}
+
+/// Decompress `input` into `out`, which must be at least [`zrle_decompressed_len`] bytes long.
+/// Returns the decompressed length.
+pub fn zrle_decompress(input: &[u8], out: &mut [u8]) -> DecodeResult<usize> {
+    // Bytes written, including the last word's padding, which doesn't have to fit in `out`.
+    let mut written = 0;
+    let mut reader = ZrleReader::new(input)?;
+    while let Some(run) = reader.next_run()? {
+        let end = written + run.words() * 8;
+        let dst = out.get_mut(written..end.min(out.len())).unwrap_or_default();
+        match run {
+            ZrleRun::Packed { tag, bytes } => {
+                let mut word = [0u8; 8];
+                let mut bytes = bytes.iter();
+                for (i, byte) in word.iter_mut().enumerate() {
+                    if tag & (1 << i) != 0 {
+                        *byte = *bytes.next().expect("the tag's bytes were read");
+                    }
+                }
+                dst.copy_from_slice(&word[..dst.len()]);
+            }
+            ZrleRun::Zeros { .. } => dst.fill(0),
+            ZrleRun::Raw { bytes } => dst.copy_from_slice(&bytes[..dst.len()]),
+        }
+        written = end;
+    }
+
+    let len = reader.finish(written / 8)?;
+    assert!(len <= out.len(), "buffer too small to decompress into");
+    Ok(len)
+}
+
+enum ZrleRun<'a> {
+    /// A word with only the bytes marked in `tag` non-zero.
+    Packed {
+        tag: u8,
+        bytes: &'a [u8],
+    },
+    Zeros {
+        words: usize,
+    },
+    /// Whole words copied as-is.
+    Raw {
+        bytes: &'a [u8],
+    },
+}
+
+impl ZrleRun<'_> {
+    fn words(&self) -> usize {
+        match self {
+            Self::Packed { .. } => 1,
+            Self::Zeros { words } => *words,
+            Self::Raw { bytes } => bytes.len() / 8,
+        }
+    }
+}
+
+struct ZrleReader<'a> {
+    input: &'a [u8],
+    offset: usize,
+    padding: usize,
+    /// Raw words to yield after a `0xff` word.
+    raw_words: Option<usize>,
+}
+
+impl<'a> ZrleReader<'a> {
+    fn new(input: &'a [u8]) -> DecodeResult<Self> {
+        match input.first() {
+            Some(&padding) if padding < 8 => Ok(Self {
+                input,
+                offset: 1,
+                padding: padding as usize,
+                raw_words: None,
+            }),
+            _ => Err(invalid(0)),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> DecodeResult<&'a [u8]> {
+        let bytes = self
+            .input
+            .get(self.offset..self.offset + len)
+            .ok_or(invalid(self.offset))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn next_run(&mut self) -> DecodeResult<Option<ZrleRun<'a>>> {
+        if let Some(words) = self.raw_words.take() {
+            return Ok(Some(ZrleRun::Raw {
+                bytes: self.take(words * 8)?,
+            }));
+        }
+        if self.offset == self.input.len() {
+            return Ok(None);
+        }
+
+        let tag = self.take(1)?[0];
+        let bytes = self.take(tag.count_ones() as usize)?;
+        match tag {
+            0x00 => Ok(Some(ZrleRun::Zeros {
+                words: 1 + self.take(1)?[0] as usize,
+            })),
+            0xff => {
+                self.raw_words = Some(self.take(1)?[0] as usize);
+                Ok(Some(ZrleRun::Raw { bytes }))
+            }
+            _ => Ok(Some(ZrleRun::Packed { tag, bytes })),
+        }
+    }
+
+    /// The decompressed length of `words` words, checking that the padding fits in them.
+    fn finish(&self, words: usize) -> DecodeResult<usize> {
+        if words == 0 && self.padding != 0 {
+            return Err(invalid(0));
+        }
+        Ok(words * 8 - self.padding)
+    }
+}
+
+#[cold]
+fn invalid(offset: usize) -> DecodeError {
+    DecodeError::new(DecodeErrorKind::InvalidCompressedData, offset)
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn check_decompressed_len(len: usize, max_len: usize) -> DecodeResult<usize> {
+    if len > max_len {
+        return Err(DecodeError::new(
+            DecodeErrorKind::DecompressedLenLimitExceeded { len },
+            0,
+        ));
+    }
+    Ok(len)
+}
+
+#[cfg(feature = "lz4")]
+fn lz4_decompress(input: &[u8], max_len: usize) -> DecodeResult<Vec<u8>> {
+    // LZ4 can't compress by more than a factor of 255, so a larger length is bogus - and
+    // shouldn't be allocated.
+    let len = input
+        .first_chunk::<4>()
+        .map(|len| u32::from_le_bytes(*len) as usize)
+        .filter(|&len| len <= (input.len() - 4).saturating_mul(255))
+        .ok_or(invalid(0))?;
+    let len = check_decompressed_len(len, max_len)?;
+    lz4_flex::decompress(&input[4..], len).map_err(|_| invalid(4))
+}
@@ -0,0 +1,682 @@
+//! An append-only file format for large numbers of records of one type, e.g. for replay or
+//! analytics.
+//!
+//! A container file is laid out as:
+//!
+//! | Bytes | Contents |
+//! |-------|----------|
+//! | 24    | Header, see below |
+//! | ..    | Blocks of records, each ended by a checkpoint |
+//! | ..    | An index of where each record starts, and a footer - only if the writer was configured with [`ContainerConfig::index`] and [finished](ContainerWriter::finish) |
+//!
+//! The header holds [`CONTAINER_MAGIC`], the format version as a little-endian `u16`, two zero
+//! bytes, the [`Fingerprint`] of the record type as a little-endian `u64`, and the CRC32C of those
+//! 20 bytes as a little-endian `u32`.
+//!
+//! Each record is its encoded length as a little-endian `u32`, followed by the encoded value -
+//! like the messages of [`write_message`](crate::write_message). Lengths of `0xffff_fff0` and up
+//! are tags instead, and all of the following integers are little-endian:
+//! - `0xffff_fffe` is a checkpoint, which ends a block. It's followed by the number of records in
+//!   the block as a `u32`, and the CRC32C of the whole block up to there as a `u32`.
+//! - `0xffff_ffff` is the index, which follows the last block. It's followed by the number of
+//!   records as a `u64`, the file offset of each record as a `u64`, and the CRC32C of the index up
+//!   to there as a `u32`. The file then ends with a footer: the file offset of the index as a
+//!   `u64`, and the magic bytes `MPROTOIX`.
+//!
+//! Records are only read from whole blocks whose checksum matches. If a writer crashed partway
+//! through a block, [`ContainerReader`] stops with [`ContainerError::Truncated`] after the last
+//! whole block, and [`ContainerWriter::open_append`] cuts the file back to there before appending
+//! more records.
+
+use core::{fmt, marker::PhantomData, ops::Range};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use crate::{
+    Compatible, DecodeError, EncodeError, Fingerprint, LazyBuf, Owned, Verified, checksum::crc32c,
+    encode_value, encoded_len, verify_value,
+};
+
+/// The magic bytes a container file starts with.
+pub const CONTAINER_MAGIC: [u8; 8] = *b"MPROTOCF";
+
+/// The container format version written by [`ContainerWriter`]. [`ContainerReader`] rejects any
+/// other version.
+pub const CONTAINER_VERSION: u16 = 1;
+
+/// Length of the header at the start of a container file.
+pub const CONTAINER_HEADER_LEN: usize = 24;
+
+const INDEX_FOOTER_MAGIC: [u8; 8] = *b"MPROTOIX";
+const INDEX_FOOTER_LEN: usize = 16;
+
+/// Record lengths from here up are tags.
+const FIRST_TAG: u32 = 0xffff_fff0;
+const CHECKPOINT_TAG: u32 = 0xffff_fffe;
+const INDEX_TAG: u32 = 0xffff_ffff;
+
+const RECORD_LEN_PREFIX_LEN: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContainerConfig {
+    /// Maximum number of records in a block. Records are written out a block at a time, so a
+    /// crash loses the records of the current block - call [`ContainerWriter::flush`] to end it
+    /// early.
+    pub block_records: u32,
+    /// A block is also ended once its records take up this many bytes.
+    pub max_block_len: usize,
+    /// Whether [`ContainerWriter::finish`] writes an index for random access to records. The
+    /// writer keeps 8 bytes per record in memory until then.
+    pub index: bool,
+}
+
+impl ContainerConfig {
+    pub const DEFAULT: Self = Self {
+        block_records: 1024,
+        max_block_len: 1024 * 1024,
+        index: true,
+    };
+}
+
+impl Default for ContainerConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Debug)]
+pub enum ContainerError {
+    /// The file doesn't start with a valid container header.
+    InvalidHeader,
+    /// The file was written by an unknown version of the format.
+    UnsupportedVersion {
+        version: u16,
+    },
+    /// The file holds records of a different type, according to their [`Fingerprint`].
+    TypeMismatch {
+        expected: u64,
+        found: u64,
+    },
+    /// The block or index at `offset` doesn't match its checksum. Reading continues with the next
+    /// block.
+    ChecksumMismatch {
+        offset: u64,
+    },
+    /// The file ends partway through a block, e.g. because its writer crashed. Only the records
+    /// before `valid_len` can be read.
+    Truncated {
+        valid_len: u64,
+    },
+    /// The record at `offset` has an invalid length prefix, so nothing after it can be read.
+    InvalidRecord {
+        offset: u64,
+    },
+    /// The file has no index, e.g. because its writer wasn't finished.
+    MissingIndex,
+    /// A record past the end of the file was requested.
+    IndexOutOfBounds {
+        index: u64,
+        len: u64,
+    },
+    /// A record failed to decode.
+    Decode(DecodeError),
+    /// A record failed to encode.
+    Encode(EncodeError),
+    Io(io::Error),
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid container header"),
+            Self::UnsupportedVersion { version } => {
+                write!(f, "unsupported container version {version}")
+            }
+            Self::TypeMismatch { expected, found } => write!(
+                f,
+                "container type mismatch: expected fingerprint {expected:#x}, found {found:#x}"
+            ),
+            Self::ChecksumMismatch { offset } => {
+                write!(f, "container checksum mismatch at offset {offset}")
+            }
+            Self::Truncated { valid_len } => {
+                write!(f, "container truncated after offset {valid_len}")
+            }
+            Self::InvalidRecord { offset } => {
+                write!(f, "invalid container record at offset {offset}")
+            }
+            Self::MissingIndex => write!(f, "container has no index"),
+            Self::IndexOutOfBounds { index, len } => write!(
+                f,
+                "record {index} out of bounds for container of {len} records"
+            ),
+            Self::Decode(e) => write!(f, "failed to decode container record: {e}"),
+            Self::Encode(e) => write!(f, "failed to encode container record: {e}"),
+            Self::Io(e) => write!(f, "container I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ContainerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(e) => Some(e),
+            Self::Encode(e) => Some(e),
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DecodeError> for ContainerError {
+    fn from(e: DecodeError) -> Self {
+        Self::Decode(e)
+    }
+}
+
+impl From<EncodeError> for ContainerError {
+    fn from(e: EncodeError) -> Self {
+        Self::Encode(e)
+    }
+}
+
+impl From<io::Error> for ContainerError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// What [`ContainerWriter::open_append`] found in an existing container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recovery {
+    /// Records kept from the existing container.
+    pub records: u64,
+    /// Bytes of a partial block cut off the end of the file.
+    pub truncated_bytes: u64,
+}
+
+/// Writes records of type `T` to a container.
+///
+/// Records still in the current block are lost if the writer is dropped without calling
+/// [`flush`](Self::flush) or [`finish`](Self::finish).
+pub struct ContainerWriter<T, W: Write> {
+    w: W,
+    config: ContainerConfig,
+    /// The current block, not yet written to `w`.
+    block: Vec<u8>,
+    block_records: u32,
+    /// File offset of the start of `block`.
+    block_offset: u64,
+    records: u64,
+    /// File offsets of the records, if writing an index.
+    offsets: Option<Vec<u64>>,
+    _record: PhantomData<fn(T)>,
+}
+
+impl<T: Fingerprint, W: Write> ContainerWriter<T, W> {
+    /// Start a new container, writing its header to `w`.
+    pub fn new(mut w: W, config: ContainerConfig) -> Result<Self, ContainerError> {
+        w.write_all(&header_bytes(T::FINGERPRINT))?;
+        Ok(Self::resume(
+            w,
+            config,
+            CONTAINER_HEADER_LEN as u64,
+            0,
+            Vec::new(),
+        ))
+    }
+
+    fn resume(w: W, config: ContainerConfig, len: u64, records: u64, offsets: Vec<u64>) -> Self {
+        Self {
+            w,
+            config,
+            block: Vec::new(),
+            block_records: 0,
+            block_offset: len,
+            records,
+            offsets: config.index.then_some(offsets),
+            _record: PhantomData,
+        }
+    }
+
+    /// Append a record. Returns its record number.
+    pub fn push(&mut self, value: impl Compatible<T>) -> Result<u64, ContainerError> {
+        let len = encoded_len(&value);
+        let prefix = u32::try_from(len)
+            .ok()
+            .filter(|&len| len < FIRST_TAG)
+            .ok_or(EncodeError::MessageTooLarge)?;
+
+        let start = self.block.len();
+        if let Some(offsets) = &mut self.offsets {
+            offsets.push(self.block_offset + start as u64);
+        }
+        self.block.extend_from_slice(&prefix.to_le_bytes());
+        self.block.resize(start + RECORD_LEN_PREFIX_LEN + len, 0);
+        encode_value(value, &mut self.block[start + RECORD_LEN_PREFIX_LEN..]);
+
+        self.block_records += 1;
+        self.records += 1;
+        if self.block_records >= self.config.block_records
+            || self.block.len() >= self.config.max_block_len
+        {
+            self.end_block()?;
+        }
+        Ok(self.records - 1)
+    }
+
+    /// Number of records in the container so far.
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.records
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+
+    /// End the current block and flush the underlying writer, so that every record pushed so far
+    /// is written out.
+    pub fn flush(&mut self) -> Result<(), ContainerError> {
+        self.end_block()?;
+        self.w.flush()?;
+        Ok(())
+    }
+
+    /// End the last block and write the index, if configured. Returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, ContainerError> {
+        self.end_block()?;
+
+        if let Some(offsets) = self.offsets.take() {
+            let mut index = Vec::with_capacity(16 + offsets.len() * 8 + INDEX_FOOTER_LEN);
+            index.extend_from_slice(&INDEX_TAG.to_le_bytes());
+            index.extend_from_slice(&(offsets.len() as u64).to_le_bytes());
+            for offset in offsets {
+                index.extend_from_slice(&offset.to_le_bytes());
+            }
+            let crc = crc32c(&index);
+            index.extend_from_slice(&crc.to_le_bytes());
+            index.extend_from_slice(&self.block_offset.to_le_bytes());
+            index.extend_from_slice(&INDEX_FOOTER_MAGIC);
+            self.w.write_all(&index)?;
+        }
+
+        self.w.flush()?;
+        Ok(self.w)
+    }
+
+    fn end_block(&mut self) -> io::Result<()> {
+        if self.block_records == 0 {
+            return Ok(());
+        }
+
+        self.block.extend_from_slice(&CHECKPOINT_TAG.to_le_bytes());
+        self.block
+            .extend_from_slice(&self.block_records.to_le_bytes());
+        let crc = crc32c(&self.block);
+        self.block.extend_from_slice(&crc.to_le_bytes());
+        self.w.write_all(&self.block)?;
+
+        self.block_offset += self.block.len() as u64;
+        self.block.clear();
+        self.block_records = 0;
+        Ok(())
+    }
+}
+
+impl<T: Owned + Fingerprint> ContainerWriter<T, File> {
+    /// Create a container file at `path`, replacing any existing file.
+    pub fn create(path: impl AsRef<Path>, config: ContainerConfig) -> Result<Self, ContainerError> {
+        Self::new(File::create(path)?, config)
+    }
+
+    /// Open the container file at `path` to append more records to it.
+    ///
+    /// Its index is dropped, to be written again by [`finish`](Self::finish). If its writer
+    /// crashed, the partial block at the end is cut off - see [`Recovery`]. Any other damage to
+    /// the file is returned as an error rather than cutting off the records after it.
+    pub fn open_append(
+        path: impl AsRef<Path>,
+        config: ContainerConfig,
+    ) -> Result<(Self, Recovery), ContainerError> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let file_len = file.metadata()?.len();
+
+        let mut reader = ContainerReader::<T, _>::new(BufReader::new(&file))?;
+        let mut offsets = Vec::new();
+        let truncated = loop {
+            match reader.read_block() {
+                Ok(true) => {
+                    let block_offset = reader.block_offset;
+                    if config.index {
+                        offsets.extend(reader.records.iter().map(|record| {
+                            block_offset + (record.start - RECORD_LEN_PREFIX_LEN) as u64
+                        }));
+                    }
+                    reader.record_count += reader.records.len() as u64;
+                }
+                Ok(false) => break false,
+                Err(ContainerError::Truncated { .. }) => break true,
+                Err(e) => return Err(e),
+            }
+        };
+        let (len, records) = (reader.pos, reader.record_count);
+        drop(reader);
+
+        file.set_len(len)?;
+        file.seek(SeekFrom::Start(len))?;
+        let recovery = Recovery {
+            records,
+            truncated_bytes: if truncated { file_len - len } else { 0 },
+        };
+        Ok((Self::resume(file, config, len, records, offsets), recovery))
+    }
+}
+
+/// Reads the records of type `T` from a container.
+///
+/// Records are read a block at a time, checking the block's checksum first. Containers with an
+/// index also support random access to records with [`get`](Self::get), if `R` is [`Seek`].
+pub struct ContainerReader<T, R> {
+    r: R,
+    /// The current block, including its checkpoint.
+    block: Vec<u8>,
+    /// The records in `block`, and the next one to read.
+    records: Vec<Range<usize>>,
+    next: usize,
+    /// File offsets of the start of `block`, and of the end of the last whole block.
+    block_offset: u64,
+    pos: u64,
+    /// Records in the blocks before the current one.
+    record_count: u64,
+    ended: bool,
+    index: Option<Vec<u64>>,
+    _record: PhantomData<fn() -> T>,
+}
+
+impl<T: Owned + Fingerprint> ContainerReader<T, BufReader<File>> {
+    /// Open the container file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ContainerError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<T: Owned + Fingerprint, R: Read> ContainerReader<T, R> {
+    /// Read a container's header from `r`, checking that it holds records of type `T`.
+    pub fn new(mut r: R) -> Result<Self, ContainerError> {
+        let mut header = [0u8; CONTAINER_HEADER_LEN];
+        r.read_exact(&mut header).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => ContainerError::InvalidHeader,
+            _ => e.into(),
+        })?;
+        let crc = u32::from_le_bytes(header[20..].try_into().unwrap());
+        if header[..8] != CONTAINER_MAGIC || crc32c(&header[..20]) != crc {
+            return Err(ContainerError::InvalidHeader);
+        }
+        let version = u16::from_le_bytes([header[8], header[9]]);
+        if version != CONTAINER_VERSION {
+            return Err(ContainerError::UnsupportedVersion { version });
+        }
+        let fingerprint = u64::from_le_bytes(header[12..20].try_into().unwrap());
+        if fingerprint != T::FINGERPRINT {
+            return Err(ContainerError::TypeMismatch {
+                expected: T::FINGERPRINT,
+                found: fingerprint,
+            });
+        }
+
+        Ok(Self {
+            r,
+            block: Vec::new(),
+            records: Vec::new(),
+            next: 0,
+            block_offset: CONTAINER_HEADER_LEN as u64,
+            pos: CONTAINER_HEADER_LEN as u64,
+            record_count: 0,
+            ended: false,
+            index: None,
+            _record: PhantomData,
+        })
+    }
+
+    /// Read the next record, or `None` at the end of the container.
+    ///
+    /// Errors don't necessarily end the container: after a [`ContainerError::ChecksumMismatch`]
+    /// reading continues with the next block, and after a [`ContainerError::Decode`] with the
+    /// next record.
+    pub fn next_record(&mut self) -> Result<Option<Verified<T::Lazy<'_>>>, ContainerError> {
+        match self.next_record_bytes()? {
+            Some(bytes) => Ok(Some(verify_value::<T>(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn next_record_bytes(&mut self) -> Result<Option<&[u8]>, ContainerError> {
+        while self.next == self.records.len() {
+            if !self.read_block()? {
+                return Ok(None);
+            }
+            self.record_count += self.records.len() as u64;
+        }
+
+        self.next += 1;
+        Ok(Some(&self.block[self.records[self.next - 1].clone()]))
+    }
+
+    /// Read the next block into `self.block`, checking its checksum. Returns `false` at the end
+    /// of the container.
+    fn read_block(&mut self) -> Result<bool, ContainerError> {
+        self.block.clear();
+        self.records.clear();
+        self.next = 0;
+        self.block_offset = self.pos;
+        if self.ended {
+            return Ok(false);
+        }
+
+        loop {
+            let Some(tag) = self.read_u32()? else {
+                if self.block.is_empty() {
+                    self.ended = true;
+                    return Ok(false);
+                }
+                return Err(self.truncated());
+            };
+
+            match tag {
+                INDEX_TAG if self.block.is_empty() => {
+                    self.ended = true;
+                    return Ok(false);
+                }
+                CHECKPOINT_TAG => {
+                    self.block.extend_from_slice(&tag.to_le_bytes());
+                    let (Some(count), Some(crc)) = (self.read_u32()?, self.read_u32()?) else {
+                        return Err(self.truncated());
+                    };
+                    self.block.extend_from_slice(&count.to_le_bytes());
+                    self.pos += self.block.len() as u64 + 4;
+
+                    if crc32c(&self.block) != crc || count as usize != self.records.len() {
+                        self.records.clear();
+                        return Err(ContainerError::ChecksumMismatch {
+                            offset: self.block_offset,
+                        });
+                    }
+                    return Ok(true);
+                }
+                len if len >= FIRST_TAG => {
+                    self.ended = true;
+                    return Err(ContainerError::InvalidRecord {
+                        offset: self.block_offset + self.block.len() as u64,
+                    });
+                }
+                len => {
+                    self.block.extend_from_slice(&tag.to_le_bytes());
+                    let start = self.block.len();
+                    // Grow the block as data arrives rather than trusting the length up front.
+                    (&mut self.r)
+                        .take(len as u64)
+                        .read_to_end(&mut self.block)?;
+                    if self.block.len() - start < len as usize {
+                        return Err(self.truncated());
+                    }
+                    self.records.push(start..self.block.len());
+                }
+            }
+        }
+    }
+
+    /// Read a little-endian `u32`, or `None` if `r` has already ended.
+    fn read_u32(&mut self) -> Result<Option<u32>, ContainerError> {
+        let mut bytes = [0u8; 4];
+        let mut filled = 0;
+        while filled < bytes.len() {
+            match self.r.read(&mut bytes[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(self.truncated()),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(Some(u32::from_le_bytes(bytes)))
+    }
+
+    fn truncated(&mut self) -> ContainerError {
+        self.ended = true;
+        self.records.clear();
+        ContainerError::Truncated {
+            valid_len: self.pos,
+        }
+    }
+}
+
+impl<T: Owned + Fingerprint, R: Read> Iterator for ContainerReader<T, R> {
+    type Item = Result<LazyBuf<T, Vec<u8>>, ContainerError>;
+
+    /// Like [`next_record`](Self::next_record), but copies each record into its own buffer.
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_record_bytes() {
+            Ok(Some(bytes)) => Some(LazyBuf::try_new(bytes.to_vec()).map_err(Into::into)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl<T: Owned + Fingerprint, R: Read + Seek> ContainerReader<T, R> {
+    /// Number of records in the container, according to its index.
+    pub fn record_count(&mut self) -> Result<u64, ContainerError> {
+        Ok(self.index()?.len() as u64)
+    }
+
+    /// Read record number `index` using the container's index, without reading the records
+    /// before it. The record is verified, but the checksum of its block isn't checked - read
+    /// through the container to check those.
+    ///
+    /// This doesn't affect which record [`next_record`](Self::next_record) reads next.
+    pub fn get(&mut self, index: u64) -> Result<LazyBuf<T, Vec<u8>>, ContainerError> {
+        let offsets = self.index()?;
+        let offset = *offsets
+            .get(index as usize)
+            .ok_or(ContainerError::IndexOutOfBounds {
+                index,
+                len: offsets.len() as u64,
+            })?;
+
+        let resume = self.r.stream_position()?;
+        self.r.seek(SeekFrom::Start(offset))?;
+        let bytes = self.read_record(offset);
+        self.r.seek(SeekFrom::Start(resume))?;
+        Ok(LazyBuf::try_new(bytes?)?)
+    }
+
+    fn read_record(&mut self, offset: u64) -> Result<Vec<u8>, ContainerError> {
+        let mut prefix = [0u8; RECORD_LEN_PREFIX_LEN];
+        self.r.read_exact(&mut prefix)?;
+        let len = u32::from_le_bytes(prefix);
+        if len >= FIRST_TAG {
+            return Err(ContainerError::InvalidRecord { offset });
+        }
+
+        let mut bytes = Vec::new();
+        (&mut self.r).take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() < len as usize {
+            return Err(ContainerError::InvalidRecord { offset });
+        }
+        Ok(bytes)
+    }
+
+    /// The file offsets of the records, read from the index the first time they're needed.
+    fn index(&mut self) -> Result<&[u64], ContainerError> {
+        if self.index.is_none() {
+            let resume = self.r.stream_position()?;
+            let index = self.read_index();
+            self.r.seek(SeekFrom::Start(resume))?;
+            self.index = Some(index?);
+        }
+        Ok(self.index.as_deref().unwrap())
+    }
+
+    fn read_index(&mut self) -> Result<Vec<u64>, ContainerError> {
+        let file_len = self.r.seek(SeekFrom::End(0))?;
+        if file_len < (CONTAINER_HEADER_LEN + INDEX_FOOTER_LEN) as u64 {
+            return Err(ContainerError::MissingIndex);
+        }
+        let mut footer = [0u8; INDEX_FOOTER_LEN];
+        self.r
+            .seek(SeekFrom::Start(file_len - INDEX_FOOTER_LEN as u64))?;
+        self.r.read_exact(&mut footer)?;
+        if footer[8..] != INDEX_FOOTER_MAGIC {
+            return Err(ContainerError::MissingIndex);
+        }
+
+        let index_offset = u64::from_le_bytes(footer[..8].try_into().unwrap());
+        let index_len = (file_len - INDEX_FOOTER_LEN as u64)
+            .checked_sub(index_offset)
+            .ok_or(ContainerError::MissingIndex)?;
+        let mut index = Vec::new();
+        self.r.seek(SeekFrom::Start(index_offset))?;
+        (&mut self.r).take(index_len).read_to_end(&mut index)?;
+
+        let checksum_mismatch = ContainerError::ChecksumMismatch {
+            offset: index_offset,
+        };
+        let Some((index, crc)) = index.split_last_chunk::<4>() else {
+            return Err(checksum_mismatch);
+        };
+        if crc32c(index) != u32::from_le_bytes(*crc) {
+            return Err(checksum_mismatch);
+        }
+        match index.split_first_chunk::<12>() {
+            Some((header, offsets))
+                if header[..4] == INDEX_TAG.to_le_bytes()
+                    && u64::from_le_bytes(header[4..].try_into().unwrap())
+                        == (offsets.len() / 8) as u64
+                    && offsets.len() % 8 == 0 =>
+            {
+                Ok(offsets
+                    .as_chunks::<8>()
+                    .0
+                    .iter()
+                    .map(|offset| u64::from_le_bytes(*offset))
+                    .collect())
+            }
+            _ => Err(checksum_mismatch),
+        }
+    }
+}
+
+fn header_bytes(fingerprint: u64) -> [u8; CONTAINER_HEADER_LEN] {
+    let mut header = [0u8; CONTAINER_HEADER_LEN];
+    header[..8].copy_from_slice(&CONTAINER_MAGIC);
+    header[8..10].copy_from_slice(&CONTAINER_VERSION.to_le_bytes());
+    header[12..20].copy_from_slice(&fingerprint.to_le_bytes());
+    let crc = crc32c(&header[..20]);
+    header[20..].copy_from_slice(&crc.to_le_bytes());
+    header
+}
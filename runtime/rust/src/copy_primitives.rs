@@ -1,7 +1,87 @@
+use core::mem::MaybeUninit;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::{vec, vec::Vec};
+
 use crate::{
-    BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeResult, Encode, EncodeCursor, Lazy, Owned,
+    BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeErrorKind, DecodeResult, Encode,
+    EncodeCursor, Lazy, Owned, Verified,
 };
 
+/// The numeric types. Every bit pattern is a valid value and they're encoded in little-endian
+/// byte order with no padding, so on little-endian platforms a list of them is laid out in the
+/// buffer exactly like a slice in memory and can be copied in bulk.
+pub trait Primitive: Owned + Copy + sealed::Sealed {}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Copy the little-endian `bytes` of `dst.len()` primitives to `dst`.
+pub(crate) fn copy_from_le_bytes<T: Primitive>(bytes: &[u8], dst: &mut [MaybeUninit<T>]) {
+    assert_eq!(bytes.len(), size_of_val(dst));
+
+    #[cfg(target_endian = "little")]
+    // SAFETY: `dst` is `bytes.len()` bytes long, and every bit pattern is a valid primitive.
+    unsafe {
+        core::ptr::copy_nonoverlapping(bytes.as_ptr(), dst.as_mut_ptr().cast::<u8>(), bytes.len());
+    }
+
+    #[cfg(not(target_endian = "little"))]
+    for (item, bytes) in dst.iter_mut().zip(bytes.chunks_exact(T::BASE_LEN)) {
+        item.write(T::decode(&DecodeCursor::new(bytes)).expect("primitives are always valid"));
+    }
+}
+
+#[inline]
+fn encode_primitives<T: Primitive>(items: &[T], cursor: &mut EncodeCursor) {
+    #[cfg(target_endian = "little")]
+    {
+        // SAFETY: primitives have no padding, so all of their bytes are initialized.
+        let bytes =
+            unsafe { core::slice::from_raw_parts(items.as_ptr().cast::<u8>(), size_of_val(items)) };
+        cursor.scratch_bytes(bytes);
+    }
+
+    #[cfg(not(target_endian = "little"))]
+    cursor.inner_in_scratch(size_of_val(items), |cursor| {
+        for item in items {
+            item.encode(cursor);
+        }
+    });
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[inline]
+fn decode_primitives<T: Primitive>(cursor: &DecodeCursor, len: usize) -> DecodeResult<Vec<T>> {
+    let bytes = cursor.base(len.saturating_mul(T::BASE_LEN))?;
+    let mut vec = Vec::with_capacity(len);
+    copy_from_le_bytes(bytes, &mut vec.spare_capacity_mut()[..len]);
+    // SAFETY: the first `len` items were just initialized.
+    unsafe { vec.set_len(len) };
+    Ok(vec)
+}
+
+macro_rules! bulk_primitive_impl {
+    ($t:ty) => {
+        impl sealed::Sealed for $t {}
+        impl Primitive for $t {}
+    };
+}
+
+bulk_primitive_impl!(u8);
+bulk_primitive_impl!(u16);
+bulk_primitive_impl!(u32);
+bulk_primitive_impl!(u64);
+bulk_primitive_impl!(u128);
+bulk_primitive_impl!(i8);
+bulk_primitive_impl!(i16);
+bulk_primitive_impl!(i32);
+bulk_primitive_impl!(i64);
+bulk_primitive_impl!(i128);
+bulk_primitive_impl!(f32);
+bulk_primitive_impl!(f64);
+
 macro_rules! copy_primitive_owned_impl {
     ($t:ty) => {
         impl Owned for $t {
@@ -11,13 +91,24 @@ macro_rules! copy_primitive_owned_impl {
             fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
                 Ok(lazy)
             }
+
+            #[inline]
+            fn shorten_lazy<'b, 'a: 'b>(lazy: Self::Lazy<'a>) -> Self::Lazy<'b> {
+                lazy
+            }
         }
 
         impl Lazy<'_> for $t {
             type Owned = $t;
+            type View = $t;
+
+            #[inline]
+            fn view(verified: Verified<Self>) -> Self::View {
+                verified.into_inner()
+            }
         }
 
-        impl Compatible<$t> for $t { }
+        impl Compatible<$t> for $t {}
     };
 }
 
@@ -27,15 +118,19 @@ copy_primitive_owned_impl!(u8);
 copy_primitive_owned_impl!(u16);
 copy_primitive_owned_impl!(u32);
 copy_primitive_owned_impl!(u64);
+copy_primitive_owned_impl!(u128);
 copy_primitive_owned_impl!(i8);
 copy_primitive_owned_impl!(i16);
 copy_primitive_owned_impl!(i32);
 copy_primitive_owned_impl!(i64);
+copy_primitive_owned_impl!(i128);
 copy_primitive_owned_impl!(f32);
 copy_primitive_owned_impl!(f64);
 
 impl BaseLen for () {
     const BASE_LEN: usize = 0;
+    const WIDE_BASE_LEN: usize = Self::BASE_LEN;
+    const HAS_SCRATCH: bool = false;
 }
 
 impl Encode for () {
@@ -44,8 +139,19 @@ impl Encode for () {
         0
     }
 
+    #[inline]
+    fn wide_scratch_len(&self) -> usize {
+        0
+    }
+
     #[inline]
     fn encode(&self, _: &mut EncodeCursor) {}
+
+    /// There's nothing to write for the items, however many there are.
+    #[inline]
+    fn encode_list(_: &[Self], cursor: &mut EncodeCursor) {
+        cursor.inner_in_scratch(0, |_| {});
+    }
 }
 
 impl<'a> Decode<'a> for () {
@@ -53,10 +159,18 @@ impl<'a> Decode<'a> for () {
     fn decode(_: &DecodeCursor<'a>) -> DecodeResult<Self> {
         Ok(())
     }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline]
+    fn decode_vec(_: &DecodeCursor<'a>, len: usize) -> DecodeResult<Vec<Self>> {
+        Ok(vec![(); len])
+    }
 }
 
 impl BaseLen for bool {
     const BASE_LEN: usize = 1;
+    const WIDE_BASE_LEN: usize = Self::BASE_LEN;
+    const HAS_SCRATCH: bool = false;
 }
 
 impl Encode for bool {
@@ -65,6 +179,11 @@ impl Encode for bool {
         0
     }
 
+    #[inline]
+    fn wide_scratch_len(&self) -> usize {
+        0
+    }
+
     #[inline]
     fn encode(&self, cursor: &mut EncodeCursor) {
         cursor.base(1)[0] = if *self { 1 } else { 0 };
@@ -74,17 +193,22 @@ impl Encode for bool {
 impl<'a> Decode<'a> for bool {
     #[inline]
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        let b = cursor.base(1)[0];
-        if b <= 1 {
-            Ok(b == 1)
-        } else {
-            Err(DecodeError)
+        let offset = cursor.offset();
+        match cursor.base(1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(DecodeError::new(
+                DecodeErrorKind::InvalidBool { value },
+                offset,
+            )),
         }
     }
 }
 
 impl BaseLen for u8 {
     const BASE_LEN: usize = 1;
+    const WIDE_BASE_LEN: usize = Self::BASE_LEN;
+    const HAS_SCRATCH: bool = false;
 }
 
 impl Encode for u8 {
@@ -93,21 +217,39 @@ impl Encode for u8 {
         0
     }
 
+    #[inline]
+    fn wide_scratch_len(&self) -> usize {
+        0
+    }
+
     #[inline]
     fn encode(&self, cursor: &mut EncodeCursor) {
         cursor.base(1)[0] = *self;
     }
+
+    #[inline]
+    fn encode_list(items: &[Self], cursor: &mut EncodeCursor) {
+        encode_primitives(items, cursor);
+    }
 }
 
 impl<'a> Decode<'a> for u8 {
     #[inline]
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        Ok(cursor.base(1)[0])
+        Ok(cursor.base(1)?[0])
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline]
+    fn decode_vec(cursor: &DecodeCursor<'a>, len: usize) -> DecodeResult<Vec<Self>> {
+        decode_primitives(cursor, len)
     }
 }
 
 impl BaseLen for i8 {
     const BASE_LEN: usize = 1;
+    const WIDE_BASE_LEN: usize = Self::BASE_LEN;
+    const HAS_SCRATCH: bool = false;
 }
 
 impl Encode for i8 {
@@ -116,16 +258,32 @@ impl Encode for i8 {
         0
     }
 
+    #[inline]
+    fn wide_scratch_len(&self) -> usize {
+        0
+    }
+
     #[inline]
     fn encode(&self, cursor: &mut EncodeCursor) {
         cursor.base(1)[0] = *self as u8;
     }
+
+    #[inline]
+    fn encode_list(items: &[Self], cursor: &mut EncodeCursor) {
+        encode_primitives(items, cursor);
+    }
 }
 
 impl<'a> Decode<'a> for i8 {
     #[inline]
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        Ok(cursor.base(1)[0] as i8)
+        Ok(cursor.base(1)?[0] as i8)
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline]
+    fn decode_vec(cursor: &DecodeCursor<'a>, len: usize) -> DecodeResult<Vec<Self>> {
+        decode_primitives(cursor, len)
     }
 }
 
@@ -133,6 +291,8 @@ macro_rules! integer_primitive_encoding_impl {
     ($t:ty) => {
         impl BaseLen for $t {
             const BASE_LEN: usize = core::mem::size_of::<$t>();
+            const WIDE_BASE_LEN: usize = Self::BASE_LEN;
+            const HAS_SCRATCH: bool = false;
         }
 
         impl Encode for $t {
@@ -141,23 +301,34 @@ macro_rules! integer_primitive_encoding_impl {
                 0
             }
 
+            #[inline]
+            fn wide_scratch_len(&self) -> usize {
+                0
+            }
+
             #[inline]
             fn encode(&self, cursor: &mut EncodeCursor) {
                 cursor
                     .base(<$t>::BASE_LEN)
                     .copy_from_slice(&self.to_le_bytes());
             }
+
+            #[inline]
+            fn encode_list(items: &[Self], cursor: &mut EncodeCursor) {
+                encode_primitives(items, cursor);
+            }
         }
 
         impl<'a> Decode<'a> for $t {
             #[inline]
             fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-                Ok(<$t>::from_le_bytes(
-                    cursor
-                        .base(<$t>::BASE_LEN)
-                        .try_into()
-                        .map_err(|_| DecodeError)?,
-                ))
+                Ok(<$t>::from_le_bytes(cursor.base_array()?))
+            }
+
+            #[cfg(any(feature = "std", feature = "alloc"))]
+            #[inline]
+            fn decode_vec(cursor: &DecodeCursor<'a>, len: usize) -> DecodeResult<Vec<Self>> {
+                decode_primitives(cursor, len)
             }
         }
     };
@@ -1,19 +1,52 @@
 use core::cell::Cell;
 
+use crate::{DecodeError, DecodeErrorKind, DecodeLimits, DecodeResult, WireFormat};
+
 pub struct DecodeCursor<'a> {
     buffer: &'a [u8],
     offset: Cell<usize>,
+    depth: usize,
+    limits: DecodeLimits,
+    format: WireFormat,
+    // Bytes allocated for owned values so far. Copied back from inner cursors when they're done.
+    owned_bytes: Cell<usize>,
+    // The `Bytes` that `buffer` borrows from, if any, so that owned `Bytes` values can be decoded
+    // as cheap sub-slices of it.
+    #[cfg(feature = "bytes")]
+    source: Option<&'a bytes::Bytes>,
 }
 
 impl<'a> DecodeCursor<'a> {
     #[inline]
     pub fn new(buffer: &'a [u8]) -> Self {
+        Self::at_offset(buffer, 0)
+    }
+
+    #[inline]
+    pub fn with_limits(buffer: &'a [u8], limits: DecodeLimits) -> Self {
         Self {
-            buffer,
-            offset: Cell::new(0),
+            limits,
+            ..Self::new(buffer)
         }
     }
 
+    /// A cursor over `bytes` that decodes `Bytes` values as sub-slices of it instead of copying
+    /// them. See [`decode_value_from_bytes`](crate::decode_value_from_bytes).
+    #[cfg(feature = "bytes")]
+    #[inline]
+    pub fn from_bytes(bytes: &'a bytes::Bytes) -> Self {
+        Self {
+            source: Some(bytes),
+            ..Self::new(bytes)
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    #[inline]
+    pub fn source_bytes(&self) -> Option<&'a bytes::Bytes> {
+        self.source
+    }
+
     #[inline]
     pub fn buffer(&self) -> &'a [u8] {
         self.buffer
@@ -24,48 +57,207 @@ impl<'a> DecodeCursor<'a> {
         self.offset.get()
     }
 
+    /// The number of bytes in the buffer past the cursor's current offset.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.buffer.len().saturating_sub(self.offset.get())
+    }
+
     #[inline]
     pub fn at_offset(buffer: &'a [u8], offset: usize) -> Self {
         Self {
             buffer,
             offset: Cell::new(offset),
+            depth: 0,
+            limits: DecodeLimits::DEFAULT,
+            format: WireFormat::Absolute,
+            owned_bytes: Cell::new(0),
+            #[cfg(feature = "bytes")]
+            source: None,
+        }
+    }
+
+    /// Decode a buffer encoded with the given [`WireFormat`] rather than
+    /// [`WireFormat::Absolute`].
+    #[inline]
+    pub fn with_format(self, format: WireFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    #[inline]
+    pub fn format(&self) -> WireFormat {
+        self.format
+    }
+
+    #[inline]
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    /// The number of scratch indirections followed to get to the cursor, which is checked against
+    /// [`DecodeLimits::max_depth`].
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Decode with `limits`, as if `depth` scratch indirections had already been followed. Lazily
+    /// decoded values keep their cursor's limits and depth, and use this to decode their contents
+    /// under the same limits later on.
+    #[inline]
+    pub fn with_limits_at_depth(self, limits: DecodeLimits, depth: usize) -> Self {
+        Self {
+            limits,
+            depth,
+            ..self
         }
     }
 
+    /// Check a list's length against [`DecodeLimits::max_list_len`].
     #[inline]
-    pub fn base(&self, size: usize) -> &'a [u8] {
+    pub fn check_list_len(&self, len: usize) -> DecodeResult<()> {
+        if len > self.limits.max_list_len {
+            return Err(self.limit_error(DecodeErrorKind::ListLenLimitExceeded { len }));
+        }
+        Ok(())
+    }
+
+    /// Check a string's length against [`DecodeLimits::max_string_len`].
+    #[inline]
+    pub fn check_string_len(&self, len: usize) -> DecodeResult<()> {
+        if len > self.limits.max_string_len {
+            return Err(self.limit_error(DecodeErrorKind::StringLenLimitExceeded { len }));
+        }
+        Ok(())
+    }
+
+    /// Account for `bytes` bytes about to be allocated for an owned value, checking the total
+    /// against [`DecodeLimits::max_owned_bytes`].
+    #[inline]
+    pub fn reserve_owned(&self, bytes: usize) -> DecodeResult<()> {
+        match self.owned_bytes.get().checked_add(bytes) {
+            Some(total) if total <= self.limits.max_owned_bytes => {
+                self.owned_bytes.set(total);
+                Ok(())
+            }
+            _ => Err(self.limit_error(DecodeErrorKind::OwnedBytesLimitExceeded)),
+        }
+    }
+
+    #[cold]
+    fn limit_error(&self, kind: DecodeErrorKind) -> DecodeError {
+        DecodeError::new(kind, self.offset.get())
+    }
+
+    #[inline]
+    pub fn base(&self, size: usize) -> DecodeResult<&'a [u8]> {
+        let offset = self.offset.get();
+        let bytes = slice_at(self.buffer, offset, size)?;
+        self.offset.set(offset + size);
+        Ok(bytes)
+    }
+
+    /// Like [`Self::base`], but for a fixed number of bytes.
+    #[inline]
+    pub(crate) fn base_array<const N: usize>(&self) -> DecodeResult<[u8; N]> {
         let offset = self.offset.get();
-        self.offset.set(self.offset.get() + size);
-        &self.buffer[offset..offset + size]
+        let bytes = self.base(N)?;
+        bytes
+            .try_into()
+            .map_err(|_| DecodeError::new(DecodeErrorKind::UnexpectedEnd, offset))
+    }
+
+    /// Read a list or string length from the base area. Lengths that don't fit in a `usize` come
+    /// out as `usize::MAX`, for the length limits to reject.
+    #[inline]
+    pub fn read_len(&self) -> DecodeResult<usize> {
+        match self.format {
+            WireFormat::Absolute | WireFormat::Relative => {
+                Ok(u32::from_le_bytes(self.base_array()?) as usize)
+            }
+            WireFormat::Wide => {
+                let len = u64::from_le_bytes(self.base_array()?);
+                Ok(usize::try_from(len).unwrap_or(usize::MAX))
+            }
+        }
     }
 
     #[inline]
-    pub fn scratch(&self, size: usize) -> &'a [u8] {
+    pub fn scratch(&self, size: usize) -> DecodeResult<&'a [u8]> {
         // Read the offset of this scratch buffer from the base buffer.
-        let offset = u32::from_le_bytes(self.base(4).try_into().unwrap()) as usize;
+        let offset = self.read_scratch_offset()?;
 
-        &self.buffer[offset..offset + size]
+        slice_at(self.buffer, offset, size)
     }
 
     #[inline]
-    pub fn inner_in_scratch<R>(&self, f: impl FnOnce(&Self) -> R) -> R {
+    pub fn inner_in_scratch<R>(&self, f: impl FnOnce(&Self) -> DecodeResult<R>) -> DecodeResult<R> {
         // Read the offset of this scratch buffer from the base buffer.
-        let offset = u32::from_le_bytes(self.base(4).try_into().unwrap()) as usize;
+        let pointer_offset = self.offset.get();
+        let offset = self.read_scratch_offset()?;
+
+        if self.depth >= self.limits.max_depth {
+            return Err(DecodeError::new(
+                DecodeErrorKind::DepthLimitExceeded,
+                pointer_offset,
+            ));
+        }
 
-        let inner_cursor = Self::at_offset(self.buffer, offset);
-        f(&inner_cursor)
+        let inner_cursor = Self {
+            buffer: self.buffer,
+            offset: Cell::new(offset),
+            depth: self.depth + 1,
+            limits: self.limits,
+            format: self.format,
+            owned_bytes: Cell::new(self.owned_bytes.get()),
+            #[cfg(feature = "bytes")]
+            source: self.source,
+        };
+        let result = f(&inner_cursor);
+        self.owned_bytes.set(inner_cursor.owned_bytes.get());
+        result
     }
 
     #[inline]
-    pub fn advance(&self, offset: usize) {
-        self.offset.set(self.offset.get() + offset);
+    pub fn advance(&self, size: usize) -> DecodeResult<()> {
+        self.base(size).map(|_| ())
     }
 
     #[inline]
-    pub fn follow_scratch(&self) {
-        let offset = self.offset.get();
-        let scratch_offset =
-            u32::from_le_bytes(self.buffer[offset..offset + 4].try_into().unwrap()) as usize;
-        self.offset.set(scratch_offset as usize);
+    pub fn follow_scratch(&self) -> DecodeResult<()> {
+        let scratch_offset = self.read_scratch_offset()?;
+        self.offset.set(scratch_offset);
+        Ok(())
+    }
+
+    #[inline]
+    fn read_scratch_offset(&self) -> DecodeResult<usize> {
+        let pointer_offset = self.offset.get();
+        let offset = match self.format {
+            WireFormat::Absolute => u32::from_le_bytes(self.base_array()?) as usize,
+            WireFormat::Relative => {
+                let offset = u32::from_le_bytes(self.base_array()?);
+                (pointer_offset as u32).wrapping_add(offset) as usize
+            }
+            WireFormat::Wide => {
+                let offset = u64::from_le_bytes(self.base_array()?);
+                usize::try_from(offset).unwrap_or(usize::MAX)
+            }
+        };
+        if offset > self.buffer.len() {
+            return Err(DecodeError::new(
+                DecodeErrorKind::OffsetOutOfBounds { target: offset },
+                pointer_offset,
+            ));
+        }
+        Ok(offset)
     }
 }
+
+#[inline]
+fn slice_at(buffer: &[u8], offset: usize, size: usize) -> DecodeResult<&[u8]> {
+    offset
+        .checked_add(size)
+        .and_then(|end| buffer.get(offset..end))
+        .ok_or_else(|| DecodeError::new(DecodeErrorKind::UnexpectedEnd, offset))
+}
@@ -0,0 +1,250 @@
+use core::fmt;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::boxed::Box;
+
+use crate::WireFormat;
+
+/// Maximum number of path segments recorded in a [`DecodeError`]. Segments beyond this (the ones
+/// furthest from the value that failed to decode) are dropped.
+///
+/// Without `alloc` the path is stored inline in the error, so fewer segments are kept to stop every
+/// `DecodeResult` from growing by 16 bytes per segment.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub const MAX_ERROR_PATH_LEN: usize = 8;
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+pub const MAX_ERROR_PATH_LEN: usize = 4;
+
+/// Error returned when a buffer doesn't contain a valid mproto value.
+///
+/// Besides the [kind](DecodeErrorKind) of error, it records the byte offset at which the error was
+/// detected and the path of fields, variants and list indices leading from the root value to the
+/// value that failed to decode, e.g.
+/// `MyResponse.response.Ok.value[3]: invalid enum tag 7 at offset 212`.
+///
+/// With `alloc` the error is boxed, so that `DecodeResult` stays small. Without it, it's stored
+/// inline so that building it never allocates.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DecodeError(
+    #[cfg(any(feature = "std", feature = "alloc"))] Box<Details>,
+    #[cfg(not(any(feature = "std", feature = "alloc")))] Details,
+);
+
+#[derive(Clone, PartialEq, Eq)]
+struct Details {
+    kind: DecodeErrorKind,
+    offset: usize,
+    type_name: Option<&'static str>,
+    // Innermost segment first.
+    path: [PathSegment; MAX_ERROR_PATH_LEN],
+    path_len: u8,
+    path_truncated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeErrorKind {
+    /// The buffer ended in the middle of a value.
+    UnexpectedEnd,
+    /// A string's bytes were not valid UTF-8.
+    InvalidUtf8,
+    /// A bool was neither 0 nor 1.
+    InvalidBool { value: u8 },
+    /// An enum (or `Result`) tag didn't match any variant.
+    InvalidEnumTag { tag: u8, type_name: &'static str },
+    /// An `Option` tag was neither 0 nor 1.
+    InvalidOptionTag { tag: u8 },
+    /// A scratch offset pointed past the end of the buffer.
+    OffsetOutOfBounds { target: usize },
+    /// A list was indexed past its end.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// Values were nested more deeply than [`DecodeLimits::max_depth`](crate::DecodeLimits).
+    DepthLimitExceeded,
+    /// A list was longer than [`DecodeLimits::max_list_len`](crate::DecodeLimits).
+    ListLenLimitExceeded { len: usize },
+    /// A string was longer than [`DecodeLimits::max_string_len`](crate::DecodeLimits).
+    StringLenLimitExceeded { len: usize },
+    /// Decoding would allocate more than
+    /// [`DecodeLimits::max_owned_bytes`](crate::DecodeLimits) in total.
+    OwnedBytesLimitExceeded,
+    /// A compressed message was malformed, see [`compression`](crate::compression).
+    InvalidCompressedData,
+    /// A compressed message would decompress to more than the maximum length passed to
+    /// [`Compression::decompress`](crate::compression::Compression::decompress).
+    DecompressedLenLimitExceeded { len: usize },
+    /// The value was decoded in a wire format that doesn't support the operation, e.g.
+    /// [`BoxLazy::spliced`](crate::BoxLazy::spliced) outside of [`WireFormat::Relative`].
+    UnsupportedFormat { format: WireFormat },
+}
+
+/// One step of the path from the root value to the value that failed to decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSegment {
+    /// A struct field or enum variant.
+    Name(&'static str),
+    /// A list item.
+    Index(usize),
+}
+
+impl DecodeError {
+    #[inline]
+    pub fn new(kind: DecodeErrorKind, offset: usize) -> Self {
+        #[allow(clippy::useless_conversion)]
+        Self(
+            Details {
+                kind,
+                offset,
+                type_name: None,
+                path: [PathSegment::Index(0); MAX_ERROR_PATH_LEN],
+                path_len: 0,
+                path_truncated: false,
+            }
+            .into(),
+        )
+    }
+
+    #[inline]
+    pub fn kind(&self) -> DecodeErrorKind {
+        self.0.kind
+    }
+
+    /// Byte offset in the buffer at which the error was detected.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.0.offset
+    }
+
+    /// Name of the outermost struct or enum on the error's path, if any.
+    #[inline]
+    pub fn type_name(&self) -> Option<&'static str> {
+        self.0.type_name
+    }
+
+    /// The path from the root value to the value that failed to decode, outermost segment first.
+    pub fn path(&self) -> impl Iterator<Item = PathSegment> + '_ {
+        self.0.path[..self.0.path_len as usize]
+            .iter()
+            .rev()
+            .copied()
+    }
+
+    /// Whether segments were dropped from the outer end of the path because it got too deep.
+    #[inline]
+    pub fn is_path_truncated(&self) -> bool {
+        self.0.path_truncated
+    }
+
+    /// Record that the error happened while decoding field `field` of struct `type_name`.
+    #[inline]
+    pub fn in_field(mut self, type_name: &'static str, field: &'static str) -> Self {
+        self.0.type_name = Some(type_name);
+        self.push_segment(PathSegment::Name(field))
+    }
+
+    /// Record that the error happened while decoding variant `variant` of enum `type_name`.
+    #[inline]
+    pub fn in_variant(mut self, type_name: &'static str, variant: &'static str) -> Self {
+        self.0.type_name = Some(type_name);
+        self.push_segment(PathSegment::Name(variant))
+    }
+
+    /// Record that the error happened while decoding item `index` of a list.
+    #[inline]
+    pub fn at_index(self, index: usize) -> Self {
+        self.push_segment(PathSegment::Index(index))
+    }
+
+    /// Prepend a segment to the error's path. Decoders call this as the error propagates outwards,
+    /// so segments are pushed innermost first.
+    #[inline]
+    pub fn push_segment(mut self, segment: PathSegment) -> Self {
+        let details = &mut self.0;
+        let len = details.path_len as usize;
+        if len < MAX_ERROR_PATH_LEN {
+            details.path[len] = segment;
+            details.path_len += 1;
+        } else {
+            details.path_truncated = true;
+        }
+        self
+    }
+}
+
+impl fmt::Debug for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecodeError")
+            .field("kind", &self.0.kind)
+            .field("offset", &self.0.offset)
+            .field("type_name", &self.0.type_name)
+            .field("path", &PathDebug(self))
+            .finish()
+    }
+}
+
+struct PathDebug<'e>(&'e DecodeError);
+
+impl fmt::Debug for PathDebug<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.path()).finish()
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut has_path = false;
+        if let Some(type_name) = self.0.type_name {
+            write!(f, "{type_name}")?;
+            has_path = true;
+        }
+        if self.0.path_truncated {
+            write!(f, "{}\u{2026}", if has_path { "." } else { "" })?;
+            has_path = true;
+        }
+        for segment in self.path() {
+            match segment {
+                PathSegment::Name(name) if has_path => write!(f, ".{name}")?,
+                PathSegment::Name(name) => write!(f, "{name}")?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+            }
+            has_path = true;
+        }
+        if has_path {
+            write!(f, ": ")?;
+        }
+
+        write!(f, "{} at offset {}", self.0.kind, self.0.offset)
+    }
+}
+
+impl fmt::Display for DecodeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "unexpected end of buffer"),
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8 in string"),
+            Self::InvalidBool { value } => write!(f, "invalid bool {value}"),
+            Self::InvalidEnumTag { tag, .. } => write!(f, "invalid enum tag {tag}"),
+            Self::InvalidOptionTag { tag } => write!(f, "invalid option tag {tag}"),
+            Self::OffsetOutOfBounds { target } => {
+                write!(f, "scratch offset {target} out of bounds")
+            }
+            Self::IndexOutOfBounds { index, len } => {
+                write!(f, "index {index} out of bounds for list of length {len}")
+            }
+            Self::DepthLimitExceeded => write!(f, "nesting depth limit exceeded"),
+            Self::ListLenLimitExceeded { len } => write!(f, "list length {len} exceeds limit"),
+            Self::StringLenLimitExceeded { len } => {
+                write!(f, "string length {len} exceeds limit")
+            }
+            Self::OwnedBytesLimitExceeded => write!(f, "owned allocation limit exceeded"),
+            Self::InvalidCompressedData => write!(f, "invalid compressed data"),
+            Self::DecompressedLenLimitExceeded { len } => {
+                write!(f, "decompressed length {len} exceeds limit")
+            }
+            Self::UnsupportedFormat { format } => {
+                write!(f, "not supported in the {format:?} wire format")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
@@ -0,0 +1,33 @@
+/// Limits on the resources a single decode may use, to protect against hostile buffers. See
+/// [`decode_value_with_limits`](crate::decode_value_with_limits).
+///
+/// Lengths are already bounded by the size of the buffer, but a small buffer can still describe a
+/// huge owned value, e.g. many boxes or list items pointing at the same large string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Maximum number of scratch indirections (lists, strings and boxes) followed while decoding a
+    /// value. Bounds stack usage when decoding deeply nested or cyclic buffers.
+    pub max_depth: usize,
+    /// Maximum number of items in a single list.
+    pub max_list_len: usize,
+    /// Maximum length of a single string, in bytes.
+    pub max_string_len: usize,
+    /// Maximum number of bytes allocated for owned lists, strings and boxes in total.
+    pub max_owned_bytes: usize,
+}
+
+impl DecodeLimits {
+    /// The limits used by [`decode_value`](crate::decode_value) - only nesting depth is limited.
+    pub const DEFAULT: Self = Self {
+        max_depth: 128,
+        max_list_len: usize::MAX,
+        max_string_len: usize::MAX,
+        max_owned_bytes: usize::MAX,
+    };
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::collections::{BTreeMap, btree_map::Entry};
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, btree_map::Entry};
+
+use crate::checksum;
+
+/// What deduplication saved while encoding a value, see [`EncodeCursor::with_dedup`].
+///
+/// [`EncodeCursor::with_dedup`]: crate::EncodeCursor::with_dedup
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DedupReport {
+    /// Payloads written to scratch space and available for later copies to share.
+    pub unique_payloads: usize,
+    /// Payloads that pointed at an identical earlier one instead of being written again.
+    pub duplicate_payloads: usize,
+    /// Scratch bytes not written thanks to duplicates, i.e. how much smaller the encoding is than
+    /// [`encoded_len`](crate::encoded_len).
+    pub bytes_saved: usize,
+}
+
+/// The payloads written so far, by hash. Only the first payload with a given hash is kept, so a
+/// collision just means a missed duplicate.
+#[derive(Default)]
+pub(crate) struct DedupTable {
+    /// Offset and length of each payload.
+    payloads: BTreeMap<u64, (usize, usize)>,
+    report: DedupReport,
+}
+
+impl DedupTable {
+    /// Where an earlier payload that may be identical to `bytes` is, or `None` if there isn't
+    /// one, in which case `bytes` are about to be written at `offset`.
+    #[inline]
+    pub(crate) fn lookup(&mut self, bytes: &[u8], offset: usize) -> Option<usize> {
+        match self.payloads.entry(payload_hash(bytes)) {
+            Entry::Occupied(entry) => {
+                let (offset, len) = *entry.get();
+                (len == bytes.len()).then_some(offset)
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((offset, bytes.len()));
+                self.report.unique_payloads += 1;
+                None
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn record_duplicate(&mut self, len: usize) {
+        self.report.duplicate_payloads += 1;
+        self.report.bytes_saved += len;
+    }
+
+    #[inline]
+    pub(crate) fn report(&self) -> DedupReport {
+        self.report
+    }
+}
+
+/// 64-bit FNV-1a. Deterministic, so that the same value always deduplicates to the same bytes.
+#[inline]
+fn payload_hash(bytes: &[u8]) -> u64 {
+    checksum::fnv1a(checksum::FNV1A_OFFSET_BASIS, bytes)
+}
@@ -1,102 +1,399 @@
-use crate::Encode;
+#[cfg(any(feature = "std", feature = "alloc"))]
+use core::mem::MaybeUninit;
+use core::{marker::PhantomData, slice};
 
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use crate::{Encode, EncodeError, EncodeResult, WireFormat};
+#[cfg(any(feature = "std", feature = "alloc"))]
+use crate::{
+    ListBuilder, Owned,
+    dedup::{DedupReport, DedupTable},
+};
+
+/// Cursor for encoding a value into a buffer, see [`Encode`].
+///
+/// The base area of the value currently being encoded is `base_offset..base_end` from `base_ptr`,
+/// and scratch space is handed out sequentially from `scratch_offset` on. Offsets are relative to
+/// `ptr`, the start of the encoded value.
 pub struct EncodeCursor<'a> {
-    base_cursor: BufferEncodeCursor<'a>,
-    scratch_cursor: BufferEncodeCursor<'a>,
-    scratch_offset: u32,
+    ptr: *mut u8,
+    /// Usually `ptr`, except while encoding a value whose base area is outside the buffer - see
+    /// [`Self::encode_with_base`].
+    base_ptr: *mut u8,
+    /// How many bytes at `ptr` can be written to. Never less than `base_end` or `scratch_offset`.
+    /// For growable cursors this reaches into the `Vec`'s spare capacity, which is zeroed ahead
+    /// of time.
+    len: usize,
+    base_offset: usize,
+    base_end: usize,
+    scratch_offset: usize,
+    format: WireFormat,
+    /// The base area of the value being encoded in the narrow and wide formats, so that
+    /// [`Self::with_format`] can resize it.
+    root_base_lens: (usize, usize),
+    /// Slots in the detached base area passed to [`Self::encode_with_base`] that hold absolute
+    /// offsets still to be made relative, as where the base area ends up isn't known yet.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    base_fixups: Vec<u32>,
+    /// Payloads written by [`Self::scratch_bytes`], if deduplicating.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    dedup: Option<DedupTable>,
+    /// For growable cursors, the `Vec` that `ptr` points into and where in it the value starts.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    vec: Option<(&'a mut Vec<u8>, usize)>,
+    buffer_ty: PhantomData<&'a mut [u8]>,
 }
 
 impl<'a> EncodeCursor<'a> {
+    /// Encode a `T` into `buffer`, which must be at least [`encoded_len`](crate::encoded_len)
+    /// bytes long.
     #[inline]
     pub fn new<T: Encode + ?Sized>(buffer: &'a mut [u8]) -> Self {
-        let (base_buffer, scratch_buffer) = buffer.split_at_mut(T::BASE_LEN);
-
+        assert!(
+            buffer.len() >= T::BASE_LEN,
+            "buffer too small to encode mproto value"
+        );
+        let ptr = buffer.as_mut_ptr();
         Self {
-            base_cursor: BufferEncodeCursor::new(base_buffer),
-            scratch_cursor: BufferEncodeCursor::new(scratch_buffer),
-            scratch_offset: T::BASE_LEN as u32,
+            ptr,
+            base_ptr: ptr,
+            len: buffer.len(),
+            base_offset: 0,
+            base_end: T::BASE_LEN,
+            scratch_offset: T::BASE_LEN,
+            format: WireFormat::Absolute,
+            root_base_lens: (T::BASE_LEN, T::WIDE_BASE_LEN),
+            #[cfg(any(feature = "std", feature = "alloc"))]
+            base_fixups: Vec::new(),
+            #[cfg(any(feature = "std", feature = "alloc"))]
+            dedup: None,
+            #[cfg(any(feature = "std", feature = "alloc"))]
+            vec: None,
+            buffer_ty: PhantomData,
         }
     }
 
+    /// Checked counterpart of [`Self::new`] for encoding a value of `encoded_len` bytes, as
+    /// returned by [`encoded_len`](crate::encoded_len). Fails without touching `buffer` if the
+    /// value doesn't fit in it or can't be addressed by mproto's 32-bit offsets.
     #[inline]
-    pub fn encoded_len(&self) -> usize {
-        self.scratch_offset as usize
+    pub fn try_new<T: Encode + ?Sized>(
+        buffer: &'a mut [u8],
+        encoded_len: usize,
+    ) -> EncodeResult<Self> {
+        Self::try_new_with_format::<T>(buffer, encoded_len, WireFormat::Absolute)
     }
 
+    /// Like [`Self::try_new`], but in the given [`WireFormat`], with `encoded_len` as returned by
+    /// [`encoded_len_with_format`](crate::encoded_len_with_format).
     #[inline]
-    pub fn base(&mut self, size: usize) -> &'a mut [u8] {
-        self.base_cursor.take(size)
+    pub fn try_new_with_format<T: Encode + ?Sized>(
+        buffer: &'a mut [u8],
+        encoded_len: usize,
+        format: WireFormat,
+    ) -> EncodeResult<Self> {
+        let needed = encoded_len.max(format.base_len::<T>());
+        if needed > format.max_len() {
+            return Err(EncodeError::MessageTooLarge);
+        }
+        if needed > buffer.len() {
+            return Err(EncodeError::BufferTooSmall {
+                needed,
+                available: buffer.len(),
+            });
+        }
+
+        Ok(Self::new::<T>(&mut buffer[..needed]).with_format(format))
     }
 
+    /// Encode a `T` onto the end of `vec`, growing it as scratch space is needed instead of sizing
+    /// the buffer up front. This saves walking the value a second time to compute its
+    /// [`scratch_len`](Encode::scratch_len).
+    #[cfg(any(feature = "std", feature = "alloc"))]
     #[inline]
-    pub fn scratch(&mut self, size: usize) -> &'a mut [u8] {
-        // Write the offset of this scratch buffer into the base buffer.
-        self.base(4)
-            .copy_from_slice(&self.scratch_offset.to_le_bytes());
-        self.scratch_offset += size as u32;
+    pub fn growable<T: Encode + ?Sized>(vec: &'a mut Vec<u8>) -> Self {
+        let start = vec.len();
+        let ptr = vec.as_mut_ptr();
+        let mut cursor = Self {
+            ptr,
+            base_ptr: ptr,
+            len: 0,
+            base_offset: 0,
+            base_end: T::BASE_LEN,
+            scratch_offset: T::BASE_LEN,
+            format: WireFormat::Absolute,
+            root_base_lens: (T::BASE_LEN, T::WIDE_BASE_LEN),
+            base_fixups: Vec::new(),
+            dedup: None,
+            vec: Some((vec, start)),
+            buffer_ty: PhantomData,
+        };
+        cursor.grow(T::BASE_LEN);
+        cursor.set_vec_len(T::BASE_LEN);
+        cursor
+    }
 
-        self.scratch_cursor.take(size)
+    /// Encode with the given [`WireFormat`] rather than [`WireFormat::Absolute`]. Must be set
+    /// before anything is encoded.
+    ///
+    /// In [`WireFormat::Wide`] the value's base area may be bigger, so a fixed buffer must be at
+    /// least [`encoded_len_with_format`](crate::encoded_len_with_format) bytes long.
+    #[inline]
+    pub fn with_format(mut self, format: WireFormat) -> Self {
+        let (narrow, wide) = self.root_base_lens;
+        let base_len = format.select(narrow, wide);
+        if base_len > self.len {
+            self.grow(base_len);
+        }
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        self.set_vec_len(base_len);
+        self.base_end = base_len;
+        self.scratch_offset = base_len;
+        self.format = format;
+        self
     }
 
     #[inline]
-    pub fn inner_in_scratch(&mut self, base_size: usize, f: impl FnOnce(&mut Self)) {
-        // https://github.com/alecmocatta/replace_with/blob/1e86e4a3633c133cd3a8f797dc06ac92401bff1e/src/lib.rs#L478
-        fn replace_with<T, F: FnOnce(T) -> T>(dest: &mut T, f: F) {
-            unsafe {
-                let new = f(core::ptr::read(dest));
-                core::ptr::write(dest, new);
+    pub fn format(&self) -> WireFormat {
+        self.format
+    }
+
+    /// Have identical string and primitive list payloads share scratch space, by pointing later
+    /// copies at the first one. Decoders don't need to know, but the encoding is no longer
+    /// canonical, it's smaller than [`Encode::scratch_len`] says, and boxes in it can't be
+    /// [spliced](crate::BoxLazy::spliced). Must be set before anything is encoded.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline]
+    pub fn with_dedup(mut self) -> Self {
+        self.dedup = Some(DedupTable::default());
+        self
+    }
+
+    /// What deduplication has saved so far, or `None` if it isn't enabled.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline]
+    pub fn dedup_report(&self) -> Option<DedupReport> {
+        self.dedup.as_ref().map(DedupTable::report)
+    }
+
+    #[inline]
+    pub fn encoded_len(&self) -> usize {
+        self.scratch_offset
+    }
+
+    #[inline]
+    pub fn base(&mut self, size: usize) -> &mut [u8] {
+        let start = self.base_offset;
+        self.base_offset += size;
+        assert!(
+            self.base_offset <= self.base_end,
+            "mproto value overflowed its base area"
+        );
+
+        // SAFETY: the base area is within the buffer or the one given to `encode_with_base`, and
+        // the returned slice borrows `self` so it can't outlive it or overlap with any other slice
+        // handed out.
+        unsafe { slice::from_raw_parts_mut(self.base_ptr.add(start), size) }
+    }
+
+    /// Write a list or string length into the next slot in the base area. Panics if it's too long
+    /// for the format.
+    #[inline]
+    pub fn write_len(&mut self, len: usize) {
+        match self.format {
+            WireFormat::Absolute | WireFormat::Relative => {
+                let len = u32::try_from(len)
+                    .expect("mproto value too large: length overflows u32, use WireFormat::Wide");
+                self.base(4).copy_from_slice(&len.to_le_bytes());
             }
+            WireFormat::Wide => self.base(8).copy_from_slice(&(len as u64).to_le_bytes()),
         }
+    }
 
-        replace_with(self, move |s| Self::_inner_in_scratch(s, base_size, f));
+    #[inline]
+    pub fn scratch(&mut self, size: usize) -> &mut [u8] {
+        // Write the offset of this scratch buffer into the base buffer.
+        self.write_offset(self.scratch_offset);
+        self.alloc_scratch(size)
     }
 
+    /// Like [`Self::scratch`], but filled with `bytes`, which must not contain any offsets. If
+    /// deduplicating, an identical payload written earlier is pointed to instead.
     #[inline]
-    fn _inner_in_scratch(mut self, base_size: usize, f: impl FnOnce(&mut Self)) -> Self {
-        let inner_base_buffer = self.scratch(base_size);
+    pub fn scratch_bytes(&mut self, bytes: &[u8]) {
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        if self.dedup.is_some() {
+            self.scratch_bytes_dedup(bytes);
+            return;
+        }
 
-        let Self {
-            base_cursor,
-            scratch_cursor,
-            scratch_offset,
-        } = self;
+        self.scratch(bytes.len()).copy_from_slice(bytes);
+    }
 
-        let mut inner = Self {
-            base_cursor: BufferEncodeCursor::new(inner_base_buffer),
-            scratch_cursor,
-            scratch_offset,
-        };
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline(never)]
+    fn scratch_bytes_dedup(&mut self, bytes: &[u8]) {
+        if let Some(dedup) = &mut self.dedup
+            && !bytes.is_empty()
+            && let Some(target) = dedup.lookup(bytes, self.scratch_offset)
+        {
+            // SAFETY: `target` is the offset of an earlier payload of the same length, which was
+            // written to the buffer before `scratch_offset`.
+            let earlier = unsafe { slice::from_raw_parts(self.ptr.add(target), bytes.len()) };
+            if earlier == bytes {
+                dedup.record_duplicate(bytes.len());
+                self.write_offset(target);
+                return;
+            }
+        }
 
-        f(&mut inner);
+        self.scratch(bytes.len()).copy_from_slice(bytes);
+    }
 
-        Self {
-            base_cursor,
-            scratch_cursor: inner.scratch_cursor,
-            scratch_offset: inner.scratch_offset,
+    /// Write the offset of `target` into the next slot in the base area. `alloc_scratch` made
+    /// sure it fits.
+    #[inline]
+    fn write_offset(&mut self, target: usize) {
+        match self.format {
+            WireFormat::Absolute => self.base(4).copy_from_slice(&(target as u32).to_le_bytes()),
+            WireFormat::Relative => {
+                let offset = self.relative_offset(target as u32);
+                self.base(4).copy_from_slice(&offset.to_le_bytes());
+            }
+            WireFormat::Wide => self.base(8).copy_from_slice(&(target as u64).to_le_bytes()),
         }
     }
-}
 
-struct BufferEncodeCursor<'a> {
-    buffer: &'a mut [u8],
-}
+    /// The offset of `target` relative to the next slot in the base area.
+    #[inline]
+    fn relative_offset(&mut self, target: u32) -> u32 {
+        let slot = self.base_offset as u32;
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        if self.base_ptr != self.ptr {
+            self.base_fixups.push(slot);
+            return target;
+        }
+        target.wrapping_sub(slot)
+    }
 
-impl<'a> BufferEncodeCursor<'a> {
+    /// Hand out `size` bytes of scratch space without writing their offset anywhere.
     #[inline]
-    fn new(buffer: &'a mut [u8]) -> Self {
-        Self { buffer }
+    pub(crate) fn alloc_scratch(&mut self, size: usize) -> &mut [u8] {
+        let start = self.scratch_offset;
+        // Offsets are 32 bits on the wire unless the format is wide - fail loudly rather than
+        // silently wrapping.
+        self.scratch_offset = start
+            .checked_add(size)
+            .filter(|&end| end <= self.format.max_len())
+            .expect("mproto value too large: scratch offset overflows u32, use WireFormat::Wide");
+
+        let end = self.scratch_offset;
+        if end > self.len {
+            self.grow(end);
+        }
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        self.set_vec_len(end);
+
+        // SAFETY: `scratch_offset <= len` after growing, and the returned slice borrows `self`.
+        unsafe { slice::from_raw_parts_mut(self.ptr.add(start), size) }
     }
 
+    /// Encode a list of `T`s at the cursor by pushing its items one at a time, for when the number
+    /// of items isn't known up front. The list is finished when the builder is dropped.
+    #[cfg(any(feature = "std", feature = "alloc"))]
     #[inline]
-    fn take(&mut self, size: usize) -> &'a mut [u8] {
-        // Use a little unsafe to make the compiler forget that it got `buffer` from self. This is
-        // safe because we immediately replace `self.buffer` with the remaining buffer after
-        // `split_at_mut`.
-        let buffer: &'a mut [u8] =
-            unsafe { core::slice::from_raw_parts_mut(self.buffer.as_mut_ptr(), self.buffer.len()) };
-        let (taken_buffer, remaining) = buffer.split_at_mut(size);
-        self.buffer = remaining;
+    pub fn list_builder<T: Owned>(&mut self) -> ListBuilder<'_, 'a, T> {
+        ListBuilder::new(self)
+    }
 
-        taken_buffer
+    /// Encode a value whose base area is `base_size` bytes of new scratch space, writing its
+    /// offset into the current base area.
+    #[inline]
+    pub fn inner_in_scratch(&mut self, base_size: usize, f: impl FnOnce(&mut Self)) {
+        let inner_base_offset = self.scratch_offset;
+        self.scratch(base_size);
+        self.with_base(self.ptr, inner_base_offset, base_size, f);
+    }
+
+    /// Encode a value whose base area is `base` rather than part of the buffer, with its scratch
+    /// space handed out from the buffer as usual.
+    ///
+    /// In [`WireFormat::Relative`], offsets written to `base` are absolute, and where they are is
+    /// appended to `fixups` so they can be made relative once `base` is copied into the buffer.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline]
+    pub(crate) fn encode_with_base(
+        &mut self,
+        base: &mut [u8],
+        fixups: &mut Vec<u32>,
+        f: impl FnOnce(&mut Self),
+    ) {
+        let outer_fixups = core::mem::replace(&mut self.base_fixups, core::mem::take(fixups));
+        self.with_base(base.as_mut_ptr(), 0, base.len(), f);
+        *fixups = core::mem::replace(&mut self.base_fixups, outer_fixups);
+    }
+
+    #[inline]
+    fn with_base(
+        &mut self,
+        base_ptr: *mut u8,
+        base_offset: usize,
+        base_size: usize,
+        f: impl FnOnce(&mut Self),
+    ) {
+        // If the current base area is in the buffer, `grow` may move it while `f` runs.
+        let outer_in_buffer = self.base_ptr == self.ptr;
+        let outer_base_ptr = core::mem::replace(&mut self.base_ptr, base_ptr);
+        let outer_base_offset = core::mem::replace(&mut self.base_offset, base_offset);
+        let outer_base_end = core::mem::replace(&mut self.base_end, base_offset + base_size);
+
+        f(self);
+
+        self.base_ptr = if outer_in_buffer {
+            self.ptr
+        } else {
+            outer_base_ptr
+        };
+        self.base_offset = outer_base_offset;
+        self.base_end = outer_base_end;
+    }
+
+    /// Make at least `len` bytes writable, or panic if the buffer is fixed.
+    #[cold]
+    fn grow(&mut self, len: usize) {
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        if let Some((vec, start)) = &mut self.vec {
+            vec.reserve(*start + len - vec.len());
+            // Zero all the spare capacity rather than just what's needed, so that this isn't
+            // called for every bit of scratch space.
+            vec.spare_capacity_mut().fill(MaybeUninit::new(0));
+            let base_in_buffer = self.base_ptr == self.ptr;
+            // SAFETY: `start <= vec.len()`.
+            self.ptr = unsafe { vec.as_mut_ptr().add(*start) };
+            if base_in_buffer {
+                self.base_ptr = self.ptr;
+            }
+            self.len = vec.capacity() - *start;
+            return;
+        }
+
+        panic!(
+            "buffer too small to encode mproto value: needed at least {len} bytes, {} available",
+            self.len
+        );
+    }
+
+    /// Extend a growable cursor's `Vec` to the first `len` bytes of the value.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline]
+    fn set_vec_len(&mut self, len: usize) {
+        if let Some((vec, start)) = &mut self.vec {
+            debug_assert!(len <= self.len);
+            // SAFETY: the first `self.len` bytes from `start` on are within `vec`'s capacity, and
+            // were zeroed by `grow` if they weren't part of it already.
+            unsafe { vec.set_len(*start + len) };
+        }
     }
 }
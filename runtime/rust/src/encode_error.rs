@@ -0,0 +1,27 @@
+use core::fmt;
+
+/// Error returned by the fallible encoding functions, e.g. [`try_encode_value`](crate::try_encode_value).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The buffer can't hold the encoded value.
+    BufferTooSmall { needed: usize, available: usize },
+    /// The encoded value is larger than the 4 GiB addressable by mproto's 32-bit offsets, and
+    /// needs [`WireFormat::Wide`](crate::WireFormat::Wide).
+    MessageTooLarge,
+}
+
+pub type EncodeResult<T> = Result<T, EncodeError>;
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BufferTooSmall { needed, available } => write!(
+                f,
+                "buffer too small to encode mproto value: needed {needed} bytes, {available} available"
+            ),
+            Self::MessageTooLarge => write!(f, "mproto value too large to encode"),
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {}
@@ -0,0 +1,367 @@
+//! Checksummed envelopes, for messages stored or sent over links that can corrupt them.
+//!
+//! A flipped byte in an encoded message can decode as wrong data, or send an offset somewhere
+//! else entirely. An envelope carries a checksum of the encoded value, and [`open`] checks it and
+//! [verifies](crate::verify_value) the value before handing out a view of it.
+//!
+//! Typed envelopes also carry the [`Fingerprint`] of the value's type, and [`open_typed`] checks
+//! that it's the expected type instead of misinterpreting the bytes.
+//!
+//! Each envelope is laid out as:
+//!
+//! | Bytes | Contents |
+//! |-------|----------|
+//! | 1     | Format version, [`ENVELOPE_VERSION`] |
+//! | 1     | [`Checksum`] algorithm: 1 for CRC32C, 2 for xxHash64 |
+//! | 1     | Flags: bit 0 is set for typed envelopes, the rest must be zero |
+//! | 1     | Reserved, must be zero |
+//! | 4     | Length of the encoded value, as a little-endian `u32` |
+//! | 8     | Checksum of the rest of the envelope, as a little-endian `u64` - CRC32C is zero-extended |
+//! | 0 / 8 | Fingerprint of the value's type, as a little-endian `u64` - only in typed envelopes |
+//! | ..    | The encoded value |
+//!
+//! The checksums are table-driven and don't need an allocator, so envelopes work in `no_std`.
+
+use core::fmt;
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    Compatible, DecodeError, Encode, EncodeError, EncodeResult, Fingerprint, Owned, Verified,
+    checksum, encode_value, verify_value,
+};
+
+/// Length of the header at the start of each envelope, not counting the type fingerprint.
+pub const ENVELOPE_HEADER_LEN: usize = 16;
+
+/// Length of the type fingerprint following the header of typed envelopes.
+pub const ENVELOPE_FINGERPRINT_LEN: usize = 8;
+
+const FLAG_TYPED: u8 = 1;
+
+/// The envelope format version written by [`seal`]. [`open`] rejects any other version.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// A checksum algorithm for envelopes.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Checksum {
+    /// CRC32C (Castagnoli), as used by iSCSI, ext4 and many network protocols.
+    Crc32c,
+    /// xxHash64 with a seed of 0. Faster than CRC32C on large messages, and 64 bits wide.
+    XxHash64,
+}
+
+impl Checksum {
+    /// The checksum of `bytes`.
+    pub fn compute(self, bytes: &[u8]) -> u64 {
+        match self {
+            Self::Crc32c => checksum::crc32c(bytes) as u64,
+            Self::XxHash64 => checksum::xxhash64(bytes, 0),
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            Self::Crc32c => 1,
+            Self::XxHash64 => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(Self::Crc32c),
+            2 => Some(Self::XxHash64),
+            _ => None,
+        }
+    }
+}
+
+/// The header at the start of an envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvelopeHeader {
+    pub checksum: Checksum,
+    /// Fingerprint of the value's type, for typed envelopes.
+    pub fingerprint: Option<u64>,
+    /// Length of the encoded value.
+    pub len: u32,
+    /// Checksum of the rest of the envelope: the fingerprint, if any, and the encoded value.
+    pub digest: u64,
+}
+
+impl EnvelopeHeader {
+    /// Read the header at the start of `buf`, e.g. to find out how long the envelope is before
+    /// reading the rest of it.
+    pub fn read(buf: &[u8]) -> Result<Self, EnvelopeError> {
+        let header =
+            buf.first_chunk::<ENVELOPE_HEADER_LEN>()
+                .ok_or(EnvelopeError::LengthMismatch {
+                    expected: ENVELOPE_HEADER_LEN,
+                    found: buf.len(),
+                })?;
+        let [version, checksum, flags, reserved] = *header.first_chunk::<4>().unwrap();
+        if version != ENVELOPE_VERSION {
+            return Err(EnvelopeError::UnsupportedVersion { version });
+        }
+        let checksum =
+            Checksum::from_tag(checksum).ok_or(EnvelopeError::UnknownChecksum { tag: checksum })?;
+        if flags & !FLAG_TYPED != 0 || reserved != 0 {
+            return Err(EnvelopeError::InvalidHeader);
+        }
+        let fingerprint = if flags & FLAG_TYPED != 0 {
+            let fingerprint = buf[ENVELOPE_HEADER_LEN..]
+                .first_chunk::<ENVELOPE_FINGERPRINT_LEN>()
+                .ok_or(EnvelopeError::LengthMismatch {
+                    expected: ENVELOPE_HEADER_LEN + ENVELOPE_FINGERPRINT_LEN,
+                    found: buf.len(),
+                })?;
+            Some(u64::from_le_bytes(*fingerprint))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            checksum,
+            fingerprint,
+            len: u32::from_le_bytes(header[4..8].try_into().unwrap()),
+            digest: u64::from_le_bytes(header[8..].try_into().unwrap()),
+        })
+    }
+
+    /// Write the header to the start of `buf`, which must be at least [`header_len`] bytes long.
+    /// Returns the header length.
+    ///
+    /// [`header_len`]: Self::header_len
+    pub fn write(&self, buf: &mut [u8]) -> usize {
+        let header_len = self.header_len();
+        assert!(
+            buf.len() >= header_len,
+            "buffer too small for envelope header"
+        );
+
+        buf[0] = ENVELOPE_VERSION;
+        buf[1] = self.checksum.tag();
+        buf[2] = if self.fingerprint.is_some() {
+            FLAG_TYPED
+        } else {
+            0
+        };
+        buf[3] = 0;
+        buf[4..8].copy_from_slice(&self.len.to_le_bytes());
+        buf[8..ENVELOPE_HEADER_LEN].copy_from_slice(&self.digest.to_le_bytes());
+        if let Some(fingerprint) = self.fingerprint {
+            buf[ENVELOPE_HEADER_LEN..header_len].copy_from_slice(&fingerprint.to_le_bytes());
+        }
+        header_len
+    }
+
+    /// Length of the header, including the fingerprint of typed envelopes.
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        if self.fingerprint.is_some() {
+            ENVELOPE_HEADER_LEN + ENVELOPE_FINGERPRINT_LEN
+        } else {
+            ENVELOPE_HEADER_LEN
+        }
+    }
+
+    /// Length of the whole envelope, including the header.
+    #[inline]
+    pub fn envelope_len(&self) -> usize {
+        self.header_len() + self.len as usize
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvelopeError {
+    /// The buffer's length didn't match the envelope's - it's truncated, or has trailing bytes.
+    LengthMismatch { expected: usize, found: usize },
+    /// The envelope was written by an unknown version of the format.
+    UnsupportedVersion { version: u8 },
+    /// The envelope's checksum algorithm is unknown.
+    UnknownChecksum { tag: u8 },
+    /// The header's reserved bytes weren't zero.
+    InvalidHeader,
+    /// The rest of the envelope doesn't match the checksum in the header.
+    ChecksumMismatch { expected: u64, found: u64 },
+    /// The envelope holds a different type than expected, or isn't typed.
+    TypeMismatch { expected: u64, found: Option<u64> },
+    /// The checksum matched, but the value failed to verify.
+    Decode(DecodeError),
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch { expected, found } => write!(
+                f,
+                "envelope length mismatch: expected {expected} bytes, found {found}"
+            ),
+            Self::UnsupportedVersion { version } => {
+                write!(f, "unsupported envelope version {version}")
+            }
+            Self::UnknownChecksum { tag } => write!(f, "unknown envelope checksum {tag}"),
+            Self::InvalidHeader => write!(f, "invalid envelope header"),
+            Self::ChecksumMismatch { expected, found } => write!(
+                f,
+                "envelope checksum mismatch: expected {expected:#x}, found {found:#x}"
+            ),
+            Self::TypeMismatch {
+                expected,
+                found: Some(found),
+            } => write!(
+                f,
+                "envelope type mismatch: expected fingerprint {expected:#x}, found {found:#x}"
+            ),
+            Self::TypeMismatch {
+                expected,
+                found: None,
+            } => write!(
+                f,
+                "envelope type mismatch: expected fingerprint {expected:#x}, found untyped envelope"
+            ),
+            Self::Decode(e) => write!(f, "failed to decode envelope: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for EnvelopeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DecodeError> for EnvelopeError {
+    fn from(e: DecodeError) -> Self {
+        Self::Decode(e)
+    }
+}
+
+/// Length of the envelope [`seal`] writes for `v`. Typed envelopes are
+/// [`ENVELOPE_FINGERPRINT_LEN`] bytes longer.
+pub fn sealed_len<E: Encode>(v: &E) -> EncodeResult<usize> {
+    envelope_len(v, ENVELOPE_HEADER_LEN)
+}
+
+fn envelope_len<E: Encode>(v: &E, header_len: usize) -> EncodeResult<usize> {
+    E::BASE_LEN
+        .checked_add(v.scratch_len())
+        .filter(|&len| u32::try_from(len).is_ok())
+        .and_then(|len| len.checked_add(header_len))
+        .ok_or(EncodeError::MessageTooLarge)
+}
+
+/// Encode `v` into an envelope at the start of `buf`, with the given checksum. Returns the length
+/// of the envelope. Nothing is written to `buf` on error.
+pub fn seal<E: Encode>(v: E, checksum: Checksum, buf: impl AsMut<[u8]>) -> EncodeResult<usize> {
+    seal_with_fingerprint(v, checksum, None, buf)
+}
+
+/// Like [`seal`], but writes a typed envelope with the [`Fingerprint`] of `T`, which
+/// [`open_typed`] checks.
+pub fn seal_typed<T: Fingerprint>(
+    v: impl Compatible<T>,
+    checksum: Checksum,
+    buf: impl AsMut<[u8]>,
+) -> EncodeResult<usize> {
+    seal_with_fingerprint(v, checksum, Some(T::FINGERPRINT), buf)
+}
+
+fn seal_with_fingerprint<E: Encode>(
+    v: E,
+    checksum: Checksum,
+    fingerprint: Option<u64>,
+    mut buf: impl AsMut<[u8]>,
+) -> EncodeResult<usize> {
+    let buf = buf.as_mut();
+    let mut header = EnvelopeHeader {
+        checksum,
+        fingerprint,
+        len: 0,
+        digest: 0,
+    };
+    let envelope_len = envelope_len(&v, header.header_len())?;
+    if buf.len() < envelope_len {
+        return Err(EncodeError::BufferTooSmall {
+            needed: envelope_len,
+            available: buf.len(),
+        });
+    }
+
+    let buf = &mut buf[..envelope_len];
+    header.len = (envelope_len - header.header_len()) as u32;
+    let header_len = header.write(buf);
+    encode_value(v, &mut buf[header_len..]);
+    header.digest = checksum.compute(&buf[ENVELOPE_HEADER_LEN..]);
+    header.write(buf);
+    Ok(envelope_len)
+}
+
+/// Like [`seal`], but allocates a `Vec` to hold the envelope.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn seal_vec<E: Encode>(v: E, checksum: Checksum) -> EncodeResult<Vec<u8>> {
+    let mut buf = vec![0; sealed_len(&v)?];
+    seal(v, checksum, &mut buf)?;
+    Ok(buf)
+}
+
+/// Like [`seal_typed`], but allocates a `Vec` to hold the envelope.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn seal_typed_vec<T: Fingerprint>(
+    v: impl Compatible<T>,
+    checksum: Checksum,
+) -> EncodeResult<Vec<u8>> {
+    let mut buf = vec![0; envelope_len(&v, ENVELOPE_HEADER_LEN + ENVELOPE_FINGERPRINT_LEN)?];
+    seal_typed(v, checksum, &mut buf)?;
+    Ok(buf)
+}
+
+/// Check the checksum of the envelope in `buf`, which must hold exactly one envelope, and return
+/// the encoded value without decoding it. The envelope may be typed, but its type isn't checked.
+pub fn open_bytes(buf: &[u8]) -> Result<&[u8], EnvelopeError> {
+    open_checked(buf).map(|(_, value)| value)
+}
+
+fn open_checked(buf: &[u8]) -> Result<(EnvelopeHeader, &[u8]), EnvelopeError> {
+    let header = EnvelopeHeader::read(buf)?;
+    if buf.len() != header.envelope_len() {
+        return Err(EnvelopeError::LengthMismatch {
+            expected: header.envelope_len(),
+            found: buf.len(),
+        });
+    }
+
+    let digest = header.checksum.compute(&buf[ENVELOPE_HEADER_LEN..]);
+    if digest != header.digest {
+        return Err(EnvelopeError::ChecksumMismatch {
+            expected: header.digest,
+            found: digest,
+        });
+    }
+    Ok((header, &buf[header.header_len()..]))
+}
+
+/// Check the checksum of the envelope in `buf`, which must hold exactly one envelope, then verify
+/// the `T` in it and return a lazy view of it - see [`verify_value`]. The envelope may be typed,
+/// but its type isn't checked.
+pub fn open<'a, T: Owned>(buf: &'a [u8]) -> Result<Verified<T::Lazy<'a>>, EnvelopeError> {
+    Ok(verify_value::<T>(open_bytes(buf)?)?)
+}
+
+/// Like [`open`], but for a typed envelope, which must hold a `T` according to its fingerprint.
+pub fn open_typed<'a, T: Owned + Fingerprint>(
+    buf: &'a [u8],
+) -> Result<Verified<T::Lazy<'a>>, EnvelopeError> {
+    let (header, value) = open_checked(buf)?;
+    if header.fingerprint != Some(T::FINGERPRINT) {
+        return Err(EnvelopeError::TypeMismatch {
+            expected: T::FINGERPRINT,
+            found: header.fingerprint,
+        });
+    }
+    Ok(verify_value::<T>(value)?)
+}
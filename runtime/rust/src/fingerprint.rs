@@ -0,0 +1,84 @@
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::checksum::{FNV1A_OFFSET_BASIS, fnv1a};
+
+/// A stable fingerprint of a type's structure. Typed envelopes embed it to catch a buffer being
+/// decoded as the wrong type - see [`envelope::seal_typed`](crate::envelope::seal_typed).
+///
+/// Codegen computes the fingerprints of generated types from their schema definitions: the names
+/// and types of their fields and the names and order of their variants, with the types they use
+/// expanded in place. Type names aren't part of it, so renaming a type keeps its fingerprint.
+/// Generic types mix in the fingerprints of their type arguments with [`fingerprint_with_args`].
+pub trait Fingerprint {
+    const FINGERPRINT: u64;
+}
+
+/// The fingerprint of a generic type, from the fingerprint of its definition and those of its
+/// type arguments.
+#[doc(hidden)]
+pub const fn fingerprint_with_args(fingerprint: u64, args: &[u64]) -> u64 {
+    let mut hash = fingerprint;
+    let mut i = 0;
+    while i < args.len() {
+        hash = fnv1a(hash, &args[i].to_le_bytes());
+        i += 1;
+    }
+    hash
+}
+
+macro_rules! impl_fingerprint {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(
+            impl Fingerprint for $ty {
+                const FINGERPRINT: u64 = fnv1a(FNV1A_OFFSET_BASIS, $name.as_bytes());
+            }
+        )*
+    };
+}
+
+impl_fingerprint! {
+    () => "void",
+    u8 => "u8",
+    u16 => "u16",
+    u32 => "u32",
+    u64 => "u64",
+    u128 => "u128",
+    i8 => "i8",
+    i16 => "i16",
+    i32 => "i32",
+    i64 => "i64",
+    i128 => "i128",
+    bool => "bool",
+    f32 => "f32",
+    f64 => "f64",
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl_fingerprint! {
+    String => "string",
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<T: Fingerprint> Fingerprint for Box<T> {
+    const FINGERPRINT: u64 =
+        fingerprint_with_args(fnv1a(FNV1A_OFFSET_BASIS, b"box"), &[T::FINGERPRINT]);
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<T: Fingerprint> Fingerprint for Vec<T> {
+    const FINGERPRINT: u64 =
+        fingerprint_with_args(fnv1a(FNV1A_OFFSET_BASIS, b"list"), &[T::FINGERPRINT]);
+}
+
+impl<T: Fingerprint> Fingerprint for Option<T> {
+    const FINGERPRINT: u64 =
+        fingerprint_with_args(fnv1a(FNV1A_OFFSET_BASIS, b"option"), &[T::FINGERPRINT]);
+}
+
+impl<T: Fingerprint, E: Fingerprint> Fingerprint for Result<T, E> {
+    const FINGERPRINT: u64 = fingerprint_with_args(
+        fnv1a(FNV1A_OFFSET_BASIS, b"result"),
+        &[T::FINGERPRINT, E::FINGERPRINT],
+    );
+}
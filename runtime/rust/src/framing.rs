@@ -0,0 +1,292 @@
+//! Framing for streams of mproto messages, e.g. over TCP or Unix sockets.
+//!
+//! Each frame is laid out as:
+//!
+//! | Bytes | Contents |
+//! |-------|----------|
+//! | 4     | Length of the rest of the frame, as a little-endian `u32` |
+//! | 0 / 4 | Type tag, as a little-endian `u32` - only if [`FrameConfig::type_tag`] is set |
+//! | ..    | The encoded value |
+//!
+//! Both ends of a stream must agree on the [`FrameConfig`]. Frames without a type tag are the same
+//! as the messages of [`write_message`](crate::write_message) and
+//! [`read_message`](crate::read_message).
+//!
+//! With the `tokio-codec` feature, [`FrameCodec`] implements `tokio_util`'s `Encoder` and `Decoder`
+//! for this format. With the `async` feature, [`AsyncMessageReader`](crate::AsyncMessageReader)
+//! and [`AsyncMessageWriter`](crate::AsyncMessageWriter) read and write it over `futures-io`
+//! streams.
+
+use core::fmt;
+use std::io;
+
+#[cfg(any(feature = "tokio-codec", feature = "async"))]
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{DecodeError, EncodeError};
+#[cfg(any(feature = "tokio-codec", feature = "async"))]
+use crate::{Encode, LazyBuf, Owned, encode_to_buf, encoded_len};
+
+/// Length of the length prefix at the start of each frame.
+pub const FRAME_LEN_PREFIX_LEN: usize = 4;
+
+/// Length of a frame's type tag, if it has one.
+pub const FRAME_TYPE_TAG_LEN: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameConfig {
+    /// Tag identifying the type of the framed values. If set, it's written to every frame and
+    /// checked on every frame read, to catch streams carrying a different type than expected.
+    pub type_tag: Option<u32>,
+    /// Maximum length of a frame, not counting the length prefix. Longer frames are rejected
+    /// before buffering them, both when reading and writing.
+    pub max_frame_len: usize,
+}
+
+impl FrameConfig {
+    pub const DEFAULT: Self = Self {
+        type_tag: None,
+        max_frame_len: 8 * 1024 * 1024,
+    };
+
+    /// Length of the type tag in each frame.
+    #[inline]
+    pub fn type_tag_len(&self) -> usize {
+        if self.type_tag.is_some() {
+            FRAME_TYPE_TAG_LEN
+        } else {
+            0
+        }
+    }
+}
+
+impl Default for FrameConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    /// A frame was longer than [`FrameConfig::max_frame_len`].
+    FrameTooLarge {
+        len: usize,
+        max_len: usize,
+    },
+    /// A frame was too short to hold its type tag.
+    FrameTooShort {
+        len: usize,
+    },
+    /// A frame's type tag didn't match [`FrameConfig::type_tag`].
+    TypeTagMismatch {
+        expected: u32,
+        found: u32,
+    },
+    /// A frame's value failed to decode.
+    Decode(DecodeError),
+    /// A value failed to encode.
+    Encode(EncodeError),
+    Io(io::Error),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FrameTooLarge { len, max_len } => {
+                write!(f, "frame of {len} bytes exceeds maximum of {max_len} bytes")
+            }
+            Self::FrameTooShort { len } => {
+                write!(f, "frame of {len} bytes is too short for its type tag")
+            }
+            Self::TypeTagMismatch { expected, found } => write!(
+                f,
+                "frame type tag mismatch: expected {expected:#x}, found {found:#x}"
+            ),
+            Self::Decode(e) => write!(f, "failed to decode frame: {e}"),
+            Self::Encode(e) => write!(f, "failed to encode frame: {e}"),
+            Self::Io(e) => write!(f, "frame I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(e) => Some(e),
+            Self::Encode(e) => Some(e),
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DecodeError> for FrameError {
+    fn from(e: DecodeError) -> Self {
+        Self::Decode(e)
+    }
+}
+
+impl From<EncodeError> for FrameError {
+    fn from(e: EncodeError) -> Self {
+        Self::Encode(e)
+    }
+}
+
+impl From<io::Error> for FrameError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<FrameError> for io::Error {
+    fn from(e: FrameError) -> Self {
+        match e {
+            FrameError::Io(e) => e,
+            FrameError::Encode(_) => io::Error::new(io::ErrorKind::InvalidInput, e),
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+/// Split the first frame off `src` if it's complete, reserving space for the rest of it
+/// otherwise.
+#[cfg(any(feature = "tokio-codec", feature = "async"))]
+pub(crate) fn decode_frame<T: Owned>(
+    config: &FrameConfig,
+    src: &mut BytesMut,
+) -> Result<Option<LazyBuf<T, Bytes>>, FrameError> {
+    let Some(prefix) = src.first_chunk::<FRAME_LEN_PREFIX_LEN>() else {
+        return Ok(None);
+    };
+    let len = u32::from_le_bytes(*prefix) as usize;
+    if len > config.max_frame_len {
+        return Err(FrameError::FrameTooLarge {
+            len,
+            max_len: config.max_frame_len,
+        });
+    }
+
+    let frame_len = FRAME_LEN_PREFIX_LEN + len;
+    if src.len() < frame_len {
+        src.reserve(frame_len - src.len());
+        return Ok(None);
+    }
+
+    src.advance(FRAME_LEN_PREFIX_LEN);
+    let mut frame = src.split_to(len);
+    if let Some(expected) = config.type_tag {
+        if frame.len() < config.type_tag_len() {
+            return Err(FrameError::FrameTooShort { len });
+        }
+        let found = frame.get_u32_le();
+        if found != expected {
+            return Err(FrameError::TypeTagMismatch { expected, found });
+        }
+    }
+
+    Ok(Some(LazyBuf::try_new(frame.freeze())?))
+}
+
+/// Append a frame holding `item` to `dst`.
+#[cfg(any(feature = "tokio-codec", feature = "async"))]
+pub(crate) fn encode_frame<E: Encode>(
+    config: &FrameConfig,
+    item: E,
+    dst: &mut BytesMut,
+) -> Result<(), FrameError> {
+    let len = config.type_tag_len() + encoded_len(&item);
+    if len > config.max_frame_len {
+        return Err(FrameError::FrameTooLarge {
+            len,
+            max_len: config.max_frame_len,
+        });
+    }
+    let prefix = u32::try_from(len).map_err(|_| EncodeError::MessageTooLarge)?;
+
+    dst.reserve(FRAME_LEN_PREFIX_LEN + len);
+    dst.put_u32_le(prefix);
+    if let Some(type_tag) = config.type_tag {
+        dst.put_u32_le(type_tag);
+    }
+    encode_to_buf(item, dst);
+
+    Ok(())
+}
+
+#[cfg(feature = "tokio-codec")]
+pub use codec::FrameCodec;
+
+#[cfg(feature = "tokio-codec")]
+mod codec {
+    use bytes::{Bytes, BytesMut};
+    use tokio_util::codec::{Decoder, Encoder};
+
+    use super::{FrameConfig, FrameError, decode_frame, encode_frame};
+    use crate::{Compatible, Encode, LazyBuf, Owned};
+
+    /// `tokio_util` codec for frames of `T`s, see the [module docs](super). Decodes frames into
+    /// `LazyBuf`s sharing the read buffer's memory.
+    pub struct FrameCodec<T> {
+        config: FrameConfig,
+        value_ty: core::marker::PhantomData<fn() -> T>,
+    }
+
+    impl<T> FrameCodec<T> {
+        #[inline]
+        pub fn new() -> Self {
+            Self::with_config(FrameConfig::DEFAULT)
+        }
+
+        #[inline]
+        pub fn with_config(config: FrameConfig) -> Self {
+            Self {
+                config,
+                value_ty: core::marker::PhantomData,
+            }
+        }
+
+        #[inline]
+        pub fn config(&self) -> &FrameConfig {
+            &self.config
+        }
+    }
+
+    impl<T> Default for FrameCodec<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<T> Clone for FrameCodec<T> {
+        fn clone(&self) -> Self {
+            Self::with_config(self.config)
+        }
+    }
+
+    impl<T> core::fmt::Debug for FrameCodec<T> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.debug_struct("FrameCodec")
+                .field("config", &self.config)
+                .finish()
+        }
+    }
+
+    impl<T: Owned> Decoder for FrameCodec<T> {
+        type Item = LazyBuf<T, Bytes>;
+        type Error = FrameError;
+
+        #[inline]
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+            decode_frame(&self.config, src)
+        }
+    }
+
+    impl<T: Owned, E: Encode + Compatible<T>> Encoder<E> for FrameCodec<T> {
+        type Error = FrameError;
+
+        #[inline]
+        fn encode(&mut self, item: E, dst: &mut BytesMut) -> Result<(), Self::Error> {
+            encode_frame(&self.config, item, dst)
+        }
+    }
+}
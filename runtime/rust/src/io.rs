@@ -0,0 +1,59 @@
+//! Reading and writing whole messages over `std::io`.
+//!
+//! Each message is written as its encoded length as a little-endian `u32`, followed by the encoded
+//! value.
+
+use std::io::{self, Read, Write};
+
+use crate::{Decode, DecodeError, Encode, EncodeCursor, EncodeError, decode_value, encoded_len};
+
+/// Length of the prefix written before each message.
+pub const MESSAGE_LEN_PREFIX_LEN: usize = 4;
+
+impl From<DecodeError> for io::Error {
+    fn from(e: DecodeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+impl From<EncodeError> for io::Error {
+    fn from(e: EncodeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, e)
+    }
+}
+
+/// Write `value` to `w` as a length-prefixed message.
+pub fn write_message<E: Encode>(w: &mut impl Write, value: E) -> io::Result<()> {
+    let len = encoded_len(&value);
+    let prefix = u32::try_from(len).map_err(|_| EncodeError::MessageTooLarge)?;
+
+    let mut buf = vec![0u8; MESSAGE_LEN_PREFIX_LEN + len];
+    buf[..MESSAGE_LEN_PREFIX_LEN].copy_from_slice(&prefix.to_le_bytes());
+    value.encode(&mut EncodeCursor::new::<E>(
+        &mut buf[MESSAGE_LEN_PREFIX_LEN..],
+    ));
+
+    w.write_all(&buf)
+}
+
+/// Read a length-prefixed message from `r` into `buf` and decode it. `buf` is cleared first, so the
+/// same buffer can be reused for every message, and holds the message's encoded value afterwards.
+///
+/// Fails with [`io::ErrorKind::UnexpectedEof`] if `r` ends before the message does, including
+/// before its length prefix, and with [`io::ErrorKind::InvalidData`] wrapping a [`DecodeError`] if
+/// the message isn't a valid `D`.
+pub fn read_message<'a, D: Decode<'a>>(r: &mut impl Read, buf: &'a mut Vec<u8>) -> io::Result<D> {
+    let mut prefix = [0u8; MESSAGE_LEN_PREFIX_LEN];
+    r.read_exact(&mut prefix)?;
+    let len = u32::from_le_bytes(prefix) as usize;
+
+    buf.clear();
+    // Grow the buffer as data arrives rather than trusting the prefix up front, so that a bogus
+    // length can't make us allocate gigabytes.
+    r.take(len as u64).read_to_end(buf)?;
+    if buf.len() < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Ok(decode_value(buf)?)
+}
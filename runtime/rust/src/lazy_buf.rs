@@ -0,0 +1,156 @@
+use core::{
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::Deref,
+};
+
+use crate::{DecodeResult, Owned, decode_value};
+
+#[cfg(feature = "std")]
+use std::{rc::Rc, sync::Arc};
+
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::{boxed::Box, rc::Rc, sync::Arc, vec::Vec};
+
+/// An owner of encoded bytes that a [`LazyBuf`] can borrow from while it moves around.
+///
+/// # Safety
+///
+/// `deref` must always return the same slice - same address, same length - and the bytes in it
+/// must stay valid and unmodified for as long as the owner exists, even when the owner is moved.
+pub unsafe trait StableBuf: Deref<Target = [u8]> {}
+
+unsafe impl StableBuf for &[u8] {}
+#[cfg(any(feature = "std", feature = "alloc"))]
+unsafe impl StableBuf for Vec<u8> {}
+#[cfg(any(feature = "std", feature = "alloc"))]
+unsafe impl StableBuf for Box<[u8]> {}
+#[cfg(any(feature = "std", feature = "alloc"))]
+unsafe impl StableBuf for Arc<[u8]> {}
+#[cfg(any(feature = "std", feature = "alloc"))]
+unsafe impl StableBuf for Rc<[u8]> {}
+
+/// A lazily decoded `T` bundled with the buffer it borrows from, so that it can be stored and
+/// passed around (e.g. between tasks) without a lifetime.
+///
+/// The lazy value is decoded once up front and stored with its lifetime extended to `'static`,
+/// which is sound because:
+/// - [`StableBuf`] guarantees the bytes it borrows stay put for as long as `buf` lives, and the
+///   lazy value is stored next to `buf`.
+/// - The lazy value is only ever handed out with its lifetime shortened to a borrow of the
+///   `LazyBuf`, through [`Owned::shorten_lazy`] rather than a transmute, so the compiler checks
+///   that doing so is valid.
+/// - Both are stored in [`NoAlias`], so moving `buf` (e.g. a `Box`) doesn't invalidate the
+///   references into it.
+pub struct LazyBuf<T: Owned, B> {
+    lazy: NoAlias<T::Lazy<'static>>,
+    buf: NoAlias<B>,
+}
+
+/// Hides a value from the compiler's aliasing assumptions: moving or passing around a `Box` or
+/// reference asserts that it's valid and, for `Box`, unique - which doesn't hold for a `LazyBuf`'s
+/// buffer and the references into it. Like yoke's `KindaSortaDangling`.
+struct NoAlias<T>(MaybeUninit<T>);
+
+impl<T> NoAlias<T> {
+    #[inline]
+    fn new(value: T) -> Self {
+        Self(MaybeUninit::new(value))
+    }
+
+    #[inline]
+    fn get(&self) -> &T {
+        // SAFETY: always initialized, see `new`.
+        unsafe { self.0.assume_init_ref() }
+    }
+
+    #[inline]
+    fn into_inner(self) -> T {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: always initialized, and not dropped again since `this` is `ManuallyDrop`.
+        unsafe { this.0.assume_init_read() }
+    }
+}
+
+impl<T> Drop for NoAlias<T> {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: always initialized, see `new`.
+        unsafe { self.0.assume_init_drop() }
+    }
+}
+
+mod sealed {
+    // workaround for compiler limitation
+    // https://github.com/rust-lang/rust/issues/49601#issuecomment-1007884546
+    pub trait LazyBufMapFn<T, U>: FnOnce(T) -> U {}
+    impl<F, T, U> LazyBufMapFn<T, U> for F where F: FnOnce(T) -> U {}
+}
+
+impl<T: Owned, B: StableBuf> LazyBuf<T, B> {
+    /// Panics if `buf` doesn't contain a valid `T` - see [`Self::try_new`].
+    #[inline]
+    #[track_caller]
+    pub fn new(buf: B) -> Self {
+        match Self::try_new(buf) {
+            Ok(lazy_buf) => lazy_buf,
+            Err(e) => panic!("failed to decode LazyBuf: {e}"),
+        }
+    }
+
+    #[inline]
+    pub fn try_new(buf: B) -> DecodeResult<Self> {
+        let buf = NoAlias::new(buf);
+        // SAFETY: `StableBuf` guarantees the bytes outlive `buf`, and `lazy` is never exposed with
+        // a lifetime longer than a borrow of `self`.
+        let bytes: &'static [u8] = unsafe { &*(buf.get().deref() as *const [u8]) };
+        let lazy = decode_value::<T::Lazy<'static>>(bytes)?;
+        Ok(Self {
+            lazy: NoAlias::new(lazy),
+            buf,
+        })
+    }
+
+    #[inline]
+    pub fn get(&self) -> T::Lazy<'_> {
+        T::shorten_lazy(*self.lazy.get())
+    }
+
+    /// The encoded bytes.
+    #[inline]
+    pub fn buf(&self) -> &[u8] {
+        self.buf.get()
+    }
+
+    #[inline]
+    pub fn into_buf(self) -> B {
+        self.buf.into_inner()
+    }
+
+    /// Project the lazy value to another one borrowing from the same buffer, e.g. one of its
+    /// fields.
+    #[inline]
+    pub fn map<U: Owned, F>(self, f: F) -> LazyBuf<U, B>
+    where
+        F: for<'a> sealed::LazyBufMapFn<T::Lazy<'a>, U::Lazy<'a>>,
+    {
+        // `f` works for any lifetime, so it can't smuggle the `'static` lazy value out.
+        let lazy = f(*self.lazy.get());
+        LazyBuf {
+            lazy: NoAlias::new(lazy),
+            buf: self.buf,
+        }
+    }
+
+    /// Fallible version of [`Self::map`], e.g. for projecting to a lazily decoded field.
+    #[inline]
+    pub fn try_map<U: Owned, E, F>(self, f: F) -> Result<LazyBuf<U, B>, E>
+    where
+        F: for<'a> sealed::LazyBufMapFn<T::Lazy<'a>, Result<U::Lazy<'a>, E>>,
+    {
+        let lazy = f(*self.lazy.get())?;
+        Ok(LazyBuf {
+            lazy: NoAlias::new(lazy),
+            buf: self.buf,
+        })
+    }
+}
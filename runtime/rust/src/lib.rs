@@ -3,61 +3,191 @@
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
 extern crate alloc;
 
-use core::{ops::Deref, pin::Pin};
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::{vec, vec::Vec};
 
-pub use boxed::BoxLazy;
+#[cfg(feature = "async")]
+pub use async_io::{AsyncMessageReader, AsyncMessageWriter, DEFAULT_MAX_BUFFERED_LEN};
+pub use boxed::{BoxLazy, SplicedBox};
+#[cfg(feature = "bytes")]
+pub use bytes_buf::{decode_value_from_bytes, encode_to_buf};
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use canonical::is_canonical;
+pub use copy_primitives::Primitive;
 pub use decode_cursor::DecodeCursor;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use dedup::DedupReport;
+pub use decode_error::{DecodeError, DecodeErrorKind, MAX_ERROR_PATH_LEN, PathSegment};
+pub use decode_limits::DecodeLimits;
 pub use encode_cursor::EncodeCursor;
-pub use list::{ListGen, ListLazy};
+pub use encode_error::{EncodeError, EncodeResult};
+pub use fingerprint::{Fingerprint, fingerprint_with_args};
+#[cfg(feature = "std")]
+pub use io::{MESSAGE_LEN_PREFIX_LEN, read_message, write_message};
+pub use lazy_buf::{LazyBuf, StableBuf};
+pub use list::{ListGen, ListLazy, ListLazyChunks, ListLazyIter, ListLazyTryIter, ListOnce};
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use list_builder::{ListBuilder, ListBuilderGen};
+pub use list_mut::ListMut;
+pub use verify::{Verified, verify_value, verify_value_with_format};
+pub use wire_format::WireFormat;
 
+#[cfg(feature = "async")]
+mod async_io;
 mod boxed;
+#[cfg(feature = "bytes")]
+mod bytes_buf;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod canonical;
+mod checksum;
+pub mod compression;
+#[cfg(feature = "std")]
+pub mod container;
 mod copy_primitives;
 mod decode_cursor;
+mod decode_error;
+mod decode_limits;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod dedup;
 mod encode_cursor;
+mod encode_error;
+pub mod envelope;
+mod fingerprint;
+#[cfg(feature = "std")]
+pub mod framing;
+#[cfg(feature = "std")]
+mod io;
+mod lazy_buf;
 mod list;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod list_builder;
+mod list_mut;
+#[cfg(feature = "mmap")]
+mod mmap;
 mod option;
 mod result;
 mod string;
 #[cfg(test)]
 mod tests;
+mod verify;
+mod wire_format;
 
 pub trait BaseLen {
     const BASE_LEN: usize;
+
+    /// The size of the base area in [`WireFormat::Wide`], where offsets and lengths are 8 bytes
+    /// rather than 4. It has no default so that implementations can't forget the offsets and
+    /// lengths they hold - it's just `Self::BASE_LEN` for types without any.
+    const WIDE_BASE_LEN: usize;
+
+    /// Whether values of this type can take up any scratch space. If not, a value is encoded as
+    /// just its base area, which lazy values copy as is when they're re-encoded, and which is the
+    /// same in every [`WireFormat`].
+    const HAS_SCRATCH: bool = true;
 }
 
+/// Implementations must write every byte of the base and scratch space they take, zeroing any
+/// padding, so that equal values always encode to the same bytes - see [`is_canonical`].
 pub trait Encode: BaseLen {
     fn scratch_len(&self) -> usize;
 
-    fn encode(&self, cursor: &mut EncodeCursor);
-}
+    /// The scratch space taken up in [`WireFormat::Wide`]. Like [`BaseLen::WIDE_BASE_LEN`] it has
+    /// no default, as it differs from [`scratch_len`](Self::scratch_len) whenever the scratch
+    /// space holds offsets or lengths.
+    fn wide_scratch_len(&self) -> usize;
 
-#[derive(Debug)]
-pub struct DecodeError;
+    fn encode(&self, cursor: &mut EncodeCursor);
 
-impl core::fmt::Display for DecodeError {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "failed to decode mproto value")
+    /// Encode the items of a list in scratch space, writing their offset into the base area.
+    /// Primitives override this to copy all of their bytes at once.
+    #[doc(hidden)]
+    #[inline]
+    fn encode_list(items: &[Self], cursor: &mut EncodeCursor)
+    where
+        Self: Sized,
+    {
+        let items_len = items.len() * cursor.format().base_len::<Self>();
+        cursor.inner_in_scratch(items_len, |cursor| {
+            for item in items {
+                item.encode(cursor);
+            }
+        });
     }
 }
 
-impl core::error::Error for DecodeError {}
-
 pub type DecodeResult<T> = Result<T, DecodeError>;
 
 pub trait Decode<'a>: BaseLen + Sized {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self>;
+
+    /// Decode the `len` items of a list, whose base areas start at the cursor. Primitives
+    /// override this to copy all of their bytes at once.
+    #[doc(hidden)]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline]
+    fn decode_vec(cursor: &DecodeCursor<'a>, len: usize) -> DecodeResult<Vec<Self>> {
+        let mut vec = Vec::with_capacity(len);
+        for i in 0..len {
+            vec.push(Self::decode(cursor).map_err(|e| e.at_index(i))?);
+        }
+        Ok(vec)
+    }
 }
 
 pub trait Compatible<Other: ?Sized>: Encode {}
 
-pub trait Owned: Encode + for<'a> Decode<'a> + Compatible<Self> + Clone + Send + Sync + 'static {
+pub trait Owned:
+    Encode + for<'a> Decode<'a> + Compatible<Self> + Clone + Send + Sync + 'static
+{
     type Lazy<'a>: Lazy<'a, Owned = Self>;
 
     fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self>;
+
+    /// Shorten the lifetime of a lazy value. This is what makes [`LazyBuf`] sound, so it must not
+    /// be implemented with `unsafe` code.
+    ///
+    /// If `Self::Lazy<'a>` is covariant in `'a`, this is just `lazy` - the compiler checks the
+    /// covariance. Lazy types made of other types' lazy values, like `Option<T::Lazy<'a>>`, aren't
+    /// known to be covariant and shorten each of those values instead.
+    fn shorten_lazy<'b, 'a: 'b>(lazy: Self::Lazy<'a>) -> Self::Lazy<'b>;
+
+    /// Check that a valid value of this type can be decoded at the cursor, advancing the cursor
+    /// past it. See [`verify_value`].
+    ///
+    /// The default implementation decodes the whole owned value, so types that allocate should
+    /// override it to walk the buffer instead.
+    #[inline]
+    fn verify(cursor: &DecodeCursor<'_>) -> DecodeResult<()> {
+        Self::decode(cursor).map(|_| ())
+    }
+
+    /// Re-encode the value at the cursor into `out`, advancing the cursor past it. This is how
+    /// lazy values holding a `Self` are encoded.
+    ///
+    /// The default implementation decodes the lazy value and encodes that. Types with scratch
+    /// space override it to copy their scratch regions as they are and write new offsets to them,
+    /// so that e.g. strings aren't checked to be UTF-8 again.
+    #[inline]
+    fn copy_encoded(cursor: &DecodeCursor<'_>, out: &mut EncodeCursor) -> DecodeResult<()> {
+        Self::Lazy::decode(cursor)?.encode(out);
+        Ok(())
+    }
+
+    /// The scratch space [`copy_encoded`](Self::copy_encoded) takes to re-encode the value at the
+    /// cursor in `format`, advancing the cursor past it.
+    #[inline]
+    fn copy_scratch_len(cursor: &DecodeCursor<'_>, format: WireFormat) -> DecodeResult<usize> {
+        Ok(format.scratch_len(&Self::Lazy::decode(cursor)?))
+    }
 }
 
 pub trait Lazy<'a>: Encode + Decode<'a> + Copy + Clone + PartialEq + core::fmt::Debug {
     type Owned: Owned<Lazy<'a> = Self>;
+
+    /// What a [`Verified`] lazy value of this type can be accessed as, without error handling.
+    type View: Copy + core::fmt::Debug;
+
+    fn view(verified: Verified<Self>) -> Self::View;
 }
 
 #[inline]
@@ -65,6 +195,12 @@ pub fn encoded_len<T: Encode>(value: T) -> usize {
     T::BASE_LEN + value.scratch_len()
 }
 
+/// Like [`encoded_len`], but in the given [`WireFormat`].
+#[inline]
+pub fn encoded_len_with_format<T: Encode>(value: T, format: WireFormat) -> usize {
+    format.base_len::<T>() + format.scratch_len(&value)
+}
+
 #[inline]
 pub fn encode_value<E: Encode>(v: E, mut buf: impl AsMut<[u8]>) -> usize {
     let mut cursor = EncodeCursor::new::<E>(buf.as_mut());
@@ -72,6 +208,30 @@ pub fn encode_value<E: Encode>(v: E, mut buf: impl AsMut<[u8]>) -> usize {
     cursor.encoded_len()
 }
 
+/// Like [`encode_value`], but returns an error instead of panicking if `buf` is too small to hold
+/// the encoded value. Nothing is written to `buf` on error.
+#[inline]
+pub fn try_encode_value<E: Encode>(v: E, buf: impl AsMut<[u8]>) -> EncodeResult<usize> {
+    try_encode_value_with_format(v, buf, WireFormat::Absolute)
+}
+
+/// Like [`try_encode_value`], but in the given [`WireFormat`]. Values too large for 32-bit offsets
+/// fail with [`EncodeError::MessageTooLarge`] unless the format is [`WireFormat::Wide`].
+#[inline]
+pub fn try_encode_value_with_format<E: Encode>(
+    v: E,
+    mut buf: impl AsMut<[u8]>,
+    format: WireFormat,
+) -> EncodeResult<usize> {
+    let encoded_len = format
+        .base_len::<E>()
+        .checked_add(format.scratch_len(&v))
+        .ok_or(EncodeError::MessageTooLarge)?;
+    let mut cursor = EncodeCursor::try_new_with_format::<E>(buf.as_mut(), encoded_len, format)?;
+    v.encode(&mut cursor);
+    Ok(cursor.encoded_len())
+}
+
 #[cfg(any(feature = "std", feature = "alloc"))]
 #[inline]
 pub fn encode_value_vec<E: Encode>(v: E) -> Vec<u8> {
@@ -81,61 +241,71 @@ pub fn encode_value_vec<E: Encode>(v: E) -> Vec<u8> {
     buf
 }
 
+/// Like [`encode_value_vec`], but in the given [`WireFormat`].
+#[cfg(any(feature = "std", feature = "alloc"))]
 #[inline]
-pub fn decode_value<'a, D: Decode<'a>>(buf: &'a [u8]) -> DecodeResult<D> {
-    Decode::decode(&DecodeCursor::new(buf))
+pub fn encode_value_vec_with_format<E: Encode>(v: E, format: WireFormat) -> Vec<u8> {
+    let mut buf = vec![0u8; encoded_len_with_format(&v, format)];
+    let mut cursor = EncodeCursor::new::<E>(buf.as_mut()).with_format(format);
+    v.encode(&mut cursor);
+    buf
 }
 
-// TODO Consider using Yoke, either directly in apps or wrapping it here.
-pub struct LazyBuf<T: Owned, B> {
-    buf: Pin<B>,
-    lazy: T::Lazy<'static>,
+/// Like [`encode_value_vec`], but with identical string and primitive list payloads sharing
+/// scratch space - see [`EncodeCursor::with_dedup`].
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn encode_value_vec_dedup<E: Encode>(v: E) -> (Vec<u8>, DedupReport) {
+    // The encoded length without deduplication is an upper bound.
+    let mut buf = vec![0u8; encoded_len(&v)];
+    let mut cursor = EncodeCursor::new::<E>(buf.as_mut()).with_dedup();
+    v.encode(&mut cursor);
+    let (len, report) = (cursor.encoded_len(), cursor.dedup_report().unwrap_or_default());
+    buf.truncate(len);
+    (buf, report)
 }
 
-mod sealed {
-    // workaround for compiler limitation
-    // https://github.com/rust-lang/rust/issues/49601#issuecomment-1007884546
-    pub trait LazyBufMapFn<T, U>: FnOnce(T) -> U {}
-    impl<F, T, U> LazyBufMapFn<T, U> for F where F: FnOnce(T) -> U {}
+/// Encode `v` onto the end of `buf` in a single pass, growing `buf` as needed - see
+/// [`EncodeCursor::growable`]. Returns the encoded length.
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[inline]
+pub fn encode_value_extend<E: Encode>(v: E, buf: &mut Vec<u8>) -> usize {
+    let mut cursor = EncodeCursor::growable::<E>(buf);
+    v.encode(&mut cursor);
+    cursor.encoded_len()
 }
 
-impl<T: Owned, B: Deref<Target = [u8]> + core::marker::Unpin> LazyBuf<T, B> {
-    #[inline]
-    pub fn new(buf: B) -> Self {
-        let buf = Pin::new(buf);
-        let lazy: T::Lazy<'_> = decode_value(buf.as_ref().get_ref().as_ref()).unwrap();
-        // TODO is this actually safe to do?
-        // Erase lifetime of lazy value
-        let lazy = unsafe { core::mem::transmute(lazy) };
-        Self { buf, lazy }
-    }
+#[inline]
+pub fn decode_value<'a, D: Decode<'a>>(buf: &'a [u8]) -> DecodeResult<D> {
+    Decode::decode(&DecodeCursor::new(buf))
+}
 
-    #[inline]
-    pub fn get<'a>(&'a self) -> T::Lazy<'a> {
-        // TODO is this actually safe to do?
-        unsafe { core::mem::transmute(self.lazy.clone()) }
-    }
+/// Like [`decode_value`], for a buffer encoded in the given [`WireFormat`].
+#[inline]
+pub fn decode_value_with_format<'a, D: Decode<'a>>(
+    buf: &'a [u8],
+    format: WireFormat,
+) -> DecodeResult<D> {
+    Decode::decode(&DecodeCursor::new(buf).with_format(format))
+}
 
-    #[inline]
-    pub fn map<U: Owned, F>(self, f: F) -> LazyBuf<U, B>
-    where
-        F: for<'a> sealed::LazyBufMapFn<T::Lazy<'a>, U::Lazy<'a>>,
-    {
-        let lazy = f(self.lazy);
-        // TODO is this actually safe to do?
-        // Erase lifetime of lazy value
-        let lazy = unsafe { core::mem::transmute(lazy) };
-        LazyBuf {
-            buf: self.buf,
-            lazy,
-        }
-    }
+/// Like [`decode_value`], but with custom [`DecodeLimits`] for decoding untrusted buffers.
+///
+/// Lazy values keep the limits, and apply them to values later decoded through their accessors as
+/// well.
+#[inline]
+pub fn decode_value_with_limits<'a, D: Decode<'a>>(
+    buf: &'a [u8],
+    limits: DecodeLimits,
+) -> DecodeResult<D> {
+    Decode::decode(&DecodeCursor::with_limits(buf, limits))
 }
 
 impl<T, U: Compatible<T> + ?Sized> Compatible<T> for &U {}
 
 impl<T: BaseLen + ?Sized> BaseLen for &T {
     const BASE_LEN: usize = T::BASE_LEN;
+    const WIDE_BASE_LEN: usize = T::WIDE_BASE_LEN;
+    const HAS_SCRATCH: bool = T::HAS_SCRATCH;
 }
 
 impl<T: Encode + ?Sized> Encode for &T {
@@ -144,6 +314,11 @@ impl<T: Encode + ?Sized> Encode for &T {
         T::scratch_len(self)
     }
 
+    #[inline]
+    fn wide_scratch_len(&self) -> usize {
+        T::wide_scratch_len(self)
+    }
+
     #[inline]
     fn encode(&self, cursor: &mut EncodeCursor) {
         T::encode(self, cursor);
@@ -152,6 +327,8 @@ impl<T: Encode + ?Sized> Encode for &T {
 
 impl<T: BaseLen + ?Sized> BaseLen for &mut T {
     const BASE_LEN: usize = T::BASE_LEN;
+    const WIDE_BASE_LEN: usize = T::WIDE_BASE_LEN;
+    const HAS_SCRATCH: bool = T::HAS_SCRATCH;
 }
 
 impl<T: Encode + ?Sized> Encode for &mut T {
@@ -160,6 +337,11 @@ impl<T: Encode + ?Sized> Encode for &mut T {
         T::scratch_len(self)
     }
 
+    #[inline]
+    fn wide_scratch_len(&self) -> usize {
+        T::wide_scratch_len(self)
+    }
+
     #[inline]
     fn encode(&self, cursor: &mut EncodeCursor) {
         T::encode(self, cursor);
@@ -1,7 +1,13 @@
-use crate::{BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeResult, Encode, EncodeCursor, Owned};
+use core::mem::MaybeUninit;
 
-#[cfg(any(feature = "std", feature = "alloc"))]
-use crate::Lazy;
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use crate::{
+    BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeErrorKind, DecodeLimits,
+    DecodeResult, Encode, EncodeCursor, Lazy, Owned, Primitive, Verified, WireFormat,
+    copy_primitives::copy_from_le_bytes,
+};
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<T: Owned + Compatible<T>> Owned for Vec<T> {
@@ -10,11 +16,56 @@ impl<T: Owned + Compatible<T>> Owned for Vec<T> {
     fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
         lazy.try_into()
     }
+
+    #[inline]
+    fn shorten_lazy<'b, 'a: 'b>(lazy: Self::Lazy<'a>) -> Self::Lazy<'b> {
+        lazy
+    }
+
+    fn verify(cursor: &DecodeCursor<'_>) -> DecodeResult<()> {
+        let len = cursor.read_len()?;
+        cursor.check_list_len(len)?;
+
+        cursor.inner_in_scratch(|cursor| {
+            check_items_len::<T>(cursor, len)?;
+            for i in 0..len {
+                T::verify(cursor).map_err(|e| e.at_index(i))?;
+            }
+            Ok(())
+        })
+    }
+
+    fn copy_encoded(cursor: &DecodeCursor<'_>, out: &mut EncodeCursor) -> DecodeResult<()> {
+        let len = cursor.read_len()?;
+        cursor.check_list_len(len)?;
+        out.write_len(len);
+
+        cursor.inner_in_scratch(|cursor| {
+            check_items_len::<T>(cursor, len)?;
+            copy_encoded_items::<T>(cursor, len, out)
+        })
+    }
+
+    fn copy_scratch_len(cursor: &DecodeCursor<'_>, format: WireFormat) -> DecodeResult<usize> {
+        let len = cursor.read_len()?;
+        cursor.check_list_len(len)?;
+
+        cursor.inner_in_scratch(|cursor| {
+            check_items_len::<T>(cursor, len)?;
+            copy_items_scratch_len::<T>(cursor, len, format)
+        })
+    }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<'a, T: Owned + Compatible<T>> Lazy<'a> for ListLazy<'a, T> {
     type Owned = Vec<T>;
+    type View = Verified<ListLazy<'a, T>>;
+
+    #[inline]
+    fn view(verified: Verified<Self>) -> Self::View {
+        verified
+    }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
@@ -26,30 +77,28 @@ where
     type Error = DecodeError;
 
     fn try_from(other: ListLazy<'a, T>) -> Result<Self, Self::Error> {
-        let cursor = DecodeCursor::at_offset(other.buffer, other.offset);
-        Decode::decode(&cursor)
+        U::decode_vec(&other.items_cursor(other.items_offset), other.len)
     }
 }
 
 impl<T: BaseLen> BaseLen for [T] {
     const BASE_LEN: usize = 4 + 4;
+    const WIDE_BASE_LEN: usize = 8 + 8;
 }
 
 impl<T: Encode> Encode for [T] {
     fn scratch_len(&self) -> usize {
-        self.len() * T::BASE_LEN + self.iter().fold(0, |sum, item| sum + item.scratch_len())
+        list_scratch_len(self.len(), self.iter(), WireFormat::Absolute)
+    }
+
+    fn wide_scratch_len(&self) -> usize {
+        list_scratch_len(self.len(), self.iter(), WireFormat::Wide)
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
-        cursor
-            .base(4)
-            .copy_from_slice(&(self.len() as u32).to_le_bytes());
+        cursor.write_len(self.len());
 
-        cursor.inner_in_scratch(self.len() * T::BASE_LEN, |cursor| {
-            for item in self {
-                item.encode(cursor);
-            }
-        });
+        T::encode_list(self, cursor);
     }
 }
 
@@ -62,47 +111,50 @@ impl<T, U: Compatible<T>> Compatible<[T]> for Vec<U> {}
 
 impl<'a> Decode<'a> for &'a [u8] {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        let len = u32::from_le_bytes(cursor.base(4).try_into().map_err(|_| DecodeError)?) as usize;
+        let len = cursor.read_len()?;
+        cursor.check_list_len(len)?;
 
-        Ok(cursor.scratch(len))
+        cursor.scratch(len)
     }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<T: BaseLen> BaseLen for Vec<T> {
     const BASE_LEN: usize = 4 + 4;
+    const WIDE_BASE_LEN: usize = 8 + 8;
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<T: Encode> Encode for Vec<T> {
     fn scratch_len(&self) -> usize {
-        self.len() * T::BASE_LEN + self.iter().fold(0, |sum, item| sum + item.scratch_len())
+        list_scratch_len(self.len(), self.iter(), WireFormat::Absolute)
+    }
+
+    fn wide_scratch_len(&self) -> usize {
+        list_scratch_len(self.len(), self.iter(), WireFormat::Wide)
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
-        cursor
-            .base(4)
-            .copy_from_slice(&(self.len() as u32).to_le_bytes());
+        cursor.write_len(self.len());
 
-        cursor.inner_in_scratch(self.len() * T::BASE_LEN, |cursor| {
-            for item in self {
-                item.encode(cursor);
-            }
-        });
+        T::encode_list(self, cursor);
     }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<'a, T: Decode<'a>> Decode<'a> for Vec<T> {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        let len = u32::from_le_bytes(cursor.base(4).try_into().map_err(|_| DecodeError)?) as usize;
+        let len = cursor.read_len()?;
+        cursor.check_list_len(len)?;
+        // Zero-sized items don't take up any space in the buffer, so this is the only bound on
+        // how much a list of them can allocate.
+        cursor.reserve_owned(len.saturating_mul(core::mem::size_of::<T>()))?;
 
         cursor.inner_in_scratch(|cursor| {
-            let mut vec = Vec::with_capacity(len);
-            for _ in 0..len {
-                vec.push(T::decode(cursor)?);
-            }
-            Ok(vec)
+            // Make sure the buffer can actually hold `len` items before allocating space for them.
+            check_items_len::<T>(cursor, len)?;
+
+            T::decode_vec(cursor, len)
         })
     }
 }
@@ -114,9 +166,14 @@ where
     U: PartialEq<T::Lazy<'a>>,
 {
     fn eq(&self, other: &ListLazy<'a, T>) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
         for (i, item) in self.iter().enumerate() {
-            if *item != other.get(i).unwrap() {
-                return false;
+            match other.get(i) {
+                Ok(other_item) if *item == other_item => {}
+                _ => return false,
             }
         }
 
@@ -124,10 +181,13 @@ where
     }
 }
 
+/// A list encoded from an iterator, which is cloned for each pass over the items - see
+/// [`ListOnce`] for iterators that can't be.
 pub struct ListGen<I: ExactSizeIterator>(pub I);
 
 impl<I: ExactSizeIterator> BaseLen for ListGen<I> {
     const BASE_LEN: usize = 4 + 4;
+    const WIDE_BASE_LEN: usize = 8 + 8;
 }
 
 impl<I: Clone + ExactSizeIterator> Encode for ListGen<I>
@@ -135,16 +195,18 @@ where
     I::Item: Encode,
 {
     fn scratch_len(&self) -> usize {
-        self.0.len() * I::Item::BASE_LEN
-            + self.0.clone().fold(0, |sum, item| sum + item.scratch_len())
+        list_scratch_len(self.0.len(), self.0.clone(), WireFormat::Absolute)
+    }
+
+    fn wide_scratch_len(&self) -> usize {
+        list_scratch_len(self.0.len(), self.0.clone(), WireFormat::Wide)
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
-        cursor
-            .base(4)
-            .copy_from_slice(&(self.0.len() as u32).to_le_bytes());
+        cursor.write_len(self.0.len());
 
-        cursor.inner_in_scratch(self.0.len() * I::Item::BASE_LEN, |cursor| {
+        let items_len = self.0.len() * cursor.format().base_len::<I::Item>();
+        cursor.inner_in_scratch(items_len, |cursor| {
             for item in self.0.clone() {
                 item.encode(cursor);
             }
@@ -158,9 +220,85 @@ impl<T: Owned, U: Compatible<T>, I: Clone + ExactSizeIterator<Item = U>> Compati
 {
 }
 
+/// A list encoded from an iterator that can only be walked once, e.g. one draining a channel.
+///
+/// Unlike [`ListGen`] the iterator needn't be `Clone`, but there's no second pass over it to work
+/// out the list's [`scratch_len`](Encode::scratch_len) - unless the items have no scratch space,
+/// it can only be encoded with [`encode_value_extend`](crate::encode_value_extend). Either way it
+/// can only be encoded once. Anything else panics.
+pub struct ListOnce<I>(core::cell::Cell<Option<I>>);
+
+impl<I: ExactSizeIterator> ListOnce<I> {
+    #[inline]
+    pub fn new(items: I) -> Self {
+        Self(core::cell::Cell::new(Some(items)))
+    }
+
+    fn take(&self) -> I {
+        self.0.take().expect("ListOnce encoded more than once")
+    }
+
+    fn items_scratch_len(&self, format: WireFormat) -> usize
+    where
+        I::Item: BaseLen,
+    {
+        assert!(
+            !I::Item::HAS_SCRATCH,
+            "the scratch_len of a ListOnce with scratch space in its items is unknown, \
+             encode it with encode_value_extend"
+        );
+        // Read the length without taking the iterator, which is still to be encoded.
+        let items = self.take();
+        let len = items.len();
+        self.0.set(Some(items));
+        len * format.base_len::<I::Item>()
+    }
+}
+
+impl<I> BaseLen for ListOnce<I> {
+    const BASE_LEN: usize = 4 + 4;
+    const WIDE_BASE_LEN: usize = 8 + 8;
+}
+
+impl<I: ExactSizeIterator> Encode for ListOnce<I>
+where
+    I::Item: Encode,
+{
+    fn scratch_len(&self) -> usize {
+        self.items_scratch_len(WireFormat::Absolute)
+    }
+
+    fn wide_scratch_len(&self) -> usize {
+        self.items_scratch_len(WireFormat::Wide)
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let items = self.take();
+        cursor.write_len(items.len());
+
+        let items_len = items.len() * cursor.format().base_len::<I::Item>();
+        cursor.inner_in_scratch(items_len, |cursor| {
+            for item in items {
+                item.encode(cursor);
+            }
+        });
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<T: Owned, U: Compatible<T>, I: ExactSizeIterator<Item = U>> Compatible<Vec<T>>
+    for ListOnce<I>
+{
+}
+
 pub struct ListLazy<'a, T> {
     buffer: &'a [u8],
-    offset: usize,
+    len: usize,
+    items_offset: usize,
+    format: WireFormat,
+    // Limits and depth of the cursor the items are decoded with.
+    limits: DecodeLimits,
+    depth: usize,
     item_ty: core::marker::PhantomData<T>,
 }
 
@@ -174,109 +312,488 @@ impl<T: Owned, U: Compatible<T>> Compatible<ListLazy<'_, T>> for Vec<U> {}
 
 impl<'a, T: Owned> BaseLen for ListLazy<'a, T> {
     const BASE_LEN: usize = 4 + 4;
+    const WIDE_BASE_LEN: usize = 8 + 8;
 }
 
+/// Items are re-encoded with [`Owned::copy_encoded`], and this panics if an item fails to decode.
+/// It can't for a list decoded from a buffer that passed [`verify_value`](crate::verify_value).
 impl<'a, T: Owned> Encode for ListLazy<'a, T> {
     fn scratch_len(&self) -> usize {
-        self.len() * T::BASE_LEN + self.iter().fold(0, |sum, item| sum + item.scratch_len())
+        copy_items_scratch_len::<T>(
+            &self.items_cursor(self.items_offset),
+            self.len,
+            WireFormat::Absolute,
+        )
+        .unwrap()
     }
 
-    fn encode(&self, cursor: &mut EncodeCursor) {
-        cursor
-            .base(4)
-            .copy_from_slice(&(self.len() as u32).to_le_bytes());
+    fn wide_scratch_len(&self) -> usize {
+        copy_items_scratch_len::<T>(
+            &self.items_cursor(self.items_offset),
+            self.len,
+            WireFormat::Wide,
+        )
+        .unwrap()
+    }
 
-        cursor.inner_in_scratch(self.len() * T::BASE_LEN, |cursor| {
-            for item in self {
-                item.encode(cursor);
-            }
-        });
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        cursor.write_len(self.len());
+        copy_encoded_items::<T>(&self.items_cursor(self.items_offset), self.len, cursor).unwrap();
     }
 }
 
 impl<'a, T: Owned> ListLazy<'a, T> {
     pub fn len(&self) -> usize {
-        u32::from_le_bytes(
-            self.buffer[self.offset..self.offset + 4]
-                .try_into()
-                .unwrap(),
-        ) as usize
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     pub fn get(&self, index: usize) -> DecodeResult<T::Lazy<'a>> {
-        if index >= self.len() {
-            return Err(DecodeError);
+        if index >= self.len {
+            return Err(DecodeError::new(
+                DecodeErrorKind::IndexOutOfBounds {
+                    index,
+                    len: self.len,
+                },
+                self.items_offset,
+            ));
         }
 
-        let list_scratch_offset = u32::from_le_bytes(
-            self.buffer[self.offset + 4..self.offset + 8]
-                .try_into()
-                .map_err(|_| DecodeError)?,
-        ) as usize;
+        // Can't overflow - `ListLazy::decode` checked that all items lie within the buffer.
+        Decode::decode(&self.items_cursor(self.items_offset + index * self.item_len()))
+            .map_err(|e| e.at_index(index))
+    }
+
+    /// A list of the `len` items at the start of `items`, which must have no scratch space.
+    #[inline]
+    pub(crate) fn from_items(items: &'a [u8], len: usize, format: WireFormat) -> Self {
+        Self {
+            buffer: items,
+            len,
+            items_offset: 0,
+            format,
+            limits: DecodeLimits::DEFAULT,
+            depth: 0,
+            item_ty: core::marker::PhantomData,
+        }
+    }
+
+    #[inline]
+    fn items_cursor(&self, offset: usize) -> DecodeCursor<'a> {
+        DecodeCursor::at_offset(self.buffer, offset)
+            .with_format(self.format)
+            .with_limits_at_depth(self.limits, self.depth)
+    }
+
+    #[inline]
+    pub(crate) fn items_offset(&self) -> usize {
+        self.items_offset
+    }
+
+    /// The size of each item's base area.
+    #[inline]
+    fn item_len(&self) -> usize {
+        self.format.base_len::<T>()
+    }
 
-        Decode::decode(&DecodeCursor::at_offset(
-            self.buffer,
-            list_scratch_offset + (index * T::BASE_LEN),
-        ))
+    /// The encoded items' base areas. `ListLazy::decode` checked that they lie within the buffer.
+    #[inline]
+    fn items_bytes(&self) -> &'a [u8] {
+        &self.buffer[self.items_offset..self.items_offset + self.len * self.item_len()]
     }
 
+    /// Iterate over the items, stopping early if one fails to decode. See [`Self::try_iter`] to
+    /// handle errors instead.
     pub fn iter<'s>(&'s self) -> ListLazyIter<'s, 'a, T> {
-        let len = self.len();
         ListLazyIter {
+            inner: self.try_iter(),
+        }
+    }
+
+    /// Iterate over the items, yielding an error for each one that fails to decode.
+    pub fn try_iter<'s>(&'s self) -> ListLazyTryIter<'s, 'a, T> {
+        ListLazyTryIter {
             list_lazy: self,
-            len,
-            cursor: 0,
+            front: 0,
+            back: self.len,
         }
     }
+
+    /// Returns `Ok(None)` if the list is empty.
+    pub fn first(&self) -> DecodeResult<Option<T::Lazy<'a>>> {
+        self.try_iter().next().transpose()
+    }
+
+    /// Returns `Ok(None)` if the list is empty.
+    pub fn last(&self) -> DecodeResult<Option<T::Lazy<'a>>> {
+        self.try_iter().next_back().transpose()
+    }
+
+    /// Iterate over sub-lists of `chunk_size` items, the last of which may be shorter. Panics if
+    /// `chunk_size` is 0.
+    pub fn chunks(&self, chunk_size: usize) -> ListLazyChunks<'a, T> {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        ListLazyChunks {
+            rest: *self,
+            chunk_size,
+        }
+    }
+
+    /// Split the list into its first `mid` items and the rest. Panics if `mid > len`.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        assert!(mid <= self.len, "mid > len");
+        let rest = Self {
+            len: self.len - mid,
+            items_offset: self.items_offset + mid * self.item_len(),
+            ..*self
+        };
+        (Self { len: mid, ..*self }, rest)
+    }
+
+    /// Binary search a list sorted according to `f`, like [`slice::binary_search_by`]. Items are
+    /// only decoded as they're compared.
+    pub fn binary_search_by<F>(&self, mut f: F) -> DecodeResult<Result<usize, usize>>
+    where
+        F: FnMut(T::Lazy<'a>) -> core::cmp::Ordering,
+    {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match f(self.get(mid)?) {
+                core::cmp::Ordering::Less => low = mid + 1,
+                core::cmp::Ordering::Greater => high = mid,
+                core::cmp::Ordering::Equal => return Ok(Ok(mid)),
+            }
+        }
+        Ok(Err(low))
+    }
+}
+
+impl<'a, T: Primitive> ListLazy<'a, T> {
+    /// The items borrowed straight from the buffer. Returns `None` on big-endian platforms, or if
+    /// the items aren't aligned for `T` in the buffer, in which case use [`Self::copy_to_slice`].
+    #[inline]
+    pub fn as_slice(&self) -> Option<&'a [T]> {
+        #[cfg(target_endian = "little")]
+        {
+            let items = self.items_bytes().as_ptr().cast::<T>();
+            if items.is_aligned() {
+                // SAFETY: the items' bytes are in the buffer, aligned, and laid out just like a
+                // slice of little-endian primitives with no padding.
+                return Some(unsafe { core::slice::from_raw_parts(items, self.len) });
+            }
+        }
+
+        None
+    }
+
+    /// Copy the items to `dst`. Panics if `dst` isn't the same length as the list.
+    pub fn copy_to_slice(&self, dst: &mut [T]) {
+        assert_eq!(
+            dst.len(),
+            self.len,
+            "destination slice must be the same length as the list"
+        );
+
+        // SAFETY: `MaybeUninit<T>` has the same layout as `T`, and only initialized primitives
+        // are written through it.
+        let dst = unsafe {
+            core::slice::from_raw_parts_mut(dst.as_mut_ptr().cast::<MaybeUninit<T>>(), dst.len())
+        };
+        copy_from_le_bytes(self.items_bytes(), dst);
+    }
+}
+
+/// Infallible accessors for a verified list, see [`verify_value`](crate::verify_value).
+impl<'a, T: Owned> Verified<ListLazy<'a, T>> {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner().len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner().is_empty()
+    }
+
+    /// Returns `None` if `index` is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<<T::Lazy<'a> as Lazy<'a>>::View> {
+        if index >= self.len() {
+            return None;
+        }
+        Some(Verified::assume_ok(self.inner().get(index)).view())
+    }
+
+    /// Returns `None` if the list is empty.
+    #[inline]
+    pub fn first(&self) -> Option<<T::Lazy<'a> as Lazy<'a>>::View> {
+        self.get(0)
+    }
+
+    /// Returns `None` if the list is empty.
+    #[inline]
+    pub fn last(&self) -> Option<<T::Lazy<'a> as Lazy<'a>>::View> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    pub fn iter(
+        &self,
+    ) -> impl ExactSizeIterator<Item = <T::Lazy<'a> as Lazy<'a>>::View>
+    + DoubleEndedIterator
+    + core::iter::FusedIterator
+    + 'a {
+        let list = *self.inner();
+        (0..list.len()).map(move |i| Verified::assume_ok(list.get(i)).view())
+    }
+
+    /// Binary search a list sorted according to `f`, like [`slice::binary_search_by`].
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<usize, usize>
+    where
+        F: FnMut(<T::Lazy<'a> as Lazy<'a>>::View) -> core::cmp::Ordering,
+    {
+        let found = self
+            .inner()
+            .binary_search_by(|item| f(Verified::new_unchecked(item).view()));
+        Verified::assume_ok(found).into_inner()
+    }
 }
 
 impl<'a, T: Owned> Decode<'a> for ListLazy<'a, T> {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        let offset = cursor.offset();
-        cursor.advance(Self::BASE_LEN);
+        let len = cursor.read_len()?;
+        cursor.check_list_len(len)?;
+
+        // Validate the bounds of the items up front so that accessing them later can't go out of
+        // bounds.
+        let (items_offset, depth) = cursor.inner_in_scratch(|cursor| {
+            check_items_len::<T>(cursor, len)?;
+            Ok((cursor.offset(), cursor.depth()))
+        })?;
+
         Ok(ListLazy {
             buffer: cursor.buffer(),
-            offset,
+            len,
+            items_offset,
+            format: cursor.format(),
+            limits: *cursor.limits(),
+            depth,
             item_ty: core::marker::PhantomData,
         })
     }
 }
 
+/// The scratch space taken up by a list of `len` `items` in `format`: the items' base areas, then
+/// their own scratch space.
+#[inline]
+fn list_scratch_len<T: Encode>(
+    len: usize,
+    items: impl IntoIterator<Item = T>,
+    format: WireFormat,
+) -> usize {
+    let items_len = len * format.base_len::<T>();
+    if !T::HAS_SCRATCH {
+        return items_len;
+    }
+    items
+        .into_iter()
+        .fold(items_len, |sum, item| sum + format.scratch_len(&item))
+}
+
+/// Re-encode the `len` items whose base areas start at the cursor into the scratch space of `out`,
+/// see [`Owned::copy_encoded`]. Items without scratch space are copied as is, padding and all,
+/// rather than one by one.
+fn copy_encoded_items<T: Owned>(
+    cursor: &DecodeCursor,
+    len: usize,
+    out: &mut EncodeCursor,
+) -> DecodeResult<()> {
+    if !T::HAS_SCRATCH {
+        out.scratch_bytes(cursor.base(len * cursor.format().base_len::<T>())?);
+        return Ok(());
+    }
+
+    let mut result = Ok(());
+    out.inner_in_scratch(len * out.format().base_len::<T>(), |out| {
+        result = (0..len).try_for_each(|i| T::copy_encoded(cursor, out).map_err(|e| e.at_index(i)));
+    });
+    result
+}
+
+/// The scratch space [`copy_encoded_items`] takes in `format`.
+fn copy_items_scratch_len<T: Owned>(
+    cursor: &DecodeCursor,
+    len: usize,
+    format: WireFormat,
+) -> DecodeResult<usize> {
+    let items_len = len * format.base_len::<T>();
+    if !T::HAS_SCRATCH {
+        return Ok(items_len);
+    }
+    (0..len).try_fold(items_len, |sum, i| {
+        Ok(sum + T::copy_scratch_len(cursor, format).map_err(|e| e.at_index(i))?)
+    })
+}
+
+/// Checks that `len` items of type `T` fit in the buffer after the cursor's current offset.
+fn check_items_len<T: BaseLen>(cursor: &DecodeCursor, len: usize) -> DecodeResult<()> {
+    match len.checked_mul(cursor.format().base_len::<T>()) {
+        Some(items_len) if items_len <= cursor.remaining() => Ok(()),
+        _ => Err(DecodeError::new(
+            DecodeErrorKind::UnexpectedEnd,
+            cursor.offset(),
+        )),
+    }
+}
+
 impl<'s, 'a, T: Owned> IntoIterator for &'s ListLazy<'a, T> {
     type Item = T::Lazy<'a>;
     type IntoIter = ListLazyIter<'s, 'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let len = self.len();
-        ListLazyIter {
-            list_lazy: self,
-            len,
-            cursor: 0,
-        }
+        self.iter()
     }
 }
 
 pub struct ListLazyIter<'s, 'a, T> {
-    list_lazy: &'s ListLazy<'a, T>,
-    len: usize,
-    cursor: usize,
+    inner: ListLazyTryIter<'s, 'a, T>,
+}
+
+impl<'s, 'a, T: Owned> ListLazyIter<'s, 'a, T> {
+    /// Once an item fails to decode, the iterator is empty from both ends.
+    #[inline]
+    fn stop_on_error(&mut self, item: Option<DecodeResult<T::Lazy<'a>>>) -> Option<T::Lazy<'a>> {
+        match item? {
+            Ok(item) => Some(item),
+            Err(_) => {
+                self.inner.front = self.inner.back;
+                None
+            }
+        }
+    }
 }
 
+/// Iteration stops early if an item fails to decode, so the iterator only knows an upper bound on
+/// its length. Use [`ListLazy::try_iter`], or iterate a verified list, for an `ExactSizeIterator`.
 impl<'s, 'a, T: Owned> Iterator for ListLazyIter<'s, 'a, T> {
     type Item = T::Lazy<'a>;
 
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next();
+        self.stop_on_error(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let item = self.inner.nth(n);
+        self.stop_on_error(item)
+    }
+}
+
+impl<'s, 'a, T: Owned> DoubleEndedIterator for ListLazyIter<'s, 'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next_back();
+        self.stop_on_error(item)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let item = self.inner.nth_back(n);
+        self.stop_on_error(item)
+    }
+}
+
+impl<'s, 'a, T: Owned> core::iter::FusedIterator for ListLazyIter<'s, 'a, T> {}
+
+pub struct ListLazyTryIter<'s, 'a, T> {
+    list_lazy: &'s ListLazy<'a, T>,
+    front: usize,
+    back: usize,
+}
+
+impl<'s, 'a, T: Owned> Iterator for ListLazyTryIter<'s, 'a, T> {
+    type Item = DecodeResult<T::Lazy<'a>>;
+
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let i = self.cursor;
-        self.cursor += 1;
+        if self.front >= self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.list_lazy.get(self.front - 1))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.front = self.front.saturating_add(n).min(self.back);
+        self.next()
+    }
+}
+
+impl<'s, 'a, T: Owned> DoubleEndedIterator for ListLazyTryIter<'s, 'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.list_lazy.get(self.back))
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.back = self.back.saturating_sub(n).max(self.front);
+        self.next_back()
+    }
+}
+
+impl<'s, 'a, T: Owned> ExactSizeIterator for ListLazyTryIter<'s, 'a, T> {}
+impl<'s, 'a, T: Owned> core::iter::FusedIterator for ListLazyTryIter<'s, 'a, T> {}
 
-        if i < self.len {
-            Some(self.list_lazy.get(i).unwrap())
-        } else {
-            None
+pub struct ListLazyChunks<'a, T> {
+    rest: ListLazy<'a, T>,
+    chunk_size: usize,
+}
+
+impl<'a, T: Owned> Iterator for ListLazyChunks<'a, T> {
+    type Item = ListLazy<'a, T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
         }
+        let (chunk, rest) = self.rest.split_at(self.chunk_size.min(self.rest.len()));
+        self.rest = rest;
+        Some(chunk)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.rest.len().div_ceil(self.chunk_size);
+        (len, Some(len))
     }
 }
 
+impl<'a, T: Owned> ExactSizeIterator for ListLazyChunks<'a, T> {}
+impl<'a, T: Owned> core::iter::FusedIterator for ListLazyChunks<'a, T> {}
+
 impl<'a, T, const N: usize> core::convert::TryInto<[T::Lazy<'a>; N]> for ListLazy<'a, T>
 where
     T: Owned + Clone,
@@ -293,8 +810,8 @@ where
 
         let mut out: [MaybeUninit<T::Lazy<'_>>; N] =
             [const { MaybeUninit::<T::Lazy<'_>>::uninit() }; N];
-        for (out, item) in out.iter_mut().zip(self.iter()) {
-            out.write(item);
+        for (i, out) in out.iter_mut().enumerate() {
+            out.write(self.get(i).map_err(|_| ())?);
         }
 
         // TODO when stable
@@ -310,23 +827,24 @@ where
 
 impl<'a> From<ListLazy<'a, u8>> for &'a [u8] {
     fn from(other: ListLazy<'a, u8>) -> Self {
-        let list_scratch_offset = u32::from_le_bytes(
-            other.buffer[other.offset + 4..other.offset + 8]
-                .try_into()
-                .unwrap(),
-        ) as usize;
-        &other.buffer[list_scratch_offset..list_scratch_offset + other.len()]
+        // `ListLazy::decode` checked that the items lie within the buffer.
+        other
+            .buffer
+            .get(other.items_offset..other.items_offset + other.len)
+            .unwrap_or_default()
+    }
+}
+
+impl<'a> From<Verified<ListLazy<'a, u8>>> for &'a [u8] {
+    fn from(other: Verified<ListLazy<'a, u8>>) -> Self {
+        other.into_inner().into()
     }
 }
 
 impl<T: Owned> Copy for ListLazy<'_, T> {}
 impl<T: Owned> Clone for ListLazy<'_, T> {
     fn clone(&self) -> Self {
-        Self {
-            buffer: self.buffer,
-            offset: self.offset,
-            item_ty: core::marker::PhantomData,
-        }
+        *self
     }
 }
 impl<T> core::fmt::Debug for ListLazy<'_, T>
@@ -352,14 +870,16 @@ where
     T: Owned,
     for<'a> T::Lazy<'a>: PartialEq,
 {
-    /// Panics if decoding an item from either `ListLazy` fails.
+    /// Lists that fail to decode an item aren't equal to any list, like in the comparison with a
+    /// `Vec`.
     fn eq(&self, other: &Self) -> bool {
         if self.len() != other.len() {
             return false;
         }
-        for (item, other_item) in self.iter().zip(other.iter()) {
-            if !item.eq(&other_item) {
-                return false;
+        for (item, other_item) in self.try_iter().zip(other.try_iter()) {
+            match (item, other_item) {
+                (Ok(item), Ok(other_item)) if item == other_item => {}
+                _ => return false,
             }
         }
         true
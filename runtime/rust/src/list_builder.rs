@@ -0,0 +1,208 @@
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::vec::Vec;
+use core::{cell::Cell, marker::PhantomData};
+
+use crate::{BaseLen, Compatible, Encode, EncodeCursor, Owned, WireFormat};
+
+/// Encodes a list of `T`s one item at a time, for when the number of items isn't known up front -
+/// see [`EncodeCursor::list_builder`].
+///
+/// Each item's scratch space is written to the cursor as it's pushed, and the items' base areas
+/// are collected on the side and written after them when the list is finished. This is a valid
+/// list encoding, but unless the items have no scratch space it's laid out differently than the
+/// same list encoded from a `Vec`, so it's not [canonical](crate::is_canonical).
+pub struct ListBuilder<'c, 'a, T> {
+    state: ListBuilderState<'c, 'a>,
+    len: usize,
+    item_ty: PhantomData<fn(T)>,
+}
+
+enum ListBuilderState<'c, 'a> {
+    Encode {
+        cursor: &'c mut EncodeCursor<'a>,
+        bases: Vec<u8>,
+        /// Offsets in `bases` to make relative once it's written, see
+        /// [`EncodeCursor::encode_with_base`].
+        fixups: Vec<u32>,
+    },
+    /// Only adding up the scratch space the list needs, see [`ListBuilderGen`].
+    Measure {
+        scratch_len: usize,
+        format: WireFormat,
+    },
+}
+
+impl<'c, 'a, T: Owned> ListBuilder<'c, 'a, T> {
+    #[inline]
+    pub(crate) fn new(cursor: &'c mut EncodeCursor<'a>) -> Self {
+        Self {
+            state: ListBuilderState::Encode {
+                cursor,
+                bases: Vec::new(),
+                fixups: Vec::new(),
+            },
+            len: 0,
+            item_ty: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn measure(format: WireFormat) -> Self {
+        Self {
+            state: ListBuilderState::Measure {
+                scratch_len: 0,
+                format,
+            },
+            len: 0,
+            item_ty: PhantomData,
+        }
+    }
+
+    /// Number of items pushed so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push<U: Encode + Compatible<T>>(&mut self, item: U) {
+        debug_assert_eq!(U::BASE_LEN, T::BASE_LEN);
+        debug_assert_eq!(U::WIDE_BASE_LEN, T::WIDE_BASE_LEN);
+        self.len += 1;
+
+        match &mut self.state {
+            ListBuilderState::Encode {
+                cursor,
+                bases,
+                fixups,
+            } => {
+                assert!(
+                    self.len <= cursor.format().max_len(),
+                    "mproto list too long: length overflows u32, use WireFormat::Wide"
+                );
+                let start = bases.len();
+                bases.resize(start + cursor.format().base_len::<T>(), 0);
+                let first_fixup = fixups.len();
+                cursor.encode_with_base(&mut bases[start..], fixups, |cursor| item.encode(cursor));
+                for slot in &mut fixups[first_fixup..] {
+                    *slot += start as u32;
+                }
+            }
+            ListBuilderState::Measure {
+                scratch_len,
+                format,
+            } => {
+                *scratch_len += format.base_len::<T>() + format.scratch_len(&item);
+            }
+        }
+    }
+
+    /// Push every item of `items`.
+    #[inline]
+    pub fn extend<U: Encode + Compatible<T>>(&mut self, items: impl IntoIterator<Item = U>) {
+        for item in items {
+            self.push(item);
+        }
+    }
+
+    /// Write the list's length and the offset of its items. Same as dropping the builder.
+    #[inline]
+    pub fn finish(self) {}
+}
+
+impl<T> Drop for ListBuilder<'_, '_, T> {
+    fn drop(&mut self) {
+        // Don't write a half-built list while unwinding, which could panic again and abort.
+        #[cfg(feature = "std")]
+        if std::thread::panicking() {
+            return;
+        }
+        if let ListBuilderState::Encode {
+            cursor,
+            bases,
+            fixups,
+        } = &mut self.state
+        {
+            cursor.write_len(self.len);
+            // The bases are written to the next bit of scratch space.
+            let bases_offset = cursor.encoded_len() as u32;
+            for &slot in fixups.iter() {
+                let slot_bytes: &mut [u8; 4] =
+                    (&mut bases[slot as usize..][..4]).try_into().unwrap();
+                let target = u32::from_le_bytes(*slot_bytes);
+                *slot_bytes = target.wrapping_sub(bases_offset + slot).to_le_bytes();
+            }
+            cursor.scratch(bases.len()).copy_from_slice(bases);
+        }
+    }
+}
+
+/// A list of `T`s encoded by calling `F` with a [`ListBuilder`] to push the items to, e.g. for a
+/// list field of a generated `*Gen` struct whose items are streamed in from elsewhere.
+///
+/// When encoding with [`encode_value_extend`](crate::encode_value_extend), `F` is called once.
+/// Otherwise it's called a second time to work out the [`scratch_len`](Encode::scratch_len) of
+/// the list first, and must push the same items both times. Encoding panics if it pushes fewer.
+pub struct ListBuilderGen<T, F> {
+    f: F,
+    /// Number of items pushed when last measured, to check that encoding pushes as many.
+    measured_len: Cell<Option<usize>>,
+    item_ty: PhantomData<fn(T)>,
+}
+
+impl<T: Owned, F: Fn(&mut ListBuilder<'_, '_, T>)> ListBuilderGen<T, F> {
+    /// Note that `f` is called twice unless encoding with
+    /// [`encode_value_extend`](crate::encode_value_extend), see above.
+    #[inline]
+    pub fn new(f: F) -> Self {
+        Self {
+            f,
+            measured_len: Cell::new(None),
+            item_ty: PhantomData,
+        }
+    }
+
+    fn measure(&self, format: WireFormat) -> usize {
+        let mut list = ListBuilder::measure(format);
+        (self.f)(&mut list);
+        self.measured_len.set(Some(list.len));
+        match list.state {
+            ListBuilderState::Measure { scratch_len, .. } => scratch_len,
+            ListBuilderState::Encode { .. } => unreachable!(),
+        }
+    }
+}
+
+impl<T, F> BaseLen for ListBuilderGen<T, F> {
+    const BASE_LEN: usize = 4 + 4;
+    const WIDE_BASE_LEN: usize = 8 + 8;
+}
+
+impl<T: Owned, F: Fn(&mut ListBuilder<'_, '_, T>)> Encode for ListBuilderGen<T, F> {
+    fn scratch_len(&self) -> usize {
+        self.measure(WireFormat::Absolute)
+    }
+
+    fn wide_scratch_len(&self) -> usize {
+        self.measure(WireFormat::Wide)
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let mut list = cursor.list_builder();
+        (self.f)(&mut list);
+        if let Some(measured_len) = self.measured_len.take() {
+            assert_eq!(
+                list.len(),
+                measured_len,
+                "ListBuilderGen pushed a different number of items when encoding than when \
+                 measuring the list, but must push the same items both times"
+            );
+        }
+    }
+}
+
+impl<T: Owned, F: Fn(&mut ListBuilder<'_, '_, T>)> Compatible<Vec<T>> for ListBuilderGen<T, F> {}
@@ -0,0 +1,82 @@
+use core::marker::PhantomData;
+
+use crate::{
+    Compatible, Decode, DecodeCursor, DecodeResult, Encode, EncodeCursor, ListLazy, Owned,
+    WireFormat,
+};
+
+/// Mutable access to the items of an encoded list whose items have no scratch space, such as
+/// `[u32]`, for changing them in place without re-encoding the message.
+pub struct ListMut<'a, T> {
+    items: &'a mut [u8],
+    len: usize,
+    format: WireFormat,
+    item_ty: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: Owned> ListMut<'a, T> {
+    /// The list at the start of `buffer`.
+    #[inline]
+    pub fn new(buffer: &'a mut [u8]) -> DecodeResult<Self> {
+        Self::at_offset(buffer, 0, WireFormat::Absolute)
+    }
+
+    /// The list whose base area is at `offset` in `buffer`.
+    pub fn at_offset(
+        buffer: &'a mut [u8],
+        offset: usize,
+        format: WireFormat,
+    ) -> DecodeResult<Self> {
+        const { assert!(!T::HAS_SCRATCH, "ListMut items must not have scratch space") };
+
+        let list: ListLazy<T> =
+            Decode::decode(&DecodeCursor::at_offset(buffer, offset).with_format(format))?;
+        let (items_offset, len) = (list.items_offset(), list.len());
+        Ok(Self {
+            items: &mut buffer[items_offset..items_offset + len * T::BASE_LEN],
+            len,
+            format,
+            item_ty: PhantomData,
+        })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> DecodeResult<T> {
+        self.as_lazy().get(index).and_then(T::lazy_to_owned)
+    }
+
+    /// Overwrite the item at `index`. Panics if `index` is out of bounds.
+    pub fn set<U: Encode + Compatible<T>>(&mut self, index: usize, value: U) {
+        if index >= self.len {
+            let len = self.len;
+            panic!("index {index} out of bounds for ListMut of length {len}");
+        }
+
+        let item = &mut self.items[index * T::BASE_LEN..][..T::BASE_LEN];
+        value.encode(&mut EncodeCursor::new::<U>(item));
+    }
+
+    /// Read-only view of the list, e.g. to iterate over it.
+    #[inline]
+    pub fn as_lazy(&self) -> ListLazy<'_, T> {
+        ListLazy::from_items(self.items, self.len, self.format)
+    }
+}
+
+impl<T: Owned> core::fmt::Debug for ListMut<'_, T>
+where
+    for<'a> T::Lazy<'a>: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.as_lazy().fmt(f)
+    }
+}
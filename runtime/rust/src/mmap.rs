@@ -0,0 +1,47 @@
+//! Lazily decoding memory-mapped files.
+
+use std::{fs::File, io, path::Path};
+
+use memmap2::Mmap;
+
+use crate::{LazyBuf, Owned, StableBuf};
+
+// SAFETY: the mapping stays at the same address for as long as the `Mmap` lives. Creating one is
+// `unsafe` precisely because the file must not be modified while it's mapped, which is what
+// `StableBuf` requires.
+unsafe impl StableBuf for Mmap {}
+
+impl<T: Owned> LazyBuf<T, Mmap> {
+    /// Map the file at `path` read-only and lazily decode a `T` from it.
+    ///
+    /// Only the pages of the file that are actually accessed are read from disk, so e.g. getting a
+    /// few items of a huge list is cheap. The whole value is bounds-checked against the file's
+    /// length at the time it's mapped, like any other buffer.
+    ///
+    /// Decoding errors are returned as [`io::ErrorKind::InvalidData`] errors wrapping the
+    /// [`DecodeError`](crate::DecodeError).
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated - by this or any other process - while the
+    /// returned `LazyBuf` exists. Modifying it breaks the guarantee that a `LazyBuf`'s bytes don't
+    /// change underneath it, and accessing a page past the end of a truncated file kills the
+    /// process with `SIGBUS` on most platforms, which no bounds check can prevent. If that can't be
+    /// ruled out, use [`LazyBuf::read_file`] instead.
+    pub unsafe fn open_mmap(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: upheld by the caller.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(Self::try_new(mmap)?)
+    }
+}
+
+impl<T: Owned> LazyBuf<T, Vec<u8>> {
+    /// Read the file at `path` into memory and lazily decode a `T` from it. Slower and more memory
+    /// hungry than [`LazyBuf::open_mmap`] for big files, but safe no matter what happens to the
+    /// file afterwards.
+    pub fn read_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let buf = std::fs::read(path)?;
+        Ok(Self::try_new(buf)?)
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeResult, Encode, EncodeCursor,
-    Lazy, Owned,
+    BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeErrorKind, DecodeResult, Encode,
+    EncodeCursor, Lazy, Owned, Verified, WireFormat,
 };
 
 impl<T: Owned> Owned for Option<T> {
@@ -12,16 +12,59 @@ impl<T: Owned> Owned for Option<T> {
             None => Ok(None),
         }
     }
+
+    #[inline]
+    fn shorten_lazy<'b, 'a: 'b>(lazy: Self::Lazy<'a>) -> Self::Lazy<'b> {
+        lazy.map(T::shorten_lazy)
+    }
+
+    fn verify(cursor: &DecodeCursor<'_>) -> DecodeResult<()> {
+        if decode_tag(cursor)? {
+            T::verify(cursor)
+        } else {
+            cursor.advance(cursor.format().base_len::<T>())
+        }
+    }
+
+    fn copy_encoded(cursor: &DecodeCursor<'_>, out: &mut EncodeCursor) -> DecodeResult<()> {
+        if decode_tag(cursor)? {
+            out.base(1)[0] = 1;
+            T::copy_encoded(cursor, out)
+        } else {
+            cursor.advance(cursor.format().base_len::<T>())?;
+            None::<T>.encode(out);
+            Ok(())
+        }
+    }
+
+    fn copy_scratch_len(cursor: &DecodeCursor<'_>, format: WireFormat) -> DecodeResult<usize> {
+        if decode_tag(cursor)? {
+            T::copy_scratch_len(cursor, format)
+        } else {
+            cursor.advance(cursor.format().base_len::<T>())?;
+            Ok(0)
+        }
+    }
 }
 
 impl<'a, T: Lazy<'a>> Lazy<'a> for Option<T> {
     type Owned = Option<T::Owned>;
+    type View = Option<T::View>;
+
+    #[inline]
+    fn view(verified: Verified<Self>) -> Self::View {
+        verified
+            .into_inner()
+            .map(|some| Verified::new_unchecked(some).view())
+    }
 }
 
 impl<T, U: Compatible<T>> Compatible<Option<T>> for Option<U> {}
 
 impl<T: BaseLen> BaseLen for Option<T> {
     const BASE_LEN: usize = 1 + T::BASE_LEN;
+    const WIDE_BASE_LEN: usize = 1 + T::WIDE_BASE_LEN;
+    const HAS_SCRATCH: bool = T::HAS_SCRATCH;
 }
 
 impl<T: Encode> Encode for Option<T> {
@@ -32,6 +75,13 @@ impl<T: Encode> Encode for Option<T> {
         }
     }
 
+    fn wide_scratch_len(&self) -> usize {
+        match self {
+            Some(some) => some.wide_scratch_len(),
+            None => 0,
+        }
+    }
+
     fn encode(&self, cursor: &mut EncodeCursor) {
         match self {
             Some(some) => {
@@ -40,7 +90,8 @@ impl<T: Encode> Encode for Option<T> {
             }
             None => {
                 cursor.base(1)[0] = 0;
-                cursor.base(T::BASE_LEN);
+                let base_len = cursor.format().base_len::<T>();
+                cursor.base(base_len).fill(0);
             }
         }
     }
@@ -48,17 +99,25 @@ impl<T: Encode> Encode for Option<T> {
 
 impl<'a, T: Decode<'a>> Decode<'a> for Option<T> {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        let variant = cursor.base(1)[0];
-        if variant > 1 {
-            return Err(DecodeError);
-        }
-
-        let is_some = variant == 1;
-        if is_some {
+        if decode_tag(cursor)? {
             Ok(Some(T::decode(cursor)?))
         } else {
-            cursor.advance(T::BASE_LEN);
+            cursor.advance(cursor.format().base_len::<T>())?;
             Ok(None)
         }
     }
 }
+
+/// Returns whether the option is `Some`.
+#[inline]
+fn decode_tag(cursor: &DecodeCursor) -> DecodeResult<bool> {
+    let offset = cursor.offset();
+    match cursor.base(1)?[0] {
+        0 => Ok(false),
+        1 => Ok(true),
+        tag => Err(DecodeError::new(
+            DecodeErrorKind::InvalidOptionTag { tag },
+            offset,
+        )),
+    }
+}
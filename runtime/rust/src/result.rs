@@ -1,6 +1,6 @@
 use crate::{
-    max, BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeResult, Encode,
-    EncodeCursor, Lazy, Owned,
+    BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeErrorKind, DecodeResult, Encode,
+    EncodeCursor, Lazy, Owned, PathSegment, Verified, WireFormat, max,
 };
 
 impl<O: Owned, E: Owned> Owned for Result<O, E> {
@@ -12,14 +12,69 @@ impl<O: Owned, E: Owned> Owned for Result<O, E> {
             Err(err) => Ok(Err(E::lazy_to_owned(err)?)),
         }
     }
+
+    #[inline]
+    fn shorten_lazy<'b, 'a: 'b>(lazy: Self::Lazy<'a>) -> Self::Lazy<'b> {
+        lazy.map(O::shorten_lazy).map_err(E::shorten_lazy)
+    }
+
+    fn verify(cursor: &DecodeCursor<'_>) -> DecodeResult<()> {
+        if decode_tag(cursor)? {
+            O::verify(cursor).map_err(|e| e.push_segment(PathSegment::Name("Ok")))?;
+            cursor.advance(padding_len::<Self, O>(cursor.format()))
+        } else {
+            E::verify(cursor).map_err(|e| e.push_segment(PathSegment::Name("Err")))?;
+            cursor.advance(padding_len::<Self, E>(cursor.format()))
+        }
+    }
+
+    fn copy_encoded(cursor: &DecodeCursor<'_>, out: &mut EncodeCursor) -> DecodeResult<()> {
+        if decode_tag(cursor)? {
+            out.base(1)[0] = 0;
+            O::copy_encoded(cursor, out).map_err(|e| e.push_segment(PathSegment::Name("Ok")))?;
+            cursor.advance(padding_len::<Self, O>(cursor.format()))?;
+            out.base(padding_len::<Self, O>(out.format())).fill(0);
+        } else {
+            out.base(1)[0] = 1;
+            E::copy_encoded(cursor, out).map_err(|e| e.push_segment(PathSegment::Name("Err")))?;
+            cursor.advance(padding_len::<Self, E>(cursor.format()))?;
+            out.base(padding_len::<Self, E>(out.format())).fill(0);
+        }
+        Ok(())
+    }
+
+    fn copy_scratch_len(cursor: &DecodeCursor<'_>, format: WireFormat) -> DecodeResult<usize> {
+        if decode_tag(cursor)? {
+            let len = O::copy_scratch_len(cursor, format)
+                .map_err(|e| e.push_segment(PathSegment::Name("Ok")))?;
+            cursor.advance(padding_len::<Self, O>(cursor.format()))?;
+            Ok(len)
+        } else {
+            let len = E::copy_scratch_len(cursor, format)
+                .map_err(|e| e.push_segment(PathSegment::Name("Err")))?;
+            cursor.advance(padding_len::<Self, E>(cursor.format()))?;
+            Ok(len)
+        }
+    }
 }
 
 impl<'a, O: Lazy<'a>, E: Lazy<'a>> Lazy<'a> for Result<O, E> {
     type Owned = Result<O::Owned, E::Owned>;
+    type View = Result<O::View, E::View>;
+
+    #[inline]
+    fn view(verified: Verified<Self>) -> Self::View {
+        match verified.into_inner() {
+            Ok(ok) => Ok(Verified::new_unchecked(ok).view()),
+            Err(err) => Err(Verified::new_unchecked(err).view()),
+        }
+    }
 }
 
 impl<T: BaseLen, E: BaseLen> BaseLen for Result<T, E> {
     const BASE_LEN: usize = 1 + max(T::BASE_LEN, E::BASE_LEN);
+    const WIDE_BASE_LEN: usize = 1 + max(T::WIDE_BASE_LEN, E::WIDE_BASE_LEN);
+    const HAS_SCRATCH: bool = T::HAS_SCRATCH || E::HAS_SCRATCH;
 }
 
 impl<T: Encode, E: Encode> Encode for Result<T, E> {
@@ -30,17 +85,26 @@ impl<T: Encode, E: Encode> Encode for Result<T, E> {
         }
     }
 
+    fn wide_scratch_len(&self) -> usize {
+        match self {
+            Ok(ok) => ok.wide_scratch_len(),
+            Err(err) => err.wide_scratch_len(),
+        }
+    }
+
     fn encode(&self, cursor: &mut EncodeCursor) {
         match self {
             Ok(ok) => {
                 cursor.base(1)[0] = 0;
                 ok.encode(cursor);
-                cursor.base(Self::BASE_LEN - 1 - T::BASE_LEN);
+                let padding_len = padding_len::<Self, T>(cursor.format());
+                cursor.base(padding_len).fill(0);
             }
             Err(err) => {
                 cursor.base(1)[0] = 1;
                 err.encode(cursor);
-                cursor.base(Self::BASE_LEN - 1 - E::BASE_LEN);
+                let padding_len = padding_len::<Self, E>(cursor.format());
+                cursor.base(padding_len).fill(0);
             }
         }
     }
@@ -48,19 +112,13 @@ impl<T: Encode, E: Encode> Encode for Result<T, E> {
 
 impl<'a, T: Decode<'a>, E: Decode<'a>> Decode<'a> for Result<T, E> {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        let variant = cursor.base(1)[0];
-        if variant > 1 {
-            return Err(DecodeError);
-        }
-
-        let is_ok = variant == 0;
-        if is_ok {
-            let ok = T::decode(cursor)?;
-            cursor.advance(Self::BASE_LEN - 1 - T::BASE_LEN);
+        if decode_tag(cursor)? {
+            let ok = T::decode(cursor).map_err(|e| e.push_segment(PathSegment::Name("Ok")))?;
+            cursor.advance(padding_len::<Self, T>(cursor.format()))?;
             Ok(Ok(ok))
         } else {
-            let err = E::decode(cursor)?;
-            cursor.advance(Self::BASE_LEN - 1 - E::BASE_LEN);
+            let err = E::decode(cursor).map_err(|e| e.push_segment(PathSegment::Name("Err")))?;
+            cursor.advance(padding_len::<Self, E>(cursor.format()))?;
             Ok(Err(err))
         }
     }
@@ -74,3 +132,26 @@ where
     Err2: Encode,
 {
 }
+
+/// The padding after a `Result`'s tag and a variant of type `V`.
+#[inline]
+fn padding_len<R: BaseLen, V: BaseLen>(format: WireFormat) -> usize {
+    format.base_len::<R>() - 1 - format.base_len::<V>()
+}
+
+/// Returns whether the result is `Ok`.
+#[inline]
+fn decode_tag(cursor: &DecodeCursor) -> DecodeResult<bool> {
+    let offset = cursor.offset();
+    match cursor.base(1)?[0] {
+        0 => Ok(true),
+        1 => Ok(false),
+        tag => Err(DecodeError::new(
+            DecodeErrorKind::InvalidEnumTag {
+                tag,
+                type_name: "Result",
+            },
+            offset,
+        )),
+    }
+}
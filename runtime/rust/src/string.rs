@@ -1,7 +1,13 @@
-use crate::{BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeResult, Encode, EncodeCursor};
+#[cfg(all(not(feature = "std"), feature = "alloc"))]
+use alloc::string::String;
+
+use crate::{
+    BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeErrorKind, DecodeResult, Encode,
+    EncodeCursor,
+};
 
 #[cfg(any(feature = "std", feature = "alloc"))]
-use crate::{Lazy, Owned};
+use crate::{Lazy, Owned, Verified, WireFormat};
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl Compatible<String> for String {}
@@ -18,15 +24,42 @@ impl Owned for String {
     fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
         Ok(lazy.into())
     }
+
+    #[inline]
+    fn shorten_lazy<'b, 'a: 'b>(lazy: Self::Lazy<'a>) -> Self::Lazy<'b> {
+        lazy
+    }
+
+    fn verify(cursor: &DecodeCursor<'_>) -> DecodeResult<()> {
+        decode_str(cursor).map(|_| ())
+    }
+
+    fn copy_encoded(cursor: &DecodeCursor<'_>, out: &mut EncodeCursor) -> DecodeResult<()> {
+        let bytes = decode_str_bytes(cursor)?;
+        out.write_len(bytes.len());
+        out.scratch_bytes(bytes);
+        Ok(())
+    }
+
+    fn copy_scratch_len(cursor: &DecodeCursor<'_>, _format: WireFormat) -> DecodeResult<usize> {
+        Ok(decode_str_bytes(cursor)?.len())
+    }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<'a> Lazy<'a> for &'a str {
     type Owned = String;
+    type View = &'a str;
+
+    #[inline]
+    fn view(verified: Verified<Self>) -> Self::View {
+        verified.into_inner()
+    }
 }
 
 impl BaseLen for str {
     const BASE_LEN: usize = 4 + 4;
+    const WIDE_BASE_LEN: usize = 8 + 8;
 }
 
 impl Encode for str {
@@ -34,30 +67,42 @@ impl Encode for str {
         self.len()
     }
 
+    fn wide_scratch_len(&self) -> usize {
+        self.len()
+    }
+
     fn encode(&self, cursor: &mut EncodeCursor) {
-        cursor
-            .base(4)
-            .copy_from_slice(&(self.len() as u32).to_le_bytes());
-        let scratch_buf = cursor.scratch(self.len());
-        scratch_buf.copy_from_slice(self.as_bytes());
+        cursor.write_len(self.len());
+        cursor.scratch_bytes(self.as_bytes());
     }
 }
 
 impl<'a> Decode<'a> for &'a str {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        let len_bytes = cursor.base(4).try_into().map_err(|_| DecodeError)?;
-        let len = u32::from_le_bytes(len_bytes) as usize;
-        let scratch = cursor.scratch(len);
+        decode_str(cursor)
+    }
+}
 
-        let string = core::str::from_utf8(scratch).map_err(|_| DecodeError)?;
+fn decode_str<'a>(cursor: &DecodeCursor<'a>) -> DecodeResult<&'a str> {
+    let scratch = decode_str_bytes(cursor)?;
+    core::str::from_utf8(scratch).map_err(|e| {
+        // `scratch` is a subslice of the cursor's buffer.
+        let offset = scratch.as_ptr() as usize - cursor.buffer().as_ptr() as usize;
+        DecodeError::new(DecodeErrorKind::InvalidUtf8, offset + e.valid_up_to())
+    })
+}
 
-        Ok(string)
-    }
+/// A string's bytes, without checking that they're UTF-8.
+fn decode_str_bytes<'a>(cursor: &DecodeCursor<'a>) -> DecodeResult<&'a [u8]> {
+    let len = cursor.read_len()?;
+    cursor.check_string_len(len)?;
+    cursor.scratch(len)
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl BaseLen for String {
     const BASE_LEN: usize = 4 + 4;
+    const WIDE_BASE_LEN: usize = 8 + 8;
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
@@ -66,24 +111,21 @@ impl Encode for String {
         self.len()
     }
 
+    fn wide_scratch_len(&self) -> usize {
+        self.len()
+    }
+
     fn encode(&self, cursor: &mut EncodeCursor) {
-        cursor
-            .base(4)
-            .copy_from_slice(&(self.len() as u32).to_le_bytes());
-        let scratch_buf = cursor.scratch(self.len());
-        scratch_buf.copy_from_slice(self.as_bytes());
+        cursor.write_len(self.len());
+        cursor.scratch_bytes(self.as_bytes());
     }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl<'a> Decode<'a> for String {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        let len_bytes = cursor.base(4).try_into().map_err(|_| DecodeError)?;
-        let len = u32::from_le_bytes(len_bytes) as usize;
-        let scratch = cursor.scratch(len);
-
-        let string = core::str::from_utf8(scratch).map_err(|_| DecodeError)?;
-
-        Ok(string.into())
+        let s = decode_str(cursor)?;
+        cursor.reserve_owned(s.len())?;
+        Ok(s.into())
     }
 }
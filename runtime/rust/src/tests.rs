@@ -1,12 +1,53 @@
 use core::fmt::Debug;
 
 use crate::{
-    decode_value, encoded_len, BaseLen, Compatible, Decode, DecodeCursor, DecodeResult, Encode,
-    EncodeCursor, Owned,
+    BaseLen, Compatible, Decode, DecodeCursor, DecodeResult, Encode, EncodeCursor, Owned,
+    decode_value, encoded_len,
 };
 
 #[cfg(any(feature = "std", feature = "alloc"))]
-use crate::{encode_value_vec, BoxLazy, ListLazy};
+use crate::{BoxLazy, ListLazy, encode_value_vec};
+
+#[cfg(feature = "async")]
+mod async_io;
+#[cfg(feature = "bytes")]
+mod bytes;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod canonical;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod compression;
+#[cfg(feature = "std")]
+mod container;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod decode_error;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod decode_limits;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod dedup;
+mod encode_cursor;
+mod encode_error;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod envelope;
+#[cfg(feature = "tokio-codec")]
+mod framing;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod fuzz;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "std")]
+mod lazy_buf;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod list_builder;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod list_lazy;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod list_mut;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod verify;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod wire_format;
 
 fn encode_decode<E, D>(v: E)
 where
@@ -31,12 +72,21 @@ fn encode_decode_owned<E>(v: E)
 where
     E: Owned + Debug + PartialEq<E>,
 {
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    {
+        let buf = encode_value_vec(&v);
+        assert!(crate::is_canonical::<E>(&buf));
+
+        let mut growable = Vec::new();
+        assert_eq!(crate::encode_value_extend(&v, &mut growable), buf.len());
+        assert_eq!(growable, buf);
+    }
     encode_decode::<E, E>(v);
 }
 
 fn encode_decode_with_buf<'a, E>(buf: &'a mut [u8], v: &E)
 where
-    E: Encode + Decode<'a> + Debug + PartialEq<E> + ?Sized,
+    E: Encode + Decode<'a> + Debug + PartialEq<E>,
 {
     let mut cursor = EncodeCursor::new::<E>(buf);
     v.encode(&mut cursor);
@@ -121,6 +171,116 @@ fn test_vec_from_list_lazy() {
     assert_eq!(vec, original_vec);
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_lazy_copy_without_scratch() {
+    const { assert!(!<Option<Result<u32, i8>> as BaseLen>::HAS_SCRATCH) };
+    const { assert!(<Option<Box<u8>> as BaseLen>::HAS_SCRATCH) };
+
+    // Values without scratch space are copied verbatim, including non-zero padding.
+    let mut buf = encode_value_vec(vec![Some(1u32), None, Some(3)]);
+    buf[8 + 5 + 1] = 0xff;
+    let list_lazy: ListLazy<'_, Option<u32>> = decode_value(&buf).unwrap();
+    assert_eq!(encoded_len(list_lazy), buf.len());
+    assert_eq!(encode_value_vec(list_lazy), buf);
+
+    let buf = encode_value_vec(vec![Box::new(-5i64), Box::new(7)]);
+    let list_lazy: ListLazy<'_, Box<i64>> = decode_value(&buf).unwrap();
+    let boxed: BoxLazy<'_, i64> = list_lazy.get(1).unwrap();
+    assert_eq!(encode_value_vec(boxed), encode_value_vec(Box::new(7i64)));
+    assert_eq!(encode_value_vec(list_lazy), buf);
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_lazy_copy_with_scratch() {
+    use crate::{ListBuilder, ListBuilderGen};
+
+    type Item = Option<Box<Result<Vec<String>, u8>>>;
+    let value: Vec<Item> = (0..10)
+        .map(|i| match i % 3 {
+            0 => None,
+            1 => Some(Box::new(Err(i))),
+            _ => Some(Box::new(Ok(vec![format!("item {i}"); i as usize]))),
+        })
+        .collect();
+    let buf = encode_value_vec(&value);
+    let list_lazy: ListLazy<'_, Item> = decode_value(&buf).unwrap();
+    assert_eq!(encoded_len(list_lazy), buf.len());
+    assert_eq!(encode_value_vec(list_lazy), buf);
+
+    // Strings are copied without checking that they're UTF-8 again.
+    let mut buf = encode_value_vec(vec!["abc", "def"]);
+    let last = buf.len() - 1;
+    buf[last] = 0xff;
+    let list_lazy: ListLazy<'_, String> = decode_value(&buf).unwrap();
+    assert_eq!(encode_value_vec(list_lazy), buf);
+
+    // Values are copied field by field, so they're laid out canonically again.
+    let built = ListBuilderGen::new(|list: &mut ListBuilder<String>| {
+        list.extend(["one", "two", "three"]);
+    });
+    let buf = encode_value_vec(&built);
+    let list_lazy: ListLazy<'_, String> = decode_value(&buf).unwrap();
+    assert_eq!(
+        encode_value_vec(list_lazy),
+        encode_value_vec(vec!["one", "two", "three"])
+    );
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_primitive_lists() {
+    use crate::ListGen;
+
+    encode_decode_owned::<Vec<f32>>(vec![1.5, -0.0, f32::MAX, f32::MIN_POSITIVE]);
+    encode_decode_owned::<Vec<i128>>(vec![-1, i128::MIN, 1 << 100]);
+    encode_decode_owned::<Vec<u16>>(vec![]);
+
+    // Bulk copies give the same bytes as encoding items one at a time.
+    let values = (0..1000u64).map(|i| i * 0x0102_0304_0506).collect::<Vec<_>>();
+    let buf = encode_value_vec(&values);
+    assert_eq!(buf, encode_value_vec(ListGen(values.iter())));
+    assert_eq!(decode_value::<Vec<u64>>(&buf), Ok(values.clone()));
+
+    let list_lazy: ListLazy<'_, u64> = decode_value(&buf).unwrap();
+    assert_eq!(Vec::<u64>::try_from(list_lazy), Ok(values.clone()));
+    let mut copied = vec![0; values.len()];
+    list_lazy.copy_to_slice(&mut copied);
+    assert_eq!(copied, values);
+
+    // Truncated items are still an error.
+    assert!(decode_value::<Vec<u64>>(&buf[..buf.len() - 1]).is_err());
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn test_list_lazy_as_slice() {
+    #[repr(align(8))]
+    struct Aligned([u8; 32]);
+
+    let values = [1u32, 2, 0xdead_beef];
+    let encoded = encode_value_vec(values.as_slice());
+
+    // The items start 8 bytes into the buffer, so they're aligned if the buffer is.
+    let mut buf = Aligned([0; 32]);
+    buf.0[..encoded.len()].copy_from_slice(&encoded);
+    let list_lazy: ListLazy<'_, u32> = decode_value(&buf.0).unwrap();
+    if cfg!(target_endian = "little") {
+        assert_eq!(list_lazy.as_slice(), Some(values.as_slice()));
+    } else {
+        assert_eq!(list_lazy.as_slice(), None);
+    }
+
+    // Misaligned items can still be copied out.
+    buf.0[1..][..encoded.len()].copy_from_slice(&encoded);
+    let list_lazy: ListLazy<'_, u32> = decode_value(&buf.0[1..]).unwrap();
+    assert_eq!(list_lazy.as_slice(), None);
+    let mut copied = [0; 3];
+    list_lazy.copy_to_slice(&mut copied);
+    assert_eq!(copied, values);
+}
+
 #[test]
 fn test_result() {
     encode_decode_owned::<Result<u8, ()>>(Ok(42));
@@ -145,6 +305,7 @@ fn test_custom_struct() {
 
     impl<'a> BaseLen for CustomStruct<'a> {
         const BASE_LEN: usize = u32::BASE_LEN + str::BASE_LEN + u32::BASE_LEN;
+        const WIDE_BASE_LEN: usize = u32::WIDE_BASE_LEN + str::WIDE_BASE_LEN + u32::WIDE_BASE_LEN;
     }
 
     impl<'a> Encode for CustomStruct<'a> {
@@ -152,6 +313,10 @@ fn test_custom_struct() {
             self.some_str.scratch_len()
         }
 
+        fn wide_scratch_len(&self) -> usize {
+            self.some_str.wide_scratch_len()
+        }
+
         fn encode(&self, cursor: &mut EncodeCursor) {
             self.a.encode(cursor);
             self.some_str.encode(cursor);
@@ -178,6 +343,18 @@ fn test_custom_struct() {
     let mut buf = [0u8; SOME_BIG_BUFFER_SIZE];
     assert!(SOME_BIG_BUFFER_SIZE > encoded_len(&custom_struct));
     encode_decode_with_buf::<CustomStruct>(&mut buf, &custom_struct);
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    {
+        use crate::{WireFormat, decode_value_with_format, encode_value_vec_with_format};
+
+        let buf = encode_value_vec_with_format(&custom_struct, WireFormat::Wide);
+        assert_eq!(buf.len(), 4 + 16 + 4 + custom_struct.some_str.len());
+        assert_eq!(
+            decode_value_with_format::<CustomStruct>(&buf, WireFormat::Wide),
+            Ok(custom_struct)
+        );
+    }
 }
 
 // Tests for the Compatible trait impls
@@ -0,0 +1,201 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::io;
+
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_util::FutureExt;
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+use crate::{
+    AsyncMessageReader, AsyncMessageWriter, encode_value_vec,
+    framing::{FrameConfig, FrameError},
+    write_message,
+};
+
+/// Reads or writes at most one byte at a time, and is pending every other time it's polled.
+struct Trickle {
+    data: Vec<u8>,
+    pos: usize,
+    ready: bool,
+}
+
+impl Trickle {
+    fn new(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            pos: 0,
+            ready: false,
+        }
+    }
+
+    fn poll_turn(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.ready = !self.ready;
+        if self.ready {
+            Poll::Ready(())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+impl AsyncRead for Trickle {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        futures_util::ready!(self.poll_turn(cx));
+        let Some(&byte) = self.data.get(self.pos) else {
+            return Poll::Ready(Ok(0));
+        };
+        buf[0] = byte;
+        self.pos += 1;
+        Poll::Ready(Ok(1))
+    }
+}
+
+impl AsyncWrite for Trickle {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        futures_util::ready!(self.poll_turn(cx));
+        self.data.push(buf[0]);
+        Poll::Ready(Ok(1))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[tokio::test]
+async fn messages_over_duplex() {
+    let (client, server) = tokio::io::duplex(64);
+
+    let writer = tokio::spawn(async move {
+        let mut writer = AsyncMessageWriter::new(client.compat());
+        for i in 0..100 {
+            let names = vec![format!("name {i}"); i % 5];
+            writer.write(names).await.unwrap();
+        }
+        writer.send(&["borrowed"][..]).await.unwrap();
+        writer.close().await.unwrap();
+    });
+
+    let mut reader = AsyncMessageReader::<Vec<String>, _>::new(server.compat());
+    for i in 0..100 {
+        let message = reader.read().await.unwrap().unwrap();
+        assert_eq!(message.get().len(), i % 5);
+        if i % 5 > 0 {
+            assert_eq!(message.get().get(0), Ok(format!("name {i}").as_str()));
+        }
+    }
+    let message = reader.read().await.unwrap().unwrap();
+    assert_eq!(message.get().get(0), Ok("borrowed"));
+    assert!(reader.read().await.unwrap().is_none());
+
+    writer.await.unwrap();
+}
+
+#[test]
+fn cancellation_safety() {
+    let values: Vec<Vec<String>> = (0..20)
+        .map(|i| (0..i % 4).map(|j| format!("{i} {j}")).collect())
+        .collect();
+
+    // Drop every write and flush future after polling it once, retrying until it completes.
+    let mut writer = AsyncMessageWriter::new(Trickle::new(Vec::new())).with_max_buffered_len(16);
+    for value in &values {
+        while writer.write(value).now_or_never().is_none() {}
+    }
+    while writer.flush().now_or_never().is_none() {}
+    assert_eq!(writer.buffered_len(), 0);
+
+    let mut expected = Vec::new();
+    for value in &values {
+        write_message(&mut expected, value).unwrap();
+    }
+    let written = writer.into_inner().data;
+    assert_eq!(written, expected);
+
+    // Same for reads.
+    let mut reader = AsyncMessageReader::<Vec<String>, _>::new(Trickle::new(written));
+    for value in &values {
+        let message = loop {
+            if let Some(message) = reader.read().now_or_never() {
+                break message.unwrap().unwrap();
+            }
+        };
+        assert_eq!(message.get().iter().collect::<Vec<_>>(), *value);
+    }
+    let end = loop {
+        if let Some(end) = reader.read().now_or_never() {
+            break end.unwrap();
+        }
+    };
+    assert!(end.is_none());
+}
+
+#[test]
+fn backpressure() {
+    let mut writer = AsyncMessageWriter::new(Trickle::new(Vec::new())).with_max_buffered_len(8);
+
+    // Messages are buffered whole, even past the limit.
+    writer.write(0u64).now_or_never().unwrap().unwrap();
+    assert_eq!(writer.buffered_len(), 12);
+
+    // The next write waits for the buffer to drain below the limit first.
+    let mut polls = 0;
+    while writer.write(1u64).now_or_never().is_none() {
+        polls += 1;
+    }
+    assert!(polls > 0);
+    assert!(writer.buffered_len() < 8 + 12);
+}
+
+#[test]
+fn read_errors() {
+    // Ending in the middle of a message.
+    let mut data = Vec::new();
+    write_message(&mut data, "hello").unwrap();
+    data.truncate(data.len() - 1);
+    let mut reader = AsyncMessageReader::<String, _>::new(&data[..]);
+    let err = reader.read().now_or_never().unwrap();
+    assert!(matches!(err, Err(FrameError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof));
+
+    let mut reader = AsyncMessageReader::<String, _>::with_config(
+        &[100, 0, 0, 0][..],
+        FrameConfig {
+            max_frame_len: 16,
+            ..FrameConfig::DEFAULT
+        },
+    );
+    let err = reader.read().now_or_never().unwrap();
+    assert!(matches!(
+        err,
+        Err(FrameError::FrameTooLarge {
+            len: 100,
+            max_len: 16
+        })
+    ));
+
+    // Messages that fail to decode are skipped.
+    let mut data = Vec::new();
+    write_message(&mut data, u64::MAX).unwrap();
+    write_message(&mut data, "next").unwrap();
+    let mut reader = AsyncMessageReader::<String, _>::new(&data[..]);
+    let err = reader.read().now_or_never().unwrap();
+    assert!(matches!(err, Err(FrameError::Decode(_))));
+    let message = reader.read().now_or_never().unwrap().unwrap().unwrap();
+    assert_eq!(message.get(), "next");
+    assert_eq!(message.buf(), &encode_value_vec("next")[..]);
+}
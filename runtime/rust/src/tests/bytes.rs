@@ -0,0 +1,66 @@
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    LazyBuf, decode_value, decode_value_from_bytes, encode_to_buf, encode_value_vec, encoded_len,
+};
+
+fn blobs() -> Vec<Bytes> {
+    vec![
+        Bytes::from_static(b"first blob"),
+        Bytes::from(vec![7u8; 100]),
+    ]
+}
+
+#[test]
+fn encode_to_buf_matches_encode_value_vec() {
+    let expected = encode_value_vec(blobs());
+
+    // In place, into reserved capacity.
+    let mut buf = BytesMut::from(&b"prefix"[..]);
+    buf.reserve(encoded_len(blobs()));
+    assert_eq!(encode_to_buf(blobs(), &mut buf), expected.len());
+    assert_eq!(&buf[..6], b"prefix");
+    assert_eq!(&buf[6..], &expected[..]);
+
+    // Through a temporary buffer, as the chunk is too small.
+    let mut buf = Vec::new();
+    assert_eq!(encode_to_buf(blobs(), &mut buf), expected.len());
+    assert_eq!(buf, expected);
+
+    // `Bytes` and `Vec<u8>` encode the same way.
+    assert_eq!(
+        encode_value_vec(Bytes::from_static(b"abc")),
+        encode_value_vec(b"abc".to_vec())
+    );
+}
+
+#[test]
+fn decode_sub_slices() {
+    let buf = Bytes::from(encode_value_vec(Some(blobs())));
+    let buf_range = buf.as_ptr_range();
+
+    let decoded: Option<Vec<Bytes>> = decode_value_from_bytes(&buf).unwrap();
+    let decoded = decoded.unwrap();
+    assert_eq!(decoded, blobs());
+    for blob in &decoded {
+        assert!(buf_range.contains(&blob.as_ptr()));
+    }
+
+    // Without the source `Bytes` the contents are copied.
+    let copied: Option<Vec<Bytes>> = decode_value(&buf).unwrap();
+    for blob in copied.unwrap() {
+        assert!(!buf_range.contains(&blob.as_ptr()));
+    }
+
+    let as_vec: Option<Vec<Vec<u8>>> = decode_value_from_bytes(&buf).unwrap();
+    assert_eq!(as_vec.unwrap()[0], b"first blob");
+}
+
+#[test]
+fn lazy_buf_over_bytes() {
+    let buf = Bytes::from(encode_value_vec(blobs()));
+    let lazy_buf = LazyBuf::<Vec<Bytes>, Bytes>::new(buf.clone());
+    assert_eq!(lazy_buf.get().get(0), Ok(&b"first blob"[..]));
+    assert_eq!(lazy_buf.get().len(), 2);
+    assert_eq!(lazy_buf.into_buf(), buf);
+}
@@ -0,0 +1,95 @@
+use crate::{
+    DecodeErrorKind, canonical::canonical_limits, decode_value, decode_value_with_limits,
+    encode_value, encode_value_vec, is_canonical,
+};
+
+#[test]
+fn deterministic_padding() {
+    // Encoding into a dirty buffer must not leak its old contents into the padding.
+    let mut buf = [0xaa; 16];
+    let len = encode_value(None::<u64>, &mut buf);
+    assert_eq!(&buf[..len], &encode_value_vec(None::<u64>)[..]);
+    assert_eq!(&buf[..len], &[0; 9]);
+
+    let mut buf = [0xaa; 16];
+    let len = encode_value(Ok::<u8, u64>(3), &mut buf);
+    assert_eq!(&buf[..len], &[0, 3, 0, 0, 0, 0, 0, 0, 0]);
+    let len = encode_value(Err::<u64, u8>(4), &mut buf);
+    assert_eq!(&buf[..len], &[1, 4, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn rejects_non_canonical() {
+    let buf = encode_value_vec(None::<u32>);
+    assert!(is_canonical::<Option<u32>>(&buf));
+
+    // Non-zero padding.
+    let mut padded = buf.clone();
+    padded[2] = 1;
+    assert!(!is_canonical::<Option<u32>>(&padded));
+
+    // Trailing bytes.
+    let mut trailing = buf.clone();
+    trailing.push(0);
+    assert!(!is_canonical::<Option<u32>>(&trailing));
+
+    // Not a valid value at all.
+    assert!(!is_canonical::<Option<u32>>(&[2, 0, 0, 0, 0]));
+
+    // Non-zero padding in a list item, which a lazy list would copy as is.
+    let mut padded = encode_value_vec(vec![Some(1u32), None]);
+    assert!(is_canonical::<Vec<Option<u32>>>(&padded));
+    padded[8 + 5 + 1] = 1;
+    assert!(!is_canonical::<Vec<Option<u32>>>(&padded));
+    let mut padded = encode_value_vec(Box::new(None::<u32>));
+    padded[4 + 1] = 1;
+    assert!(!is_canonical::<Box<Option<u32>>>(&padded));
+
+    let buf = encode_value_vec(vec!["a", "b"]);
+    assert!(is_canonical::<Vec<String>>(&buf));
+
+    // Same value, but with the strings' scratch regions swapped.
+    let mut swapped = buf.clone();
+    swapped[12..16].copy_from_slice(&25u32.to_le_bytes());
+    swapped[20..24].copy_from_slice(&24u32.to_le_bytes());
+    swapped[24..26].copy_from_slice(b"ba");
+    assert_eq!(
+        crate::decode_value::<Vec<String>>(&swapped),
+        crate::decode_value::<Vec<String>>(&buf)
+    );
+    assert!(!is_canonical::<Vec<String>>(&swapped));
+
+    // Unreachable scratch bytes between the items and the strings.
+    let mut gap = buf[..24].to_vec();
+    gap.push(0);
+    gap.extend_from_slice(b"ab");
+    gap[12..16].copy_from_slice(&25u32.to_le_bytes());
+    gap[20..24].copy_from_slice(&26u32.to_le_bytes());
+    assert!(crate::decode_value::<Vec<String>>(&gap).is_ok());
+    assert!(!is_canonical::<Vec<String>>(&gap));
+}
+
+#[test]
+fn rejects_aliased_scratch() {
+    // A list of strings whose items all point at the first one's bytes.
+    let items = 100;
+    let string = "s".repeat(1000);
+    let mut buf = encode_value_vec(vec![string.as_str(); items]);
+    let string_offset = 8 + items * 8;
+    buf.truncate(string_offset + string.len());
+    for i in 0..items {
+        let offset_field = 8 + i * 8 + 4;
+        buf[offset_field..offset_field + 4].copy_from_slice(&(string_offset as u32).to_le_bytes());
+    }
+
+    // It's a valid list, which decodes to over 50 times its size.
+    let decoded = decode_value::<Vec<String>>(&buf).unwrap();
+    assert_eq!(decoded, vec![string; items]);
+    assert!(decoded.len() * decoded[0].len() > 50 * buf.len());
+
+    // The canonical check gives up before allocating all of that.
+    let err =
+        decode_value_with_limits::<Vec<String>>(&buf, canonical_limits(buf.len())).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::OwnedBytesLimitExceeded);
+    assert!(!is_canonical::<Vec<String>>(&buf));
+}
@@ -0,0 +1,180 @@
+use crate::{
+    DecodeErrorKind,
+    compression::{
+        Compression, decode_compressed, encode_value_compressed, zrle_compress, zrle_decompress,
+        zrle_decompressed_len, zrle_max_compressed_len,
+    },
+    encode_value_vec,
+};
+
+fn zrle_round_trip(input: &[u8]) -> Vec<u8> {
+    let mut compressed = vec![0; zrle_max_compressed_len(input.len())];
+    let len = zrle_compress(input, &mut compressed);
+    compressed.truncate(len);
+
+    assert_eq!(zrle_decompressed_len(&compressed), Ok(input.len()));
+    let mut decompressed = vec![0xaa; input.len()];
+    assert_eq!(
+        zrle_decompress(&compressed, &mut decompressed),
+        Ok(input.len())
+    );
+    assert_eq!(decompressed, input);
+    assert_eq!(
+        Compression::Zrle.decompress(&compressed, input.len()),
+        Ok(input.to_vec())
+    );
+    compressed
+}
+
+#[test]
+fn zrle_layout() {
+    assert_eq!(zrle_round_trip(&[]), [0]);
+    assert_eq!(zrle_round_trip(&[0; 3]), [5, 0x00, 0]);
+    assert_eq!(
+        zrle_round_trip(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7, 0, 9]),
+        [5, 0x00, 1, 0b101, 7, 9]
+    );
+    assert_eq!(
+        zrle_round_trip(&[1, 2, 3, 4, 5, 6, 7, 8, 1, 2, 3, 4, 5, 6, 7, 0, 1]),
+        [
+            7, 0xff, 1, 2, 3, 4, 5, 6, 7, 8, 1, 1, 2, 3, 4, 5, 6, 7, 0, 0x01, 1
+        ]
+    );
+}
+
+#[test]
+fn zrle_long_runs() {
+    // Runs longer than a count byte can hold are split.
+    zrle_round_trip(&[0; 8 * 1000 + 3]);
+    zrle_round_trip(&[0xff; 8 * 1000 + 5]);
+
+    let mixed = (0..10_000u32)
+        .map(|i| if i % 97 < 40 { 0 } else { (i * 31) as u8 })
+        .collect::<Vec<_>>();
+    let compressed = zrle_round_trip(&mixed);
+    assert!(compressed.len() < mixed.len());
+
+    // A lone word without zeros takes the most space.
+    assert_eq!(zrle_round_trip(&[1; 8]).len(), zrle_max_compressed_len(8));
+    let worst = (0..800)
+        .map(|i| if i % 16 < 2 { 0 } else { 1 })
+        .collect::<Vec<u8>>();
+    assert!(zrle_round_trip(&worst).len() <= zrle_max_compressed_len(worst.len()));
+}
+
+#[test]
+fn zrle_invalid() {
+    let invalid = |input: &[u8]| {
+        let err = zrle_decompressed_len(input).unwrap_err();
+        assert_eq!(err.kind(), DecodeErrorKind::InvalidCompressedData);
+        assert!(zrle_decompress(input, &mut [0; 64]).is_err());
+    };
+
+    invalid(&[]);
+    // Padding must be less than a word, and there must be a word to pad.
+    invalid(&[8, 0x00, 0]);
+    invalid(&[1]);
+    // Truncated words, counts and raw words.
+    invalid(&[0, 0b11, 1]);
+    invalid(&[0, 0x00]);
+    invalid(&[0, 0xff, 1, 2, 3, 4, 5, 6, 7, 8]);
+    invalid(&[0, 0xff, 1, 2, 3, 4, 5, 6, 7, 8, 1, 0, 0, 0]);
+}
+
+#[test]
+#[should_panic(expected = "buffer too small to decompress into")]
+fn zrle_decompress_too_small() {
+    zrle_decompress(&[0, 0b1, 1, 0b1, 1], &mut [0; 9]).unwrap();
+}
+
+#[test]
+fn compressed_values() {
+    let value = (0..100u64)
+        .map(|i| (i % 3 == 0).then(|| vec![i, i << 40]))
+        .collect::<Vec<_>>();
+    let encoded = encode_value_vec(&value);
+
+    let compressed = encode_value_compressed(&value, Compression::Zrle);
+    assert!(compressed.len() < encoded.len() / 2);
+    let decoded =
+        decode_compressed::<Vec<Option<Vec<u64>>>>(&compressed, Compression::Zrle, encoded.len())
+            .unwrap();
+    assert_eq!(decoded.buf(), &encoded[..]);
+    assert_eq!(decoded.get().len(), 100);
+
+    let err =
+        decode_compressed::<Vec<Option<Vec<u64>>>>(&compressed[1..], Compression::Zrle, usize::MAX)
+            .map(|_| ())
+            .unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::InvalidCompressedData);
+
+    let err = decode_compressed::<Vec<Option<Vec<u64>>>>(
+        &compressed,
+        Compression::Zrle,
+        encoded.len() - 1,
+    )
+    .map(|_| ())
+    .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::DecompressedLenLimitExceeded { len: encoded.len() }
+    );
+}
+
+#[test]
+fn zrle_decompression_bomb() {
+    // Runs of 256 zero words, 2 bytes each, expand 1024 times.
+    let mut bomb = vec![0];
+    for _ in 0..4096 {
+        bomb.extend([0x00, 0xff]);
+    }
+    let len = 4096 * 256 * 8;
+    assert_eq!(zrle_decompressed_len(&bomb), Ok(len));
+
+    let err = Compression::Zrle.decompress(&bomb, 1 << 20).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::DecompressedLenLimitExceeded { len }
+    );
+    assert_eq!(
+        err.to_string(),
+        format!("decompressed length {len} exceeds limit at offset 0")
+    );
+    assert_eq!(
+        Compression::Zrle
+            .decompress(&bomb, len)
+            .map(|out| out.len()),
+        Ok(len)
+    );
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn lz4() {
+    let value = vec![String::from("repeated"); 100].join(" ");
+    let encoded = encode_value_vec(&value);
+
+    let compressed = encode_value_compressed(&value, Compression::Lz4);
+    assert!(compressed.len() < encoded.len() / 4);
+    let decoded = decode_compressed::<String>(&compressed, Compression::Lz4, usize::MAX).unwrap();
+    assert_eq!(decoded.buf(), &encoded[..]);
+    let err = Compression::Lz4
+        .decompress(&compressed, encoded.len() - 1)
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::DecompressedLenLimitExceeded { len: encoded.len() }
+    );
+
+    // A decompressed length too large to be real isn't allocated.
+    let mut bogus = compressed.clone();
+    bogus[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    let err = Compression::Lz4.decompress(&bogus, usize::MAX).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::InvalidCompressedData);
+    assert!(
+        Compression::Lz4
+            .decompress(&compressed[..compressed.len() - 1], usize::MAX)
+            .is_err()
+    );
+    assert!(Compression::Lz4.decompress(&[1, 0], usize::MAX).is_err());
+}
@@ -0,0 +1,266 @@
+use std::{
+    io::{Cursor, Seek, SeekFrom, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    Fingerprint,
+    container::{
+        CONTAINER_HEADER_LEN, ContainerConfig, ContainerError, ContainerReader, ContainerWriter,
+        Recovery,
+    },
+};
+
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("mproto-{}-{name}", std::process::id())))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+const CONFIG: ContainerConfig = ContainerConfig {
+    block_records: 10,
+    ..ContainerConfig::DEFAULT
+};
+
+fn record(i: u64) -> Vec<String> {
+    (0..i % 5).map(|j| format!("record {i}.{j}")).collect()
+}
+
+fn write_container(records: u64, config: ContainerConfig) -> Vec<u8> {
+    let mut writer = ContainerWriter::<Vec<String>, _>::new(Vec::new(), config).unwrap();
+    for i in 0..records {
+        assert_eq!(writer.push(record(i)).unwrap(), i);
+    }
+    assert_eq!(writer.len(), records);
+    writer.finish().unwrap()
+}
+
+fn read_all(buf: &[u8]) -> Vec<Result<Vec<String>, ContainerError>> {
+    ContainerReader::<Vec<String>, _>::new(buf)
+        .unwrap()
+        .map(|record| record.map(|record| record.get().iter().map(String::from).collect()))
+        .collect()
+}
+
+#[test]
+fn write_and_read() {
+    for config in [
+        CONFIG,
+        ContainerConfig::DEFAULT,
+        ContainerConfig {
+            max_block_len: 100,
+            index: false,
+            ..CONFIG
+        },
+    ] {
+        let buf = write_container(95, config);
+        let records = read_all(&buf);
+        assert_eq!(records.len(), 95);
+        for (i, read) in records.into_iter().enumerate() {
+            assert_eq!(read.unwrap(), record(i as u64));
+        }
+
+        let mut reader = ContainerReader::<Vec<String>, _>::new(&buf[..]).unwrap();
+        let mut count = 0;
+        while let Some(record) = reader.next_record().unwrap() {
+            assert_eq!(record.len(), count % 5);
+            count += 1;
+        }
+        assert_eq!(count, 95);
+    }
+
+    // An empty container is only a header, and the index when there is one.
+    let empty = write_container(0, CONFIG);
+    assert!(read_all(&empty).is_empty());
+    let empty = write_container(
+        0,
+        ContainerConfig {
+            index: false,
+            ..CONFIG
+        },
+    );
+    assert_eq!(empty.len(), CONTAINER_HEADER_LEN);
+    assert!(read_all(&empty).is_empty());
+}
+
+#[test]
+fn random_access() {
+    let buf = write_container(95, CONFIG);
+    let mut reader = ContainerReader::<Vec<String>, _>::new(Cursor::new(&buf)).unwrap();
+    assert_eq!(reader.record_count().unwrap(), 95);
+    for i in [94, 0, 37, 10, 9] {
+        assert_eq!(reader.get(i).unwrap().get().len(), i as usize % 5);
+        assert_eq!(
+            reader.get(i).unwrap().get().get(0).ok(),
+            record(i).first().map(|s| &s[..])
+        );
+    }
+    assert!(matches!(
+        reader.get(95),
+        Err(ContainerError::IndexOutOfBounds { index: 95, len: 95 })
+    ));
+
+    // Random access leaves streaming where it was.
+    reader.next_record().unwrap().unwrap();
+    reader.get(50).unwrap();
+    assert_eq!(reader.next_record().unwrap().unwrap().len(), 1);
+
+    let unindexed = write_container(
+        95,
+        ContainerConfig {
+            index: false,
+            ..CONFIG
+        },
+    );
+    let mut reader = ContainerReader::<Vec<String>, _>::new(Cursor::new(&unindexed)).unwrap();
+    assert!(matches!(reader.get(0), Err(ContainerError::MissingIndex)));
+    assert_eq!(reader.count(), 95);
+}
+
+#[test]
+fn type_mismatch() {
+    let buf = write_container(5, CONFIG);
+    match ContainerReader::<Vec<Vec<u8>>, _>::new(&buf[..]) {
+        Err(ContainerError::TypeMismatch { expected, found }) => {
+            assert_eq!(expected, Vec::<Vec<u8>>::FINGERPRINT);
+            assert_eq!(found, Vec::<String>::FINGERPRINT);
+        }
+        _ => panic!("expected a type mismatch"),
+    }
+
+    assert!(matches!(
+        ContainerReader::<Vec<String>, _>::new(&buf[..10]),
+        Err(ContainerError::InvalidHeader)
+    ));
+    let mut corrupted = buf.clone();
+    corrupted[3] ^= 1;
+    assert!(matches!(
+        ContainerReader::<Vec<String>, _>::new(&corrupted[..]),
+        Err(ContainerError::InvalidHeader)
+    ));
+}
+
+#[test]
+fn corrupted_block() {
+    let unindexed = ContainerConfig {
+        index: false,
+        ..CONFIG
+    };
+    let buf = write_container(30, unindexed);
+    let first_block_end = write_container(10, unindexed).len();
+
+    // Corrupting a record in the second block skips just that block.
+    let mut corrupted = buf.clone();
+    corrupted[first_block_end + 4] ^= 0x40;
+    let records = read_all(&corrupted);
+    assert_eq!(records.len(), 21);
+    assert!(records[..10].iter().all(Result::is_ok));
+    match records[10] {
+        Err(ContainerError::ChecksumMismatch { offset }) => {
+            assert_eq!(offset, first_block_end as u64)
+        }
+        ref other => panic!("expected a checksum mismatch, got {other:?}"),
+    }
+    assert_eq!(records[11].as_ref().unwrap(), &record(20));
+
+    // A record length that's a tag can't be skipped over.
+    let mut corrupted = buf.clone();
+    corrupted[first_block_end..first_block_end + 4].copy_from_slice(&[0xf0, 0xff, 0xff, 0xff]);
+    let records = read_all(&corrupted);
+    assert_eq!(records.len(), 11);
+    assert!(matches!(
+        records[10],
+        Err(ContainerError::InvalidRecord { .. })
+    ));
+}
+
+#[test]
+fn truncated_tail() {
+    let unindexed = ContainerConfig {
+        index: false,
+        ..CONFIG
+    };
+    let buf = write_container(30, unindexed);
+    let second_block_end = write_container(20, unindexed).len();
+
+    for len in [buf.len() - 1, buf.len() - 20, second_block_end + 3] {
+        let records = read_all(&buf[..len]);
+        assert_eq!(records.len(), 21);
+        assert!(records[..20].iter().all(Result::is_ok));
+        match records[20] {
+            Err(ContainerError::Truncated { valid_len }) => {
+                assert_eq!(valid_len, second_block_end as u64)
+            }
+            ref other => panic!("expected truncation, got {other:?}"),
+        }
+    }
+
+    // Whole blocks read the same as a complete container.
+    assert_eq!(read_all(&buf[..second_block_end]).len(), 20);
+}
+
+#[test]
+fn append_after_crash() {
+    let file = TempFile::new("container");
+
+    let mut writer = ContainerWriter::<Vec<String>, _>::create(&file.0, CONFIG).unwrap();
+    for i in 0..25 {
+        writer.push(record(i)).unwrap();
+    }
+    writer.flush().unwrap();
+    let complete_len = std::fs::metadata(&file.0).unwrap().len();
+
+    // A crash partway through writing a block leaves part of it behind.
+    let mut partial = 100u32.to_le_bytes().to_vec();
+    partial.extend_from_slice(&[0xaa; 26]);
+    let mut file_handle = writer.finish().unwrap();
+    file_handle.set_len(complete_len).unwrap();
+    file_handle.seek(SeekFrom::Start(complete_len)).unwrap();
+    file_handle.write_all(&partial).unwrap();
+    drop(file_handle);
+
+    let (mut writer, recovery) =
+        ContainerWriter::<Vec<String>, _>::open_append(&file.0, CONFIG).unwrap();
+    assert_eq!(
+        recovery,
+        Recovery {
+            records: 25,
+            truncated_bytes: 30
+        }
+    );
+    for i in 25..40 {
+        assert_eq!(writer.push(record(i)).unwrap(), i);
+    }
+    writer.finish().unwrap();
+
+    let mut reader = ContainerReader::<Vec<String>, _>::open(&file.0).unwrap();
+    assert_eq!(reader.record_count().unwrap(), 40);
+    assert_eq!(reader.get(33).unwrap().get().len(), 3);
+    for (i, read) in reader.enumerate() {
+        let read = read.unwrap();
+        assert_eq!(read.get().iter().collect::<Vec<_>>(), record(i as u64));
+    }
+
+    // Appending to a finished container replaces its index.
+    let (writer, recovery) =
+        ContainerWriter::<Vec<String>, _>::open_append(&file.0, CONFIG).unwrap();
+    assert_eq!(
+        recovery,
+        Recovery {
+            records: 40,
+            truncated_bytes: 0
+        }
+    );
+    writer.finish().unwrap();
+    let mut reader = ContainerReader::<Vec<String>, _>::open(&file.0).unwrap();
+    assert_eq!(reader.record_count().unwrap(), 40);
+    assert_eq!(reader.count(), 40);
+}
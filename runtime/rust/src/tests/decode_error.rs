@@ -0,0 +1,101 @@
+use crate::{
+    DecodeError, DecodeErrorKind, ListLazy, MAX_ERROR_PATH_LEN, PathSegment, decode_value,
+    encode_value_vec,
+};
+
+#[test]
+fn error_kinds() {
+    let buf = encode_value_vec(7u32);
+    let err = decode_value::<u64>(&buf).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::UnexpectedEnd);
+    assert_eq!(err.offset(), 0);
+
+    let err = decode_value::<bool>(&[2]).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::InvalidBool { value: 2 });
+
+    let err = decode_value::<Option<u8>>(&[5, 0]).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::InvalidOptionTag { tag: 5 });
+
+    let err = decode_value::<Result<u8, u8>>(&[3, 0]).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::InvalidEnumTag {
+            tag: 3,
+            type_name: "Result"
+        }
+    );
+
+    let mut buf = encode_value_vec("hello");
+    buf[10] = 0xff;
+    let err = decode_value::<&str>(&buf).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::InvalidUtf8);
+    assert_eq!(err.offset(), 10);
+
+    let mut buf = encode_value_vec("hello");
+    buf[4..8].copy_from_slice(&100u32.to_le_bytes());
+    let err = decode_value::<String>(&buf).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::OffsetOutOfBounds { target: 100 }
+    );
+    assert_eq!(err.offset(), 4);
+
+    let buf = encode_value_vec(vec![1u8, 2]);
+    let list = decode_value::<ListLazy<u8>>(&buf).unwrap();
+    let err = list.get(2).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::IndexOutOfBounds { index: 2, len: 2 }
+    );
+}
+
+#[test]
+fn error_path() {
+    let mut buf = encode_value_vec(Ok::<_, u8>(vec![Some(1u8), Some(2), None, Some(4)]));
+    // Corrupt the tag of the fourth item.
+    let item_offset = 1 + 8 + 3 * 2;
+    buf[item_offset] = 7;
+
+    let err = decode_value::<Result<Vec<Option<u8>>, u8>>(&buf).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::InvalidOptionTag { tag: 7 });
+    assert_eq!(err.offset(), item_offset);
+    assert_eq!(
+        err.path().collect::<Vec<_>>(),
+        [PathSegment::Name("Ok"), PathSegment::Index(3)]
+    );
+
+    // What a generated struct decoder does with the error.
+    let err = err.in_field("MyResponse", "response");
+    assert_eq!(err.type_name(), Some("MyResponse"));
+    assert_eq!(
+        err.to_string(),
+        format!("MyResponse.response.Ok[3]: invalid option tag 7 at offset {item_offset}"),
+    );
+}
+
+#[test]
+fn error_path_truncated() {
+    let mut err = DecodeError::new(DecodeErrorKind::UnexpectedEnd, 3);
+    assert_eq!(err.to_string(), "unexpected end of buffer at offset 3");
+
+    for i in 0..MAX_ERROR_PATH_LEN + 2 {
+        err = err.at_index(i);
+    }
+    err = err.in_field("Outer", "inner");
+    assert!(err.is_path_truncated());
+    assert_eq!(err.path().count(), MAX_ERROR_PATH_LEN);
+    assert_eq!(
+        err.to_string(),
+        "Outer.\u{2026}[7][6][5][4][3][2][1][0]: unexpected end of buffer at offset 3",
+    );
+}
+
+#[test]
+fn error_path_wide_index() {
+    // Wide lists can be indexed past u32::MAX.
+    let err = DecodeError::new(DecodeErrorKind::UnexpectedEnd, 0).at_index(usize::MAX);
+    assert_eq!(
+        err.path().collect::<Vec<_>>(),
+        [PathSegment::Index(usize::MAX)]
+    );
+}
@@ -0,0 +1,122 @@
+use crate::{
+    BoxLazy, DecodeErrorKind, DecodeLimits, ListLazy, decode_value, decode_value_with_limits,
+    encode_value_vec,
+};
+
+#[test]
+fn depth_limit() {
+    let buf = encode_value_vec(Box::new(Box::new(7u8)));
+    let limits = DecodeLimits {
+        max_depth: 1,
+        ..DecodeLimits::default()
+    };
+    let err = decode_value_with_limits::<Box<Box<u8>>>(&buf, limits).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::DepthLimitExceeded);
+
+    let limits = DecodeLimits {
+        max_depth: 2,
+        ..DecodeLimits::default()
+    };
+    assert_eq!(
+        decode_value_with_limits::<Box<Box<u8>>>(&buf, limits),
+        Ok(Box::new(Box::new(7)))
+    );
+}
+
+#[test]
+fn length_limits() {
+    let limits = DecodeLimits {
+        max_list_len: 2,
+        max_string_len: 4,
+        ..DecodeLimits::default()
+    };
+
+    let buf = encode_value_vec(vec![1u16, 2, 3]);
+    let err = decode_value_with_limits::<Vec<u16>>(&buf, limits).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::ListLenLimitExceeded { len: 3 });
+    let err = decode_value_with_limits::<ListLazy<u16>>(&buf, limits).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::ListLenLimitExceeded { len: 3 });
+
+    let buf = encode_value_vec("hello");
+    let err = decode_value_with_limits::<String>(&buf, limits).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::StringLenLimitExceeded { len: 5 }
+    );
+    assert_eq!(
+        decode_value_with_limits::<&str>(&encode_value_vec("hell"), limits),
+        Ok("hell")
+    );
+
+    // Zero-sized items take no space in the buffer, so only the length limit bounds these.
+    let mut buf = [0u8; 8];
+    buf[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    buf[4..].copy_from_slice(&8u32.to_le_bytes());
+    let err = decode_value_with_limits::<Vec<()>>(&buf, limits).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::ListLenLimitExceeded {
+            len: u32::MAX as usize
+        }
+    );
+}
+
+#[test]
+fn owned_bytes_limit() {
+    let value = vec![String::from("abcd"), String::from("efgh")];
+    let buf = encode_value_vec(&value);
+    let needed = 2 * core::mem::size_of::<String>() + 8;
+
+    let limits = DecodeLimits {
+        max_owned_bytes: needed - 1,
+        ..DecodeLimits::default()
+    };
+    let err = decode_value_with_limits::<Vec<String>>(&buf, limits).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::OwnedBytesLimitExceeded);
+    assert_eq!(err.path().collect::<Vec<_>>().len(), 1);
+
+    let limits = DecodeLimits {
+        max_owned_bytes: needed,
+        ..DecodeLimits::default()
+    };
+    assert_eq!(
+        decode_value_with_limits::<Vec<String>>(&buf, limits),
+        Ok(value.clone())
+    );
+
+    // Lazy decoding doesn't allocate.
+    let limits = DecodeLimits {
+        max_owned_bytes: 0,
+        ..DecodeLimits::default()
+    };
+    assert!(decode_value_with_limits::<ListLazy<String>>(&buf, limits).is_ok());
+    assert_eq!(decode_value::<Vec<String>>(&buf), Ok(value));
+}
+
+#[test]
+fn lazy_accessors_keep_limits() {
+    let limits = DecodeLimits {
+        max_depth: 2,
+        max_string_len: 4,
+        ..DecodeLimits::default()
+    };
+
+    // The inner list only gets decoded by `get`, and is still limited.
+    let buf = encode_value_vec(vec![vec!["abc", "defgh"]]);
+    let list = decode_value_with_limits::<ListLazy<Vec<String>>>(&buf, limits).unwrap();
+    let inner = list.get(0).unwrap();
+    assert_eq!(inner.get(0).unwrap(), "abc");
+    let err = inner.get(1).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::StringLenLimitExceeded { len: 5 }
+    );
+
+    // As is the nesting depth, counting from the root.
+    let buf = encode_value_vec(Box::new(Box::new(Box::new(7u8))));
+    let boxed = decode_value_with_limits::<BoxLazy<Box<Box<u8>>>>(&buf, limits).unwrap();
+    let inner = boxed.get().unwrap();
+    let err = inner.get().unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::DepthLimitExceeded);
+    assert!(decode_value::<BoxLazy<Box<Box<u8>>>>(&buf).is_ok());
+}
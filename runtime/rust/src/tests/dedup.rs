@@ -0,0 +1,89 @@
+use crate::{
+    DedupReport, EncodeCursor, ListBuilder, ListBuilderGen, ListLazy, WireFormat, decode_value,
+    decode_value_with_format, encode_value_vec, encode_value_vec_dedup, encoded_len, is_canonical,
+    verify_value,
+};
+
+#[test]
+fn dedup_strings() {
+    let tags = ["tag", "key", "tag", "tag", "", "key", ""]
+        .map(String::from)
+        .to_vec();
+    let (buf, report) = encode_value_vec_dedup(&tags);
+
+    assert_eq!(
+        report,
+        DedupReport {
+            unique_payloads: 2,
+            duplicate_payloads: 3,
+            bytes_saved: 9,
+        }
+    );
+    assert_eq!(buf.len(), encoded_len(&tags) - 9);
+    verify_value::<Vec<String>>(&buf).unwrap();
+    assert_eq!(decode_value::<Vec<String>>(&buf), Ok(tags.clone()));
+    assert!(!is_canonical::<Vec<String>>(&buf));
+
+    // Payloads of the same length only match if their bytes do.
+    let (_, report) = encode_value_vec_dedup(vec!["ab", "ba"]);
+    assert_eq!(report.duplicate_payloads, 0);
+}
+
+#[test]
+fn dedup_primitive_lists() {
+    let value = (
+        vec![vec![1u8, 2, 3], vec![1, 2, 3], vec![]],
+        vec![vec![7u32, 8], vec![7, 8]],
+    );
+    let value = vec![Ok::<_, Vec<Vec<u32>>>(value.0), Err(value.1)];
+    let (buf, report) = encode_value_vec_dedup(&value);
+
+    assert_eq!(report.duplicate_payloads, 2);
+    assert_eq!(report.bytes_saved, 3 + 8);
+    assert_eq!(
+        decode_value::<Vec<Result<Vec<Vec<u8>>, Vec<Vec<u32>>>>>(&buf),
+        Ok(value)
+    );
+
+    // Lazy lists of items without scratch space are deduplicated when they're re-encoded, too.
+    let buf = encode_value_vec(vec![vec![5u16; 4], vec![5u16; 4]]);
+    let lazy: ListLazy<Vec<u16>> = decode_value(&buf).unwrap();
+    let lazy = [lazy.get(0).unwrap(), lazy.get(1).unwrap()];
+    let (buf, report) = encode_value_vec_dedup(lazy.as_slice());
+    assert_eq!(report.bytes_saved, 8);
+    assert_eq!(
+        decode_value::<Vec<Vec<u16>>>(&buf),
+        Ok(vec![vec![5; 4], vec![5; 4]])
+    );
+}
+
+#[test]
+fn dedup_relative_list_builder() {
+    let list = ListBuilderGen::new(|list: &mut ListBuilder<Vec<String>>| {
+        for _ in 0..3 {
+            list.push(vec!["same".to_string(), "other".to_string()]);
+        }
+    });
+
+    let mut buf = Vec::new();
+    let mut cursor = EncodeCursor::growable::<Vec<Vec<String>>>(&mut buf)
+        .with_format(WireFormat::Relative)
+        .with_dedup();
+    crate::Encode::encode(&list, &mut cursor);
+    let report = cursor.dedup_report().unwrap();
+    assert_eq!(report.duplicate_payloads, 4);
+    assert_eq!(report.bytes_saved, 2 * (4 + 5));
+
+    let decoded: Vec<Vec<String>> = decode_value_with_format(&buf, WireFormat::Relative).unwrap();
+    assert_eq!(
+        decoded,
+        vec![vec!["same".to_string(), "other".to_string()]; 3]
+    );
+}
+
+#[test]
+fn dedup_disabled() {
+    let mut buf = vec![0; 16];
+    let cursor = EncodeCursor::new::<String>(&mut buf);
+    assert_eq!(cursor.dedup_report(), None);
+}
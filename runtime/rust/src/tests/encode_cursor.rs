@@ -0,0 +1,60 @@
+use crate::{ListOnce, decode_value, encode_value_extend, encode_value_vec};
+
+#[test]
+fn growable_appends() {
+    let first = vec![Some("one".to_string()), None, Some("three".to_string())];
+    let second: Result<Vec<Box<u16>>, String> = Ok(vec![Box::new(1), Box::new(2)]);
+
+    let mut buf = Vec::new();
+    let first_len = encode_value_extend(&first, &mut buf);
+    let second_len = encode_value_extend(&second, &mut buf);
+    assert_eq!(buf.len(), first_len + second_len);
+
+    // Offsets are relative to the start of each value.
+    assert_eq!(&buf[..first_len], &encode_value_vec(&first)[..]);
+    assert_eq!(&buf[first_len..], &encode_value_vec(&second)[..]);
+    let decoded: Result<Vec<Box<u16>>, String> = decode_value(&buf[first_len..]).unwrap();
+    assert_eq!(decoded, second);
+}
+
+#[test]
+fn growable_zeroes_reused_capacity() {
+    let mut buf = vec![0xffu8; 256];
+    buf.clear();
+
+    let value: Vec<Option<String>> = vec![None, Some("some".into()), None];
+    encode_value_extend(&value, &mut buf);
+    assert_eq!(buf, encode_value_vec(&value));
+}
+
+#[test]
+fn growable_large_value() {
+    // Grows many times over.
+    let value: Vec<String> = (0..10_000).map(|i| i.to_string()).collect();
+    let mut buf = Vec::new();
+    encode_value_extend(&value, &mut buf);
+    assert_eq!(buf, encode_value_vec(&value));
+}
+
+#[test]
+fn list_once() {
+    let value: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+    // `Drain` isn't `Clone`, so can only be encoded in a single pass.
+    let mut items = value.clone();
+    let mut buf = Vec::new();
+    encode_value_extend(ListOnce::new(items.drain(..)), &mut buf);
+    assert_eq!(buf, encode_value_vec(&value));
+
+    // Without scratch space in the items the list's scratch_len is known up front.
+    let ints = ListOnce::new((0..10u32).map(|i| i * 3));
+    assert_eq!(
+        encode_value_vec(&ints),
+        encode_value_vec((0..10u32).map(|i| i * 3).collect::<Vec<_>>())
+    );
+}
+
+#[test]
+#[should_panic = "encode it with encode_value_extend"]
+fn list_once_two_pass() {
+    encode_value_vec(ListOnce::new(vec!["a".to_string()].into_iter()));
+}
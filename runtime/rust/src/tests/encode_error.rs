@@ -0,0 +1,49 @@
+use crate::{Encode, EncodeCursor, EncodeError, encoded_len, try_encode_value};
+
+#[test]
+fn try_encode_into_small_buffer() {
+    let value = Ok::<_, u32>("a string in scratch");
+    let needed = encoded_len(value);
+
+    let mut buf = [0xaau8; 64];
+    assert_eq!(
+        try_encode_value(value, &mut buf[..needed - 1]),
+        Err(EncodeError::BufferTooSmall {
+            needed,
+            available: needed - 1,
+        })
+    );
+    // Nothing was written.
+    assert!(buf.iter().all(|b| *b == 0xaa));
+
+    assert_eq!(
+        try_encode_value(value, &mut buf[..2]),
+        Err(EncodeError::BufferTooSmall {
+            needed,
+            available: 2,
+        })
+    );
+
+    assert_eq!(try_encode_value(value, &mut buf), Ok(needed));
+    assert!(buf[needed..].iter().all(|b| *b == 0xaa));
+}
+
+#[test]
+fn try_new_cursor() {
+    let mut buf = [0u8; 16];
+    assert_eq!(
+        EncodeCursor::try_new::<u64>(&mut buf[..4], 8).err(),
+        Some(EncodeError::BufferTooSmall {
+            needed: 8,
+            available: 4,
+        })
+    );
+    assert_eq!(
+        EncodeCursor::try_new::<u64>(&mut buf, u32::MAX as usize + 1).err(),
+        Some(EncodeError::MessageTooLarge)
+    );
+
+    let mut cursor = EncodeCursor::try_new::<u64>(&mut buf, 8).unwrap();
+    0x0102030405060708u64.encode(&mut cursor);
+    assert_eq!(cursor.encoded_len(), 8);
+}
@@ -0,0 +1,242 @@
+use crate::{
+    DecodeErrorKind, EncodeError, Fingerprint, encode_value_vec,
+    envelope::{
+        Checksum, ENVELOPE_FINGERPRINT_LEN, ENVELOPE_HEADER_LEN, EnvelopeError, EnvelopeHeader,
+        open, open_bytes, open_typed, seal, seal_typed, seal_typed_vec, seal_vec,
+    },
+};
+
+fn sample() -> Vec<String> {
+    (0..20).map(|i| format!("item {i}")).collect()
+}
+
+#[test]
+fn crc32c() {
+    assert_eq!(Checksum::Crc32c.compute(b""), 0);
+    assert_eq!(Checksum::Crc32c.compute(b"123456789"), 0xe306_9283);
+
+    // The lookup tables agree with computing the CRC a bit at a time, for every length around
+    // the eight bytes taken at once.
+    fn bitwise(bytes: &[u8]) -> u64 {
+        let mut crc = !0u32;
+        for &byte in bytes {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0x82f6_3b78
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc as u64
+    }
+    let bytes: Vec<u8> = (0..100u32).map(|i| (i * 37 + 11) as u8).collect();
+    for len in 0..bytes.len() {
+        assert_eq!(
+            Checksum::Crc32c.compute(&bytes[..len]),
+            bitwise(&bytes[..len])
+        );
+    }
+}
+
+#[test]
+fn xxhash64() {
+    assert_eq!(Checksum::XxHash64.compute(b""), 0xef46_db37_51d8_e999);
+    assert_eq!(Checksum::XxHash64.compute(b"abc"), 0x44bc_2cf5_ad77_0999);
+    // Long enough to go through the 32-byte stripes.
+    assert_eq!(
+        Checksum::XxHash64.compute(b"Nobody inspects the spammish repetition"),
+        0xfbce_a83c_8a37_8bf1
+    );
+}
+
+#[test]
+fn seal_and_open() {
+    for checksum in [Checksum::Crc32c, Checksum::XxHash64] {
+        let envelope = seal_vec(sample(), checksum).unwrap();
+        assert_eq!(
+            &envelope[ENVELOPE_HEADER_LEN..],
+            &encode_value_vec(sample())[..]
+        );
+
+        let header = EnvelopeHeader::read(&envelope).unwrap();
+        assert_eq!(header.checksum, checksum);
+        assert_eq!(header.envelope_len(), envelope.len());
+        assert_eq!(header.fingerprint, None);
+        let mut header_bytes = [0; ENVELOPE_HEADER_LEN];
+        assert_eq!(header.write(&mut header_bytes), ENVELOPE_HEADER_LEN);
+        assert_eq!(header_bytes, envelope[..ENVELOPE_HEADER_LEN]);
+
+        let list = open::<Vec<String>>(&envelope).unwrap();
+        assert_eq!(list.iter().collect::<Vec<_>>(), sample());
+    }
+
+    // Sealing into a buffer leaves the rest of it alone.
+    let mut buf = [0xaa; 64];
+    let len = seal(3u64, Checksum::Crc32c, &mut buf).unwrap();
+    assert_eq!(len, ENVELOPE_HEADER_LEN + 8);
+    assert_eq!(open_bytes(&buf[..len]), Ok(&3u64.to_le_bytes()[..]));
+    assert!(buf[len..].iter().all(|&byte| byte == 0xaa));
+
+    // Nothing is written to a buffer that's too small.
+    let mut buf = [0xaa; ENVELOPE_HEADER_LEN + 7];
+    assert_eq!(
+        seal(3u64, Checksum::Crc32c, &mut buf),
+        Err(EncodeError::BufferTooSmall {
+            needed: len,
+            available: len - 1
+        })
+    );
+    assert_eq!(buf, [0xaa; ENVELOPE_HEADER_LEN + 7]);
+}
+
+#[test]
+fn corrupted_envelopes() {
+    for checksum in [Checksum::Crc32c, Checksum::XxHash64] {
+        let envelope = seal_vec(sample(), checksum).unwrap();
+
+        // Every flipped bit is caught.
+        for bit in 0..envelope.len() * 8 {
+            let mut corrupted = envelope.clone();
+            corrupted[bit / 8] ^= 1 << (bit % 8);
+            assert!(open::<Vec<String>>(&corrupted).is_err(), "bit {bit}");
+        }
+    }
+
+    let envelope = seal_vec(sample(), Checksum::Crc32c).unwrap();
+    let len = envelope.len();
+    let corrupt = |offset: usize, byte: u8| {
+        let mut corrupted = envelope.clone();
+        corrupted[offset] = byte;
+        open::<Vec<String>>(&corrupted).unwrap_err()
+    };
+    assert_eq!(
+        corrupt(0, 2),
+        EnvelopeError::UnsupportedVersion { version: 2 }
+    );
+    assert_eq!(corrupt(1, 9), EnvelopeError::UnknownChecksum { tag: 9 });
+    assert_eq!(corrupt(2, 2), EnvelopeError::InvalidHeader);
+    assert_eq!(corrupt(3, 1), EnvelopeError::InvalidHeader);
+    assert_eq!(
+        corrupt(4, envelope[4] + 1),
+        EnvelopeError::LengthMismatch {
+            expected: len + 1,
+            found: len
+        }
+    );
+    assert!(matches!(
+        corrupt(ENVELOPE_HEADER_LEN, 0xff),
+        EnvelopeError::ChecksumMismatch { .. }
+    ));
+
+    assert_eq!(
+        open_bytes(&envelope[..len - 1]),
+        Err(EnvelopeError::LengthMismatch {
+            expected: len,
+            found: len - 1
+        })
+    );
+    assert_eq!(
+        open_bytes(&envelope[..3]),
+        Err(EnvelopeError::LengthMismatch {
+            expected: ENVELOPE_HEADER_LEN,
+            found: 3
+        })
+    );
+}
+
+#[test]
+fn checksummed_but_invalid_value() {
+    // A value that was already invalid when it was checksummed still fails to open.
+    let mut buf = [0; ENVELOPE_HEADER_LEN + 1];
+    seal(true, Checksum::Crc32c, &mut buf).unwrap();
+    buf[ENVELOPE_HEADER_LEN] = 2;
+    let digest = Checksum::Crc32c.compute(&buf[ENVELOPE_HEADER_LEN..]);
+    buf[8..ENVELOPE_HEADER_LEN].copy_from_slice(&digest.to_le_bytes());
+    match open::<bool>(&buf) {
+        Err(EnvelopeError::Decode(e)) => {
+            assert_eq!(e.kind(), DecodeErrorKind::InvalidBool { value: 2 })
+        }
+        other => panic!("expected a decode error, got {other:?}"),
+    }
+}
+
+#[test]
+fn typed_envelopes() {
+    let envelope = seal_typed_vec::<Vec<String>>(sample(), Checksum::Crc32c).unwrap();
+    let header = EnvelopeHeader::read(&envelope).unwrap();
+    assert_eq!(header.fingerprint, Some(Vec::<String>::FINGERPRINT));
+    assert_eq!(
+        header.header_len(),
+        ENVELOPE_HEADER_LEN + ENVELOPE_FINGERPRINT_LEN
+    );
+
+    let list = open_typed::<Vec<String>>(&envelope).unwrap();
+    assert_eq!(list.iter().collect::<Vec<_>>(), sample());
+    // The type can also go unchecked.
+    assert_eq!(open::<Vec<String>>(&envelope).unwrap().len(), 20);
+
+    // Types with the same layout are still told apart.
+    assert_eq!(
+        open_typed::<Vec<Vec<u8>>>(&envelope).unwrap_err(),
+        EnvelopeError::TypeMismatch {
+            expected: Vec::<Vec<u8>>::FINGERPRINT,
+            found: Some(Vec::<String>::FINGERPRINT),
+        }
+    );
+    let mut buf = [0; ENVELOPE_HEADER_LEN + ENVELOPE_FINGERPRINT_LEN + 8];
+    let len = seal_typed::<u64>(7u64, Checksum::XxHash64, &mut buf).unwrap();
+    assert_eq!(len, buf.len());
+    assert!(matches!(
+        open_typed::<i64>(&buf),
+        Err(EnvelopeError::TypeMismatch { .. })
+    ));
+    assert_eq!(open_typed::<u64>(&buf).unwrap().into_inner(), 7);
+
+    // Untyped envelopes don't pass as any type.
+    let untyped = seal_vec(sample(), Checksum::Crc32c).unwrap();
+    assert_eq!(
+        open_typed::<Vec<String>>(&untyped).unwrap_err(),
+        EnvelopeError::TypeMismatch {
+            expected: Vec::<String>::FINGERPRINT,
+            found: None,
+        }
+    );
+
+    // The fingerprint is covered by the checksum.
+    let mut corrupted = envelope.clone();
+    corrupted[ENVELOPE_HEADER_LEN] ^= 1;
+    assert!(matches!(
+        open_typed::<Vec<String>>(&corrupted),
+        Err(EnvelopeError::ChecksumMismatch { .. })
+    ));
+    assert_eq!(
+        EnvelopeHeader::read(&envelope[..ENVELOPE_HEADER_LEN + 3]),
+        Err(EnvelopeError::LengthMismatch {
+            expected: ENVELOPE_HEADER_LEN + ENVELOPE_FINGERPRINT_LEN,
+            found: ENVELOPE_HEADER_LEN + 3,
+        })
+    );
+}
+
+#[test]
+fn fingerprints() {
+    let fingerprints = [
+        <()>::FINGERPRINT,
+        u8::FINGERPRINT,
+        i8::FINGERPRINT,
+        String::FINGERPRINT,
+        Vec::<u8>::FINGERPRINT,
+        Vec::<i8>::FINGERPRINT,
+        Box::<u8>::FINGERPRINT,
+        Option::<u8>::FINGERPRINT,
+        Result::<u8, ()>::FINGERPRINT,
+        Result::<(), u8>::FINGERPRINT,
+    ];
+    for (i, a) in fingerprints.iter().enumerate() {
+        for b in &fingerprints[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use bytes::BytesMut;
+use futures_util::{SinkExt, StreamExt};
+use tokio_util::codec::{Decoder, Encoder, FramedRead, FramedWrite};
+
+use crate::{
+    encode_value_vec,
+    framing::{FrameCodec, FrameConfig, FrameError},
+    write_message,
+};
+
+#[tokio::test]
+async fn frames_over_duplex() {
+    let (client, server) = tokio::io::duplex(64);
+
+    let writer = tokio::spawn(async move {
+        let mut frames = FramedWrite::new(client, FrameCodec::<Vec<String>>::new());
+        for i in 0..100 {
+            let names = vec![format!("name {i}"); i % 5];
+            frames.send(names).await.unwrap();
+        }
+        // Encoding borrowed data works as well.
+        frames.send(&["borrowed"][..]).await.unwrap();
+    });
+
+    let mut frames = FramedRead::new(server, FrameCodec::<Vec<String>>::new());
+    for i in 0..100 {
+        let frame = frames.next().await.unwrap().unwrap();
+        assert_eq!(frame.get().len(), i % 5);
+        if i % 5 > 0 {
+            assert_eq!(frame.get().get(0), Ok(format!("name {i}").as_str()));
+        }
+    }
+    let frame = frames.next().await.unwrap().unwrap();
+    assert_eq!(frame.get().get(0), Ok("borrowed"));
+    assert!(frames.next().await.is_none());
+
+    writer.await.unwrap();
+}
+
+#[test]
+fn frame_format() {
+    let mut codec = FrameCodec::<u16>::with_config(FrameConfig {
+        type_tag: Some(0xabcd),
+        ..FrameConfig::DEFAULT
+    });
+    let mut buf = BytesMut::new();
+    codec.encode(0x1234u16, &mut buf).unwrap();
+    assert_eq!(&buf[..], &[6, 0, 0, 0, 0xcd, 0xab, 0, 0, 0x34, 0x12]);
+
+    // Partial frames are buffered until they're complete.
+    let mut partial = buf.split_to(7);
+    assert!(codec.decode(&mut partial).unwrap().is_none());
+    partial.unsplit(buf);
+    let frame = codec.decode(&mut partial).unwrap().unwrap();
+    assert_eq!(frame.get(), 0x1234);
+    assert!(partial.is_empty());
+
+    // Untagged frames are the same as `write_message`'s.
+    let mut untagged = FrameCodec::<Vec<u8>>::new();
+    let mut buf = BytesMut::new();
+    untagged.encode(vec![1u8, 2, 3], &mut buf).unwrap();
+    let mut message = Vec::new();
+    write_message(&mut message, vec![1u8, 2, 3]).unwrap();
+    assert_eq!(&buf[..], &message[..]);
+}
+
+#[test]
+fn frame_errors() {
+    let config = FrameConfig {
+        type_tag: Some(1),
+        max_frame_len: 16,
+    };
+    let mut codec = FrameCodec::<String>::with_config(config);
+    let mut buf = BytesMut::new();
+
+    let err = codec.encode("a string too long for the frame", &mut buf);
+    assert!(matches!(
+        err,
+        Err(FrameError::FrameTooLarge {
+            len: 43,
+            max_len: 16
+        })
+    ));
+    assert!(buf.is_empty());
+
+    // Too large frames are rejected as soon as their length is known.
+    let mut buf = BytesMut::from(&100u32.to_le_bytes()[..]);
+    let err = codec.decode(&mut buf);
+    assert!(matches!(
+        err,
+        Err(FrameError::FrameTooLarge {
+            len: 100,
+            max_len: 16
+        })
+    ));
+
+    let mut other = FrameCodec::<String>::with_config(FrameConfig {
+        type_tag: Some(2),
+        ..config
+    });
+    let mut buf = BytesMut::new();
+    other.encode("hi", &mut buf).unwrap();
+    let err = codec.decode(&mut buf);
+    assert!(matches!(
+        err,
+        Err(FrameError::TypeTagMismatch {
+            expected: 1,
+            found: 2
+        })
+    ));
+
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(&12u32.to_le_bytes());
+    buf.extend_from_slice(&1u32.to_le_bytes());
+    buf.extend_from_slice(&encode_value_vec(u64::MAX)[..]);
+    let err = codec.decode(&mut buf);
+    assert!(matches!(err, Err(FrameError::Decode(_))));
+}
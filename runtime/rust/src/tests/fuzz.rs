@@ -0,0 +1,309 @@
+//! Feeds random, truncated and corrupted buffers to every decoder in the runtime. Decoding may
+//! fail, but it must never panic.
+
+use crate::{
+    BaseLen, BoxLazy, Decode, DecodeCursor, DecodeResult, Encode, EncodeCursor, ListLazy, Owned,
+    decode_value, encode_value_vec, verify_value,
+};
+
+/// Small deterministic xorshift PRNG so the tests are reproducible without extra dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|_| match self.below(4) {
+                // Bias towards small values so that lengths and offsets are often in range.
+                0 => 0,
+                1 => self.below(16) as u8,
+                _ => self.next() as u8,
+            })
+            .collect()
+    }
+}
+
+/// Decode `buf` as a whole bunch of types, touching every lazily decoded part as well.
+fn decode_all(buf: &[u8]) {
+    let _ = decode_value::<u8>(buf);
+    let _ = decode_value::<i16>(buf);
+    let _ = decode_value::<u32>(buf);
+    let _ = decode_value::<f64>(buf);
+    let _ = decode_value::<u128>(buf);
+    let _ = decode_value::<bool>(buf);
+    let _ = decode_value::<()>(buf);
+    let _ = decode_value::<Option<u32>>(buf);
+    let _ = decode_value::<Result<u16, bool>>(buf);
+    let _ = decode_value::<&str>(buf);
+    let _ = decode_value::<String>(buf);
+    let _ = decode_value::<&[u8]>(buf);
+    let _ = decode_value::<Vec<u8>>(buf);
+    let _ = decode_value::<Vec<String>>(buf);
+    let _ = decode_value::<Vec<Option<Vec<i64>>>>(buf);
+    let _ = decode_value::<Option<Result<String, Vec<bool>>>>(buf);
+    let _ = decode_value::<Box<Result<String, u8>>>(buf);
+    let _ = decode_value::<Box<Option<Box<Vec<Box<u8>>>>>>(buf);
+    let _ = decode_value::<Tree>(buf);
+
+    if let Ok(list) = decode_value::<ListLazy<String>>(buf) {
+        touch_list(list);
+    }
+    if let Ok(list) = decode_value::<ListLazy<Vec<u32>>>(buf) {
+        touch_list(list);
+        for inner in &list {
+            touch_list(inner);
+        }
+    }
+    if let Ok(list) = decode_value::<ListLazy<u8>>(buf) {
+        let _: &[u8] = list.into();
+        let _: Result<[u8; 3], ()> = list.try_into();
+        touch_list(list);
+    }
+    if let Ok(list) = decode_value::<ListLazy<Box<Option<u16>>>>(buf) {
+        for item in &list {
+            let _ = item.get();
+        }
+        let _ = Vec::<Box<Option<u16>>>::try_from(list);
+    }
+    if let Ok(list) = decode_value::<BoxLazy<Vec<String>>>(buf).and_then(|boxed| boxed.get()) {
+        touch_list(list);
+    }
+
+    verify_all(buf);
+
+    let cursor = DecodeCursor::new(buf);
+    let _ = cursor.follow_scratch();
+    let _ = cursor.advance(3);
+    let _ = cursor.scratch(5);
+    let _ = cursor.inner_in_scratch(|cursor| cursor.base(7).map(|_| ()));
+}
+
+/// Anything that verifies must decode successfully, both lazily and owned.
+fn verify_all(buf: &[u8]) {
+    fn check<T: Owned>(buf: &[u8]) -> Option<()> {
+        let verified = verify_value::<T>(buf).ok()?;
+        T::lazy_to_owned(verified.into_inner()).expect("verified value failed to decode");
+        decode_value::<T>(buf).expect("verified value failed to decode");
+        Some(())
+    }
+
+    check::<u32>(buf);
+    check::<bool>(buf);
+    check::<String>(buf);
+    check::<Vec<String>>(buf);
+    check::<Option<Result<String, Vec<bool>>>>(buf);
+    check::<Box<Option<Box<Vec<Box<u8>>>>>>(buf);
+    check::<Vec<Option<Vec<i64>>>>(buf);
+
+    if let Ok(list) = verify_value::<Vec<Box<Option<String>>>>(buf) {
+        for item in list.view().iter() {
+            let _ = item.get().map(str::len);
+        }
+    }
+}
+
+fn touch_list<T: Owned>(list: ListLazy<T>)
+where
+    for<'a> T::Lazy<'a>: core::fmt::Debug,
+{
+    let _ = format!("{list:?}");
+    let _ = list.get(list.len());
+    let _ = list.get(list.len().wrapping_sub(1));
+    for _ in &list {}
+    let _ = Vec::<T>::try_from(list);
+}
+
+#[test]
+fn fuzz_random_buffers() {
+    let mut rng = Rng(0x9e3779b97f4a7c15);
+    for _ in 0..20_000 {
+        let len = rng.below(64);
+        decode_all(&rng.bytes(len));
+    }
+}
+
+#[test]
+fn fuzz_compressed_buffers() {
+    use crate::compression::{Compression, zrle_decompress, zrle_decompressed_len};
+
+    let mut rng = Rng(0x2545f4914f6cdd1d);
+    for _ in 0..20_000 {
+        let len = rng.below(64);
+        let buf = rng.bytes(len);
+
+        // Random bytes round trip, and random compressed data fails cleanly.
+        assert_eq!(
+            Compression::Zrle.decompress(&Compression::Zrle.compress(&buf), len),
+            Ok(buf.clone())
+        );
+        if let Ok(len) = zrle_decompressed_len(&buf) {
+            assert_eq!(zrle_decompress(&buf, &mut vec![0; len]), Ok(len));
+        }
+        #[cfg(feature = "lz4")]
+        let _ = Compression::Lz4.decompress(&buf, usize::MAX);
+    }
+}
+
+#[test]
+fn fuzz_envelopes() {
+    use crate::envelope::{Checksum, ENVELOPE_HEADER_LEN, EnvelopeHeader, open};
+
+    let mut rng = Rng(0x5851f42d4c957f2d);
+    for _ in 0..20_000 {
+        let len = rng.below(64);
+        let buf = rng.bytes(len);
+        let _ = open::<Vec<String>>(&buf);
+
+        // Random values behind a valid header and checksum.
+        let mut envelope = vec![0; ENVELOPE_HEADER_LEN];
+        EnvelopeHeader {
+            checksum: Checksum::XxHash64,
+            fingerprint: None,
+            len: len as u32,
+            digest: Checksum::XxHash64.compute(&buf),
+        }
+        .write(&mut envelope);
+        envelope.extend_from_slice(&buf);
+        let _ = open::<Vec<String>>(&envelope);
+        let _ = open::<Option<Box<u64>>>(&envelope);
+        let _ = open::<Result<Vec<u8>, String>>(&envelope);
+    }
+}
+
+#[test]
+fn fuzz_truncated_buffers() {
+    let values: Vec<Vec<u8>> = vec![
+        encode_value_vec(vec![
+            "hubba bubba".to_string(),
+            "ka pow".into(),
+            "12345".into(),
+        ]),
+        encode_value_vec(vec![Some(vec![-1i64, 2, 3]), None, Some(vec![])]),
+        encode_value_vec(Box::new(Box::new(Some(Box::new(vec![Box::new(7u8)]))))),
+        encode_value_vec(Some(Err::<String, Vec<bool>>(vec![true, false]))),
+        encode_value_vec(sample_tree(3)),
+    ];
+
+    for buf in &values {
+        for len in 0..buf.len() {
+            decode_all(&buf[..len]);
+        }
+    }
+}
+
+#[test]
+fn fuzz_corrupted_buffers() {
+    let values: Vec<Vec<u8>> = vec![
+        encode_value_vec(vec![
+            "hubba bubba".to_string(),
+            "ka pow".into(),
+            "12345".into(),
+        ]),
+        encode_value_vec(vec![Some(vec![-1i64, 2, 3]), None, Some(vec![])]),
+        encode_value_vec(Box::new(Box::new(Some(Box::new(vec![Box::new(7u8)]))))),
+        encode_value_vec(Some(Err::<String, Vec<bool>>(vec![true, false]))),
+        encode_value_vec(sample_tree(3)),
+    ];
+
+    let mut rng = Rng(0xdeadbeefcafef00d);
+    for buf in &values {
+        for _ in 0..2_000 {
+            let mut buf = buf.clone();
+            for _ in 0..1 + rng.below(4) {
+                let i = rng.below(buf.len());
+                buf[i] = rng.next() as u8;
+            }
+            decode_all(&buf);
+        }
+    }
+}
+
+#[test]
+fn offsets_past_end_of_buffer() {
+    // A string whose scratch offset points past the end of the buffer.
+    let mut buf = encode_value_vec("some string");
+    buf[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(decode_value::<&str>(&buf).is_err());
+
+    // A list whose length doesn't fit in the buffer.
+    let mut buf = encode_value_vec(vec![1u32, 2, 3]);
+    buf[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(decode_value::<Vec<u32>>(&buf).is_err());
+    assert!(decode_value::<ListLazy<u32>>(&buf).is_err());
+}
+
+#[test]
+fn cyclic_buffer() {
+    // A tree node whose list of children points back at the node itself.
+    let mut buf = vec![0u8; 8];
+    buf[0..4].copy_from_slice(&1u32.to_le_bytes());
+    buf[4..8].copy_from_slice(&0u32.to_le_bytes());
+    assert!(decode_value::<Tree>(&buf).is_err());
+}
+
+#[test]
+fn deeply_nested_buffer() {
+    let mut tree = Tree { children: vec![] };
+    for _ in 0..1000 {
+        tree = Tree {
+            children: vec![tree],
+        };
+    }
+    let buf = encode_value_vec(&tree);
+    assert!(decode_value::<Tree>(&buf).is_err());
+
+    // Tear down the tree iteratively to avoid overflowing the stack in `Drop`.
+    let mut children = tree.children;
+    while let Some(child) = children.pop() {
+        children = child.children;
+    }
+}
+
+/// A recursive type, used to make sure decoding cyclic or very deep buffers fails gracefully.
+#[derive(Debug, PartialEq)]
+struct Tree {
+    children: Vec<Tree>,
+}
+
+fn sample_tree(depth: usize) -> Tree {
+    Tree {
+        children: (0..depth).map(sample_tree).collect(),
+    }
+}
+
+impl BaseLen for Tree {
+    const BASE_LEN: usize = 8;
+    const WIDE_BASE_LEN: usize = 16;
+}
+
+impl Encode for Tree {
+    fn scratch_len(&self) -> usize {
+        self.children.scratch_len()
+    }
+
+    fn wide_scratch_len(&self) -> usize {
+        self.children.wide_scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.children.encode(cursor);
+    }
+}
+
+impl<'a> Decode<'a> for Tree {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        Ok(Tree {
+            children: Decode::decode(cursor)?,
+        })
+    }
+}
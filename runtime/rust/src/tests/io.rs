@@ -0,0 +1,47 @@
+use std::io::{self, Cursor};
+
+use crate::{DecodeError, DecodeErrorKind, ListLazy, read_message, write_message};
+
+#[test]
+fn round_trip() {
+    let mut stream = Vec::new();
+    write_message(&mut stream, vec!["a", "bc"]).unwrap();
+    write_message(&mut stream, 7u32).unwrap();
+    write_message(&mut stream, vec!["def"]).unwrap();
+    assert_eq!(&stream[..4], &(8 + 16 + 3u32).to_le_bytes());
+
+    let mut reader = Cursor::new(stream);
+    let mut buf = Vec::new();
+    let first: Vec<String> = read_message(&mut reader, &mut buf).unwrap();
+    assert_eq!(first, ["a", "bc"]);
+    assert_eq!(read_message::<u32>(&mut reader, &mut buf).unwrap(), 7);
+    let third: ListLazy<String> = read_message(&mut reader, &mut buf).unwrap();
+    assert_eq!(third.get(0), Ok("def"));
+
+    let err = read_message::<u32>(&mut reader, &mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn errors() {
+    let mut stream = Vec::new();
+    write_message(&mut stream, "hello").unwrap();
+
+    // Truncated message.
+    let mut buf = Vec::new();
+    let err = read_message::<String>(&mut &stream[..10], &mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    // Invalid message, with the decode error inside.
+    stream[4 + 8] = 0xff;
+    let err = read_message::<String>(&mut &stream[..], &mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let decode_err = err.into_inner().unwrap().downcast::<DecodeError>().unwrap();
+    assert_eq!(decode_err.kind(), DecodeErrorKind::InvalidUtf8);
+
+    // A huge length prefix with nothing behind it.
+    let mut bogus = u32::MAX.to_le_bytes().to_vec();
+    bogus.extend_from_slice(&[0; 16]);
+    let err = read_message::<u64>(&mut &bogus[..], &mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
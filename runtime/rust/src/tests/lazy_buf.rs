@@ -0,0 +1,72 @@
+//! Run under Miri too: `cargo +nightly miri test -p mproto lazy_buf`.
+
+use std::{rc::Rc, sync::Arc};
+
+use crate::{DecodeErrorKind, LazyBuf, ListLazy, StableBuf, encode_value_vec};
+
+fn names() -> Vec<u8> {
+    encode_value_vec(vec!["alice", "bob", "carol"])
+}
+
+fn check_moves<B: StableBuf>(buf: B) {
+    let lazy_buf = LazyBuf::<Vec<String>, B>::new(buf);
+    // Move the buffer around - through a function and onto the heap - before reading it.
+    let moved = std::hint::black_box(lazy_buf);
+    let boxed = Box::new(moved);
+    let items: Vec<&str> = boxed.get().iter().collect();
+    assert_eq!(items, ["alice", "bob", "carol"]);
+}
+
+#[test]
+fn owners() {
+    check_moves(names());
+    check_moves(names().into_boxed_slice());
+    check_moves(Arc::<[u8]>::from(names()));
+    check_moves(Rc::<[u8]>::from(names()));
+    check_moves(&names()[..]);
+}
+
+#[test]
+fn try_new() {
+    let mut buf = names();
+    buf.truncate(4);
+    let err = LazyBuf::<Vec<String>, _>::try_new(buf).err().unwrap();
+    assert_eq!(err.kind(), DecodeErrorKind::UnexpectedEnd);
+
+    let lazy_buf = LazyBuf::<Vec<String>, _>::try_new(names()).unwrap();
+    assert_eq!(lazy_buf.buf(), &names()[..]);
+    assert_eq!(lazy_buf.into_buf(), names());
+}
+
+#[test]
+fn map() {
+    let lazy_buf = LazyBuf::<Vec<String>, _>::new(names());
+    let second: LazyBuf<String, _> = lazy_buf
+        .try_map(|list: ListLazy<String>| list.get(1))
+        .unwrap();
+    assert_eq!(second.get(), "bob");
+
+    let lazy_buf = LazyBuf::<Vec<String>, _>::new(names());
+    let err = lazy_buf
+        .try_map::<String, _, _>(|list: ListLazy<String>| list.get(3))
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::IndexOutOfBounds { index: 3, len: 3 }
+    );
+
+    let lazy_buf = LazyBuf::<Option<Vec<String>>, _>::new(encode_value_vec(Some(vec!["dave"])));
+    let unwrapped: LazyBuf<Vec<String>, _> =
+        lazy_buf.map(|opt: Option<ListLazy<String>>| opt.unwrap());
+    assert_eq!(unwrapped.get().get(0), Ok("dave"));
+}
+
+#[test]
+fn send_between_threads() {
+    let lazy_buf = LazyBuf::<Vec<String>, Arc<[u8]>>::new(names().into());
+    let last = std::thread::spawn(move || lazy_buf.get().get(2).map(String::from))
+        .join()
+        .unwrap();
+    assert_eq!(last, Ok("carol".to_owned()));
+}
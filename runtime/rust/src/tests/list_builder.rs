@@ -0,0 +1,126 @@
+use core::cell::{Cell, RefCell};
+
+use crate::{
+    ListBuilder, ListBuilderGen, decode_value, encode_value_extend, encode_value_vec,
+    is_canonical, verify_value,
+};
+
+#[test]
+fn build_list() {
+    let list = ListBuilderGen::new(|list: &mut ListBuilder<String>| {
+        for i in 0..100 {
+            list.push(format!("item {i}"));
+        }
+        list.push("borrowed");
+    });
+    let expected: Vec<String> = (0..100)
+        .map(|i| format!("item {i}"))
+        .chain(["borrowed".to_string()])
+        .collect();
+
+    // Both the two-pass and the single-pass encoding give the same bytes.
+    let buf = encode_value_vec(&list);
+    let mut growable = Vec::new();
+    encode_value_extend(&list, &mut growable);
+    assert_eq!(buf, growable);
+
+    verify_value::<Vec<String>>(&buf).unwrap();
+    assert_eq!(decode_value::<Vec<String>>(&buf), Ok(expected));
+}
+
+#[test]
+fn build_list_without_scratch() {
+    // Items without scratch space are laid out the same as a `Vec`'s.
+    let list = ListBuilderGen::new(|list: &mut ListBuilder<Option<u32>>| {
+        list.extend((0..10).map(|i| (i % 3 != 0).then_some(i)));
+    });
+    let expected: Vec<Option<u32>> = (0..10).map(|i| (i % 3 != 0).then_some(i)).collect();
+    let buf = encode_value_vec(&list);
+    assert_eq!(buf, encode_value_vec(&expected));
+    assert!(is_canonical::<Vec<Option<u32>>>(&buf));
+
+    let empty = ListBuilderGen::new(|_: &mut ListBuilder<String>| {});
+    let buf = encode_value_vec(&empty);
+    assert_eq!(buf, encode_value_vec(Vec::<String>::new()));
+}
+
+#[test]
+fn build_nested_lists() {
+    let list = ListBuilderGen::new(|list: &mut ListBuilder<Vec<Option<Box<String>>>>| {
+        for i in 0..20 {
+            list.push(ListBuilderGen::new(move |inner: &mut ListBuilder<_>| {
+                for j in 0..i % 4 {
+                    inner.push((j % 2 == 0).then(|| Box::new(format!("{i} {j}"))));
+                }
+            }));
+        }
+    });
+    let expected: Vec<Vec<Option<Box<String>>>> = (0..20)
+        .map(|i| {
+            (0..i % 4)
+                .map(|j| (j % 2 == 0).then(|| Box::new(format!("{i} {j}"))))
+                .collect()
+        })
+        .collect();
+
+    let mut buf = vec![1, 2, 3];
+    let len = encode_value_extend(&list, &mut buf);
+    assert_eq!(buf.len(), 3 + len);
+    assert_eq!(&buf[..3], &[1, 2, 3]);
+    assert_eq!(decode_value(&buf[3..]), Ok(expected));
+    assert_eq!(&buf[3..], &encode_value_vec(&list)[..]);
+}
+
+#[test]
+fn build_list_from_one_shot_source() {
+    let items = RefCell::new(Some((0..1000u64).map(|i| i.to_string())));
+    let list = ListBuilderGen::new(|list: &mut ListBuilder<String>| {
+        list.extend(items.borrow_mut().take().expect("items pushed twice"));
+    });
+
+    // Single-pass encoding only pushes the items once.
+    let mut buf = Vec::new();
+    encode_value_extend(&list, &mut buf);
+    let decoded: Vec<String> = decode_value(&buf).unwrap();
+    assert_eq!(decoded.len(), 1000);
+    assert_eq!(decoded[999], "999");
+}
+
+#[test]
+fn list_builder_in_struct() {
+    // Wherever a list is expected, e.g. a field of a generated `*Gen` struct.
+    let value: Result<_, ()> = Ok(Some(ListBuilderGen::new(|list: &mut ListBuilder<u8>| {
+        list.extend(b"bytes".iter().copied());
+    })));
+    let buf = encode_value_vec(&value);
+    assert_eq!(
+        decode_value::<Result<Option<Vec<u8>>, ()>>(&buf),
+        Ok(Ok(Some(b"bytes".to_vec())))
+    );
+}
+
+#[test]
+#[should_panic = "must push the same items both times"]
+fn build_list_pushing_fewer_items() {
+    let calls = Cell::new(0);
+    let list = ListBuilderGen::new(|list: &mut ListBuilder<u32>| {
+        calls.set(calls.get() + 1);
+        list.extend(0..10 / calls.get());
+    });
+    encode_value_vec(&list);
+}
+
+#[test]
+fn build_list_panicking() {
+    // Pushes more items than were measured, overflowing the buffer. The builder isn't finished
+    // while unwinding, which would panic again and abort.
+    let calls = Cell::new(0);
+    let list = ListBuilderGen::new(|list: &mut ListBuilder<String>| {
+        calls.set(calls.get() + 1);
+        list.extend((0..10 * calls.get()).map(|i| i.to_string()));
+    });
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        encode_value_vec(&list);
+    }));
+    assert!(result.is_err());
+}
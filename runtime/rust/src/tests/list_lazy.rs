@@ -0,0 +1,154 @@
+use crate::{DecodeErrorKind, ListLazy, PathSegment, decode_value, encode_value_vec, verify_value};
+
+#[test]
+fn iterate_from_both_ends() {
+    let buf = encode_value_vec((0..10u32).collect::<Vec<_>>());
+    let list: ListLazy<u32> = decode_value(&buf).unwrap();
+
+    let mut iter = list.iter();
+    assert_eq!(iter.size_hint(), (0, Some(10)));
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.next_back(), Some(9));
+    assert_eq!(iter.nth(2), Some(3));
+    assert_eq!(iter.nth_back(1), Some(7));
+    assert_eq!(iter.size_hint(), (0, Some(3)));
+    assert_eq!(iter.by_ref().rev().collect::<Vec<_>>(), [6, 5, 4]);
+    assert_eq!(iter.next(), None);
+
+    assert_eq!(list.iter().skip(8).collect::<Vec<_>>(), [8, 9]);
+    assert_eq!(list.iter().nth(usize::MAX), None);
+    assert_eq!(
+        (&list).into_iter().rev().step_by(4).collect::<Vec<_>>(),
+        [9, 5, 1]
+    );
+}
+
+#[test]
+fn invalid_items() {
+    let mut buf = encode_value_vec(vec![true, false, true, false]);
+    buf[8 + 1] = 2;
+    let list: ListLazy<bool> = decode_value(&buf).unwrap();
+
+    // Plain iteration stops at the first invalid item, from either end.
+    let mut iter = list.iter();
+    assert_eq!(iter.next(), Some(true));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+    assert_eq!(list.iter().rev().collect::<Vec<_>>(), [false, true]);
+
+    // The plain iterator can only bound its length, as it may end early.
+    assert_eq!(list.iter().size_hint(), (0, Some(4)));
+    let mut try_iter = list.try_iter();
+    assert_eq!(try_iter.len(), 4);
+    try_iter.next_back();
+    assert_eq!(try_iter.len(), 3);
+
+    // Nor does it equal any list, valid or not.
+    let valid_buf = encode_value_vec(vec![true, true, true, false]);
+    let valid: ListLazy<bool> = decode_value(&valid_buf).unwrap();
+    assert_ne!(list, valid);
+    assert_ne!(valid, list);
+    assert_ne!(list, list);
+    assert_ne!(vec![true, true, true, false], list);
+
+    let items = list.try_iter().collect::<Vec<_>>();
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0], Ok(true));
+    let err = items[1].as_ref().unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::InvalidBool { value: 2 });
+    assert_eq!(err.path().collect::<Vec<_>>(), [PathSegment::Index(1)]);
+    assert_eq!(items[2..], [Ok(true), Ok(false)]);
+}
+
+#[test]
+fn first_and_last() {
+    let buf = encode_value_vec(vec!["a", "b", "c"]);
+    let list: ListLazy<String> = decode_value(&buf).unwrap();
+    assert_eq!(list.first(), Ok(Some("a")));
+    assert_eq!(list.last(), Ok(Some("c")));
+
+    let buf = encode_value_vec(Vec::<String>::new());
+    let list: ListLazy<String> = decode_value(&buf).unwrap();
+    assert_eq!(list.first(), Ok(None));
+    assert_eq!(list.last(), Ok(None));
+}
+
+#[test]
+fn chunks() {
+    let buf = encode_value_vec((0..7u16).map(|i| i.to_string()).collect::<Vec<_>>());
+    let list: ListLazy<String> = decode_value(&buf).unwrap();
+
+    let chunks = list.chunks(3);
+    assert_eq!(chunks.len(), 3);
+    let chunks = chunks
+        .map(|chunk| chunk.iter().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        chunks,
+        [vec!["0", "1", "2"], vec!["3", "4", "5"], vec!["6"]]
+    );
+
+    // Sub-lists re-encode like any other list.
+    let (head, tail) = list.split_at(5);
+    assert_eq!(
+        decode_value::<Vec<String>>(&encode_value_vec(head))
+            .unwrap()
+            .len(),
+        5
+    );
+    assert_eq!(
+        decode_value::<Vec<String>>(&encode_value_vec(tail)),
+        Ok(vec!["5".into(), "6".into()])
+    );
+}
+
+#[test]
+#[should_panic(expected = "chunk size must be non-zero")]
+fn zero_size_chunks() {
+    let buf = encode_value_vec(vec![1u8]);
+    let list: ListLazy<u8> = decode_value(&buf).unwrap();
+    list.chunks(0);
+}
+
+#[test]
+fn binary_search() {
+    let words = ["apple", "banana", "cherry", "date"];
+    let buf = encode_value_vec(words.as_slice());
+    let list: ListLazy<String> = decode_value(&buf).unwrap();
+
+    for (i, word) in words.iter().enumerate() {
+        assert_eq!(list.binary_search_by(|item| item.cmp(word)), Ok(Ok(i)));
+    }
+    assert_eq!(
+        list.binary_search_by(|item| item.cmp("aardvark")),
+        Ok(Err(0))
+    );
+    assert_eq!(
+        list.binary_search_by(|item| item.cmp("coconut")),
+        Ok(Err(3))
+    );
+    assert_eq!(
+        list.binary_search_by(|item| item.cmp("zucchini")),
+        Ok(Err(4))
+    );
+
+    // Decode errors are returned rather than treated as mismatches.
+    let mut buf = encode_value_vec(vec![false, true, true]);
+    buf[8 + 1] = 3;
+    let list: ListLazy<bool> = decode_value(&buf).unwrap();
+    let err = list.binary_search_by(|item| item.cmp(&true)).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::InvalidBool { value: 3 });
+}
+
+#[test]
+fn verified() {
+    let buf = encode_value_vec(vec![2i64, 3, 5, 7, 11]);
+    let list = verify_value::<Vec<i64>>(&buf).unwrap();
+
+    assert_eq!(list.first(), Some(2));
+    assert_eq!(list.last(), Some(11));
+    assert_eq!(list.iter().len(), 5);
+    assert_eq!(list.iter().rev().collect::<Vec<_>>(), [11, 7, 5, 3, 2]);
+    assert_eq!(list.binary_search_by(|item| item.cmp(&5)), Ok(2));
+    assert_eq!(list.binary_search_by(|item| item.cmp(&6)), Err(3));
+}
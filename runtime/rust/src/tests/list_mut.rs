@@ -0,0 +1,65 @@
+use crate::{
+    DecodeErrorKind, ListMut, WireFormat, decode_value, decode_value_with_format, encode_value_vec,
+    encode_value_vec_with_format,
+};
+
+#[test]
+fn set_items_in_place() {
+    let mut buf = encode_value_vec(vec![1u32, 2, 3]);
+    let len = buf.len();
+
+    let mut list = ListMut::<u32>::new(&mut buf).unwrap();
+    assert_eq!(list.len(), 3);
+    list.set(1, 20u32);
+    list.set(2, 30u32);
+    assert_eq!(list.get(1), Ok(20));
+    assert_eq!(list.as_lazy().iter().collect::<Vec<_>>(), vec![1, 20, 30]);
+
+    assert_eq!(buf.len(), len);
+    assert_eq!(decode_value::<Vec<u32>>(&buf), Ok(vec![1, 20, 30]));
+}
+
+#[test]
+fn set_items_with_padding() {
+    // Options of values without scratch space are fixed-size too.
+    let mut buf = encode_value_vec(vec![Some(1u16), None]);
+    let mut list = ListMut::<Option<u16>>::new(&mut buf).unwrap();
+    list.set(0, None::<u16>);
+    list.set(1, Some(7u16));
+    assert_eq!(
+        decode_value::<Vec<Option<u16>>>(&buf),
+        Ok(vec![None, Some(7)])
+    );
+}
+
+#[test]
+fn relative_format() {
+    let value = vec![vec![1u64, 2], vec![3]];
+    let mut buf = encode_value_vec_with_format(value, WireFormat::Relative);
+
+    // The second inner list's base follows the first one's in the outer list's scratch area.
+    let mut list = ListMut::<u64>::at_offset(&mut buf, 16, WireFormat::Relative).unwrap();
+    list.set(0, 30u64);
+    assert_eq!(
+        decode_value_with_format::<Vec<Vec<u64>>>(&buf, WireFormat::Relative),
+        Ok(vec![vec![1, 2], vec![30]])
+    );
+}
+
+#[test]
+fn empty_and_truncated() {
+    let mut buf = encode_value_vec(Vec::<u8>::new());
+    assert!(ListMut::<u8>::new(&mut buf).unwrap().is_empty());
+
+    let mut buf = encode_value_vec(vec![1u64, 2]);
+    buf.pop();
+    let err = ListMut::<u64>::new(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::UnexpectedEnd);
+}
+
+#[test]
+#[should_panic(expected = "index 2 out of bounds")]
+fn set_out_of_bounds() {
+    let mut buf = encode_value_vec(vec![1u8, 2]);
+    ListMut::<u8>::new(&mut buf).unwrap().set(2, 3u8);
+}
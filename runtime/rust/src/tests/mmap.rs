@@ -0,0 +1,51 @@
+use std::{io, path::PathBuf};
+
+use crate::{LazyBuf, encode_value_vec};
+
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, contents: &[u8]) -> Self {
+        let path = std::env::temp_dir().join(format!("mproto-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn open_mmap() {
+    let records: Vec<String> = (0..1000).map(|i| format!("record {i}")).collect();
+    let file = TempFile::new("records", &encode_value_vec(&records));
+
+    let mapped = unsafe { LazyBuf::<Vec<String>, _>::open_mmap(&file.0) }.unwrap();
+    assert_eq!(mapped.get().len(), 1000);
+    assert_eq!(mapped.get().get(567), Ok("record 567"));
+
+    let read = LazyBuf::<Vec<String>, _>::read_file(&file.0).unwrap();
+    assert_eq!(read.buf(), mapped.buf());
+}
+
+#[test]
+fn open_invalid() {
+    let mut buf = encode_value_vec(vec![1u64, 2, 3]);
+    buf.truncate(20);
+    let file = TempFile::new("truncated", &buf);
+
+    let err = unsafe { LazyBuf::<Vec<u64>, _>::open_mmap(&file.0) }
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = LazyBuf::<Vec<u64>, _>::read_file(&file.0).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let err = LazyBuf::<Vec<u64>, _>::read_file(file.0.with_extension("missing"))
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
@@ -0,0 +1,64 @@
+use crate::{DecodeErrorKind, ListLazy, Verified, encode_value_vec, verify_value};
+
+#[test]
+fn verify_and_view() {
+    let value: Vec<Option<Result<String, Box<u32>>>> = vec![
+        Some(Ok("hubba bubba".into())),
+        None,
+        Some(Err(Box::new(1234))),
+    ];
+    let buf = encode_value_vec(&value);
+
+    let verified = verify_value::<Vec<Option<Result<String, Box<u32>>>>>(&buf).unwrap();
+    let view = verified.view();
+    assert_eq!(view.len(), 3);
+    assert_eq!(view.get(0).unwrap(), Some(Ok("hubba bubba")));
+    assert_eq!(view.get(1).unwrap(), None);
+    let Some(Err(boxed)) = view.get(2).unwrap() else {
+        panic!("expected Some(Err(_))");
+    };
+    assert_eq!(boxed.get(), 1234);
+    assert!(view.get(3).is_none());
+    assert_eq!(view.iter().count(), 3);
+
+    // Verified values can be re-encoded as-is.
+    assert_eq!(encode_value_vec(verified), buf);
+}
+
+#[test]
+fn verify_bytes() {
+    let buf = encode_value_vec(vec![1u8, 2, 3]);
+    let verified = verify_value::<Vec<u8>>(&buf).unwrap();
+    let bytes: &[u8] = verified.view().into();
+    assert_eq!(bytes, &[1, 2, 3]);
+}
+
+#[test]
+fn verify_rejects_invalid_items() {
+    // Invalid UTF-8 in the second string.
+    let mut buf = encode_value_vec(vec!["abc".to_string(), "def".into()]);
+    let n = buf.len();
+    buf[n - 1] = 0xff;
+    let err = verify_value::<Vec<String>>(&buf).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::InvalidUtf8);
+    // ...which plain lazy decoding doesn't notice until the item is accessed.
+    let list = crate::decode_value::<ListLazy<String>>(&buf).unwrap();
+    assert!(list.get(1).is_err());
+
+    // An invalid bool deep inside a box.
+    let mut buf = encode_value_vec(Box::new(Some(true)));
+    let n = buf.len();
+    buf[n - 1] = 2;
+    let err = verify_value::<Box<Option<bool>>>(&buf).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::InvalidBool { value: 2 });
+}
+
+#[test]
+#[should_panic]
+fn unverified_view_panics() {
+    let mut buf = encode_value_vec(vec!["abc".to_string(), "def".into()]);
+    let n = buf.len();
+    buf[n - 1] = 0xff;
+    let list = crate::decode_value::<ListLazy<String>>(&buf).unwrap();
+    Verified::new_unchecked(list).iter().for_each(drop);
+}
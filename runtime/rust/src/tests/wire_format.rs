@@ -0,0 +1,229 @@
+use crate::{
+    BoxLazy, DecodeErrorKind, Encode, EncodeCursor, EncodeError, ListBuilder, ListBuilderGen,
+    ListLazy, SplicedBox, WireFormat, decode_value, decode_value_with_format, encode_value_vec,
+    encode_value_vec_with_format, encoded_len_with_format, try_encode_value_with_format,
+    verify_value_with_format,
+};
+
+#[test]
+fn relative_offsets() {
+    // The offset is from the offset itself rather than the start of the buffer.
+    let buf = encode_value_vec_with_format("hi", WireFormat::Relative);
+    assert_eq!(buf, [2, 0, 0, 0, 4, 0, 0, 0, b'h', b'i']);
+
+    let value: Vec<Option<Box<Vec<String>>>> = (0..10)
+        .map(|i| (i % 3 != 0).then(|| Box::new(vec![format!("item {i}"); i])))
+        .collect();
+    let buf = encode_value_vec_with_format(&value, WireFormat::Relative);
+    // Only the offsets differ from the absolute format.
+    assert_eq!(buf.len(), encode_value_vec(&value).len());
+    verify_value_with_format::<Vec<Option<Box<Vec<String>>>>>(&buf, WireFormat::Relative).unwrap();
+    assert_eq!(
+        decode_value_with_format::<Vec<Option<Box<Vec<String>>>>>(&buf, WireFormat::Relative),
+        Ok(value.clone())
+    );
+
+    let lazy: ListLazy<Option<Box<Vec<String>>>> =
+        decode_value_with_format(&buf, WireFormat::Relative).unwrap();
+    let item = lazy.get(4).unwrap().unwrap().get().unwrap();
+    assert_eq!(item.get(3), Ok("item 4"));
+
+    let mut growable = Vec::new();
+    let mut cursor = EncodeCursor::growable::<Vec<Option<Box<Vec<String>>>>>(&mut growable)
+        .with_format(WireFormat::Relative);
+    Encode::encode(&value, &mut cursor);
+    assert_eq!(growable, buf);
+}
+
+#[test]
+fn relative_list_builder() {
+    let list = ListBuilderGen::new(|list: &mut ListBuilder<Vec<Option<Box<String>>>>| {
+        for i in 0..20 {
+            list.push(ListBuilderGen::new(move |inner: &mut ListBuilder<_>| {
+                for j in 0..i % 4 {
+                    inner.push((j % 2 == 0).then(|| Box::new(format!("{i} {j}"))));
+                }
+            }));
+        }
+    });
+    let expected: Vec<Vec<Option<Box<String>>>> = (0..20)
+        .map(|i| {
+            (0..i % 4)
+                .map(|j| (j % 2 == 0).then(|| Box::new(format!("{i} {j}"))))
+                .collect()
+        })
+        .collect();
+
+    let mut buf = Vec::new();
+    let mut cursor = EncodeCursor::growable::<Vec<Vec<Option<Box<String>>>>>(&mut buf)
+        .with_format(WireFormat::Relative);
+    Encode::encode(&list, &mut cursor);
+
+    verify_value_with_format::<Vec<Vec<Option<Box<String>>>>>(&buf, WireFormat::Relative).unwrap();
+    assert_eq!(
+        decode_value_with_format::<Vec<Vec<Option<Box<String>>>>>(&buf, WireFormat::Relative),
+        Ok(expected)
+    );
+}
+
+#[test]
+fn splice_boxed_values() {
+    let names = vec!["a".to_string(), "b".to_string()];
+    let names_buf = encode_value_vec_with_format(&names, WireFormat::Relative);
+    let spliced = SplicedBox::<Vec<String>>::new(&names_buf).unwrap();
+    assert_eq!(spliced.as_bytes(), &names_buf[..]);
+
+    let message = vec![spliced, spliced];
+    let buf = encode_value_vec_with_format(&message, WireFormat::Relative);
+    assert_eq!(
+        decode_value_with_format::<Vec<Box<Vec<String>>>>(&buf, WireFormat::Relative),
+        Ok(vec![Box::new(names.clone()), Box::new(names.clone())])
+    );
+
+    // A relay forwards one of the boxed values without decoding it.
+    let lazy: ListLazy<Box<Vec<String>>> =
+        decode_value_with_format(&buf, WireFormat::Relative).unwrap();
+    let forwarded = lazy.get(1).unwrap().spliced().unwrap();
+    assert_eq!(forwarded.as_bytes(), &names_buf[..]);
+    let relayed = encode_value_vec_with_format(vec![forwarded], WireFormat::Relative);
+    assert_eq!(
+        decode_value_with_format::<Vec<Box<Vec<String>>>>(&relayed, WireFormat::Relative),
+        Ok(vec![Box::new(names.clone())])
+    );
+
+    // Absolute buffers get a re-encoded copy instead.
+    let buf = encode_value_vec(&message);
+    assert_eq!(
+        decode_value::<Vec<Box<Vec<String>>>>(&buf),
+        Ok(vec![Box::new(names.clone()), Box::new(names)])
+    );
+}
+
+#[test]
+fn splice_errors() {
+    let buf = encode_value_vec_with_format(vec!["abc"; 3], WireFormat::Relative);
+
+    // Trailing bytes are left out.
+    let mut padded = buf.clone();
+    padded.extend_from_slice(&[1, 2, 3]);
+    let spliced = SplicedBox::<Vec<String>>::new(&padded).unwrap();
+    assert_eq!(spliced.as_bytes(), &buf[..]);
+
+    assert!(SplicedBox::<Vec<String>>::new(&buf[..buf.len() - 1]).is_err());
+}
+
+#[test]
+fn splice_absolute() {
+    let buf = encode_value_vec(Box::new("abc"));
+    let lazy: BoxLazy<String> = decode_value(&buf).unwrap();
+    let err = lazy.spliced().unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DecodeErrorKind::UnsupportedFormat {
+            format: WireFormat::Absolute
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "not supported in the Absolute wire format at offset 4"
+    );
+}
+
+type Nested = Vec<Result<Option<Box<Vec<String>>>, u16>>;
+
+fn nested() -> Nested {
+    (0..10)
+        .map(|i| match i % 3 {
+            0 => Err(i as u16),
+            1 => Ok(None),
+            _ => Ok(Some(Box::new(vec![format!("item {i}"); i]))),
+        })
+        .collect()
+}
+
+#[test]
+fn wide_offsets() {
+    let buf = encode_value_vec_with_format("hi", WireFormat::Wide);
+    assert_eq!(
+        buf,
+        [2, 0, 0, 0, 0, 0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 0, b'h', b'i']
+    );
+
+    let value = nested();
+    let buf = encode_value_vec_with_format(&value, WireFormat::Wide);
+    assert_eq!(buf.len(), encoded_len_with_format(&value, WireFormat::Wide));
+    assert!(buf.len() > encode_value_vec(&value).len());
+    verify_value_with_format::<Nested>(&buf, WireFormat::Wide).unwrap();
+    assert_eq!(
+        decode_value_with_format::<Nested>(&buf, WireFormat::Wide),
+        Ok(value.clone())
+    );
+
+    let lazy: ListLazy<Result<Option<Box<Vec<String>>>, u16>> =
+        decode_value_with_format(&buf, WireFormat::Wide).unwrap();
+    assert_eq!(lazy.get(3).unwrap(), Err(3));
+    let item = lazy.get(5).unwrap().unwrap().unwrap().get().unwrap();
+    assert_eq!(item.get(4), Ok("item 5"));
+
+    // Lazy values can be re-encoded in either width.
+    assert_eq!(encode_value_vec(lazy), encode_value_vec(&value));
+    assert_eq!(encode_value_vec_with_format(lazy, WireFormat::Wide), buf);
+
+    let mut growable = Vec::new();
+    let mut cursor = EncodeCursor::growable::<Nested>(&mut growable).with_format(WireFormat::Wide);
+    Encode::encode(&value, &mut cursor);
+    assert_eq!(growable, buf);
+
+    let mut fixed = vec![0; buf.len()];
+    assert_eq!(
+        try_encode_value_with_format(&value, &mut fixed, WireFormat::Wide),
+        Ok(buf.len())
+    );
+    assert_eq!(fixed, buf);
+}
+
+#[test]
+fn wide_list_builder() {
+    let list = ListBuilderGen::new(|list: &mut ListBuilder<Option<Box<String>>>| {
+        for i in 0..20 {
+            list.push((i % 3 != 0).then(|| Box::new(format!("{i}"))));
+        }
+    });
+    let expected: Vec<Option<Box<String>>> = (0..20)
+        .map(|i| (i % 3 != 0).then(|| Box::new(format!("{i}"))))
+        .collect();
+
+    let buf = encode_value_vec_with_format(&list, WireFormat::Wide);
+    assert_eq!(
+        buf.len(),
+        encoded_len_with_format(&expected, WireFormat::Wide)
+    );
+    assert_eq!(
+        decode_value_with_format::<Vec<Option<Box<String>>>>(&buf, WireFormat::Wide),
+        Ok(expected)
+    );
+}
+
+#[test]
+fn wide_lengths() {
+    // A list of zero-sized items can be longer than 32 bits allow without taking up any space.
+    let value = vec![(); 1 << 32];
+    let buf = encode_value_vec_with_format(&value, WireFormat::Wide);
+    assert_eq!(buf, [0, 0, 0, 0, 1, 0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 0]);
+    let lazy: ListLazy<()> = decode_value_with_format(&buf, WireFormat::Wide).unwrap();
+    assert_eq!(lazy.len(), 1 << 32);
+
+    assert_eq!(
+        try_encode_value_with_format("hi", &mut [0; 17], WireFormat::Wide),
+        Err(EncodeError::BufferTooSmall {
+            needed: 18,
+            available: 17
+        })
+    );
+}
+
+#[test]
+#[should_panic = "length overflows u32, use WireFormat::Wide"]
+fn narrow_length_overflow() {
+    encode_value_vec(vec![(); 1 << 32]);
+}
@@ -0,0 +1,93 @@
+use crate::{
+    BaseLen, DecodeCursor, DecodeResult, Encode, EncodeCursor, Lazy, Owned, WireFormat,
+    decode_value_with_format,
+};
+
+/// A lazy value whose buffer has been checked by [`verify_value`], so that it and everything
+/// reachable from it is known to decode successfully.
+///
+/// [`Verified::view`] turns it into a view whose accessors return plain values instead of
+/// `DecodeResult`s.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Verified<L>(L);
+
+impl<L> Verified<L> {
+    /// Wrap a lazy value without verifying it. The lazy value must be part of a buffer that was
+    /// verified against its type - otherwise the view's accessors may panic.
+    #[inline]
+    pub fn new_unchecked(lazy: L) -> Self {
+        Self(lazy)
+    }
+
+    /// Wrap the result of decoding part of a verified value.
+    ///
+    /// Panics if decoding failed, which can only happen if the value wasn't actually verified.
+    #[inline]
+    #[track_caller]
+    pub fn assume_ok(result: DecodeResult<L>) -> Self {
+        match result {
+            Ok(lazy) => Self(lazy),
+            Err(e) => panic!("failed to decode verified mproto value: {e}"),
+        }
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &L {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> L {
+        self.0
+    }
+
+    #[inline]
+    pub fn view<'a>(self) -> L::View
+    where
+        L: Lazy<'a>,
+    {
+        L::view(self)
+    }
+}
+
+impl<L: BaseLen> BaseLen for Verified<L> {
+    const BASE_LEN: usize = L::BASE_LEN;
+    const WIDE_BASE_LEN: usize = L::WIDE_BASE_LEN;
+    const HAS_SCRATCH: bool = L::HAS_SCRATCH;
+}
+
+impl<L: Encode> Encode for Verified<L> {
+    #[inline]
+    fn scratch_len(&self) -> usize {
+        self.0.scratch_len()
+    }
+
+    #[inline]
+    fn wide_scratch_len(&self) -> usize {
+        self.0.wide_scratch_len()
+    }
+
+    #[inline]
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.0.encode(cursor)
+    }
+}
+
+/// Check that `buf` contains a valid `T` - every offset, length, tag, bool and string in it - in a
+/// single pass without allocating, and return a lazy view of it that can be accessed without any
+/// further error handling.
+#[inline]
+pub fn verify_value<'a, T: Owned>(buf: &'a [u8]) -> DecodeResult<Verified<T::Lazy<'a>>> {
+    verify_value_with_format::<T>(buf, WireFormat::Absolute)
+}
+
+/// Like [`verify_value`], for a buffer encoded in the given [`WireFormat`].
+#[inline]
+pub fn verify_value_with_format<'a, T: Owned>(
+    buf: &'a [u8],
+    format: WireFormat,
+) -> DecodeResult<Verified<T::Lazy<'a>>> {
+    T::verify(&DecodeCursor::new(buf).with_format(format))?;
+    Ok(Verified(decode_value_with_format(buf, format)?))
+}
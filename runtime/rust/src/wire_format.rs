@@ -0,0 +1,72 @@
+use crate::{BaseLen, Encode};
+
+/// How offsets to scratch space and lengths are written. Both ends must agree on it, as it isn't
+/// recorded in the encoded bytes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WireFormat {
+    /// Version 1: offsets are from the start of the buffer.
+    #[default]
+    Absolute,
+    /// Version 2: offsets are from the offset itself, wrapping around at `u32::MAX`, so that a
+    /// value and its scratch space can be moved as one block without changing them - see
+    /// [`SplicedBox`](crate::SplicedBox).
+    Relative,
+    /// Version 3: like [`Absolute`](Self::Absolute), but offsets and list and string lengths are
+    /// 64 bits rather than 32, for messages of 4 GiB or more. Values containing either have a
+    /// bigger base area - see [`BaseLen::WIDE_BASE_LEN`].
+    Wide,
+}
+
+impl WireFormat {
+    /// The version number of the format.
+    #[inline]
+    pub const fn version(self) -> u8 {
+        match self {
+            Self::Absolute => 1,
+            Self::Relative => 2,
+            Self::Wide => 3,
+        }
+    }
+
+    /// The number of bytes each offset and length takes up.
+    #[inline]
+    pub const fn offset_len(self) -> usize {
+        self.select(4, 8)
+    }
+
+    /// The largest offset or length that can be written, and so roughly the largest message that
+    /// can be encoded.
+    #[inline]
+    pub const fn max_len(self) -> usize {
+        match self {
+            Self::Absolute | Self::Relative => u32::MAX as usize,
+            Self::Wide => usize::MAX,
+        }
+    }
+
+    /// `wide` in [`Self::Wide`], `narrow` otherwise. For sizes and offsets that depend on how
+    /// big offsets are.
+    #[inline]
+    pub const fn select(self, narrow: usize, wide: usize) -> usize {
+        match self {
+            Self::Absolute | Self::Relative => narrow,
+            Self::Wide => wide,
+        }
+    }
+
+    /// The size of a `T`'s base area in this format.
+    #[inline]
+    pub const fn base_len<T: BaseLen + ?Sized>(self) -> usize {
+        self.select(T::BASE_LEN, T::WIDE_BASE_LEN)
+    }
+
+    /// The scratch space `value` takes up in this format.
+    #[inline]
+    pub fn scratch_len<T: Encode + ?Sized>(self, value: &T) -> usize {
+        match self {
+            Self::Absolute | Self::Relative => value.scratch_len(),
+            Self::Wide => value.wide_scratch_len(),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-import { DecodeCursor, Decoder, EncodeCursor, Encoder } from './index';
+import { DecodeCursor, Decoder, EncodeCursor, Encoder, WireFormat, offsetLength } from './index';
 
 export class BoxEncoder<T> implements Encoder<T>, Decoder<T> {
   private innerEncoder: Encoder<T> & Decoder<T>;
@@ -7,15 +7,15 @@ export class BoxEncoder<T> implements Encoder<T>, Decoder<T> {
     this.innerEncoder = innerEncoder;
   }
 
-  baseLength = () => 4;
+  baseLength = (format: WireFormat = WireFormat.Absolute) => offsetLength(format);
 
-  scratchLength(value: T): number {
-    return this.innerEncoder.baseLength() + this.innerEncoder.scratchLength(value);
+  scratchLength(value: T, format: WireFormat = WireFormat.Absolute): number {
+    return this.innerEncoder.baseLength(format) + this.innerEncoder.scratchLength(value, format);
   }
 
   encode(cursor: EncodeCursor, value: T) {
     cursor.innerInScratch(
-      this.innerEncoder.baseLength(),
+      this.innerEncoder.baseLength(cursor.format),
       (innerCursor: EncodeCursor) => {
         this.innerEncoder.encode(innerCursor, value);
       }
@@ -37,7 +37,7 @@ export class BoxLazyDecoder<T> implements Decoder<T> {
     this.innerDecoder = innerDecoder;
   }
 
-  baseLength = () => 4;
+  baseLength = (format: WireFormat = WireFormat.Absolute) => offsetLength(format);
 
   decode(cursor: DecodeCursor): T {
     let innerCursor = cursor.innerInScratch();
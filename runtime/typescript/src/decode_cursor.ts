@@ -1,10 +1,14 @@
+import { WireFormat, offsetLength } from './wire_format';
+
 export class DecodeCursor {
   public buffer: DataView;
   public baseOffset: number;
+  public format: WireFormat;
 
-  constructor(buffer: DataView, baseOffset: number = 0) {
+  constructor(buffer: DataView, baseOffset: number = 0, format: WireFormat = WireFormat.Absolute) {
     this.buffer = buffer;
     this.baseOffset = baseOffset;
+    this.format = format;
   }
 
   public base(size: number): number {
@@ -14,13 +18,27 @@ export class DecodeCursor {
   }
 
   public scratch(): number {
-    return this.buffer.getUint32(this.base(4), true);
+    let slot = this.base(offsetLength(this.format));
+    let offset = this.read(slot);
+    return this.format == WireFormat.Relative ? (slot + offset) >>> 0 : offset;
+  }
+
+  public length(): number {
+    return this.read(this.base(offsetLength(this.format)));
+  }
+
+  private read(slot: number): number {
+    if (this.format == WireFormat.Wide) {
+      return Number(this.buffer.getBigUint64(slot, true));
+    } else {
+      return this.buffer.getUint32(slot, true);
+    }
   }
 
   public innerInScratch(): DecodeCursor {
     let innerBaseOffset = this.scratch();
 
-    let inner = new DecodeCursor(this.buffer, innerBaseOffset);
+    let inner = new DecodeCursor(this.buffer, innerBaseOffset, this.format);
     return inner;
   }
 }
@@ -1,12 +1,16 @@
+import { WireFormat, maxLength, offsetLength, tooLargeError } from './wire_format';
+
 export class EncodeCursor {
   buffer: DataView;
   baseOffset: number;
   scratchOffset: number;
+  format: WireFormat;
 
-  constructor(buffer: DataView, baseLength: number = 0) {
+  constructor(buffer: DataView, baseLength: number = 0, format: WireFormat = WireFormat.Absolute) {
     this.buffer = buffer;
     this.baseOffset = 0;
     this.scratchOffset = baseLength;
+    this.format = format;
   }
 
   public base(size: number): number {
@@ -19,15 +23,32 @@ export class EncodeCursor {
     let index = this.scratchOffset;
     this.scratchOffset += size;
 
-    this.buffer.setUint32(this.base(4), index, true);
+    let slot = this.base(offsetLength(this.format));
+    let offset = this.format == WireFormat.Relative ? (index - slot) >>> 0 : index;
+    this.write(slot, offset, "scratch offset");
 
     return index;
   }
 
+  public length(length: number) {
+    this.write(this.base(offsetLength(this.format)), length, "length");
+  }
+
+  private write(slot: number, value: number, what: string) {
+    if (value > maxLength(this.format)) {
+      throw tooLargeError(this.format, what);
+    }
+    if (this.format == WireFormat.Wide) {
+      this.buffer.setBigUint64(slot, BigInt(value), true);
+    } else {
+      this.buffer.setUint32(slot, value, true);
+    }
+  }
+
   public innerInScratch(baseLength: number, f: (cursor: EncodeCursor) => void) {
     let innerBaseOffset = this.scratch(baseLength);
 
-    let inner = new EncodeCursor(this.buffer);
+    let inner = new EncodeCursor(this.buffer, 0, this.format);
     inner.baseOffset = innerBaseOffset;
     inner.scratchOffset = this.scratchOffset;
 
@@ -1,5 +1,6 @@
 import { DecodeCursor } from './decode_cursor';
 import { EncodeCursor } from './encode_cursor';
+import { WireFormat, maxLength, tooLargeError } from './wire_format';
 
 export * from './box';
 export { DecodeCursor } from './decode_cursor';
@@ -9,30 +10,46 @@ export * from './option';
 export * from './primitives';
 export * from './result';
 export * from './string';
+export { WireFormat, maxLength, offsetLength } from './wire_format';
 
+// Lengths depend on the wire format, as offsets and lengths are bigger in `WireFormat.Wide`.
 export interface Encoder<T> {
-  baseLength(): number;
-  scratchLength(value: T): number;
+  baseLength(format?: WireFormat): number;
+  scratchLength(value: T, format?: WireFormat): number;
   encode(cursor: EncodeCursor, value: T): void;
 }
 
 export interface Decoder<T> {
-  baseLength(): number;
+  baseLength(format?: WireFormat): number;
   decode(cursor: DecodeCursor): T;
 }
 
 export interface EncoderDecoder<T> extends Encoder<T>, Decoder<T> { }
 
-export function encodeValue<T>(encoder: Encoder<T>, value: T): ArrayBuffer {
-  let buffer = new ArrayBuffer(encoder.baseLength() + encoder.scratchLength(value));
+export function encodeValue<T>(
+  encoder: Encoder<T>,
+  value: T,
+  format: WireFormat = WireFormat.Absolute,
+): ArrayBuffer {
+  let baseLength = encoder.baseLength(format);
+  let length = baseLength + encoder.scratchLength(value, format);
+  if (length > maxLength(format)) {
+    throw tooLargeError(format, `length of ${length} bytes`);
+  }
+  let buffer = new ArrayBuffer(length);
   let dataView = new DataView(buffer);
-  let cursor = new EncodeCursor(dataView, encoder.baseLength())
+  let cursor = new EncodeCursor(dataView, baseLength, format)
   encoder.encode(cursor, value);
   return buffer;
 }
 
-export function decodeValue<T>(decoder: Decoder<T>, buffer: ArrayBuffer, offset: number = 0): T {
-  let cursor = new DecodeCursor(new DataView(buffer, offset), 0);
+export function decodeValue<T>(
+  decoder: Decoder<T>,
+  buffer: ArrayBuffer,
+  offset: number = 0,
+  format: WireFormat = WireFormat.Absolute,
+): T {
+  let cursor = new DecodeCursor(new DataView(buffer, offset), 0, format);
   return decoder.decode(cursor);
 }
 
@@ -1,4 +1,4 @@
-import { DecodeCursor, Decoder, EncodeCursor, Encoder } from './index';
+import { DecodeCursor, Decoder, EncodeCursor, Encoder, WireFormat, offsetLength } from './index';
 
 export class ListEncoder<T> implements Encoder<T[]>, Decoder<T[]> {
   private itemEncoder: Encoder<T> & Decoder<T>;
@@ -7,21 +7,21 @@ export class ListEncoder<T> implements Encoder<T[]>, Decoder<T[]> {
     this.itemEncoder = itemEncoder;
   }
 
-  baseLength = () => 8;
+  baseLength = (format: WireFormat = WireFormat.Absolute) => 2 * offsetLength(format);
 
-  scratchLength(value: T[]): number {
-    var length = value.length * this.itemEncoder.baseLength();
+  scratchLength(value: T[], format: WireFormat = WireFormat.Absolute): number {
+    var length = value.length * this.itemEncoder.baseLength(format);
     for (let item of value) {
-      length += this.itemEncoder.scratchLength(item);
+      length += this.itemEncoder.scratchLength(item, format);
     }
     return length;
   }
 
   encode(cursor: EncodeCursor, value: T[]) {
-    cursor.buffer.setUint32(cursor.base(4), value.length, true);
+    cursor.length(value.length);
 
     cursor.innerInScratch(
-      this.itemEncoder.baseLength() * value.length,
+      this.itemEncoder.baseLength(cursor.format) * value.length,
       (itemCursor: EncodeCursor) => {
         for (let item of value) {
           this.itemEncoder.encode(itemCursor, item);
@@ -31,7 +31,7 @@ export class ListEncoder<T> implements Encoder<T[]>, Decoder<T[]> {
   }
 
   decode(cursor: DecodeCursor): T[] {
-    let length = cursor.buffer.getUint32(cursor.base(4), true);
+    let length = cursor.length();
 
     let itemCursor = cursor.innerInScratch();
 
@@ -51,19 +51,27 @@ export class ListLazy<T> {
   private offset: number;
   private length: number;
   private itemDecoder: Decoder<T>;
-
-  constructor(buffer: DataView, offset: number, length: number, itemDecoder: Decoder<T>) {
+  private format: WireFormat;
+
+  constructor(
+    buffer: DataView,
+    offset: number,
+    length: number,
+    itemDecoder: Decoder<T>,
+    format: WireFormat = WireFormat.Absolute,
+  ) {
     this.buffer = buffer;
     this.offset = offset;
     this.length = length;
     this.itemDecoder = itemDecoder;
+    this.format = format;
   }
 
   public getItem(index: number): T {
     if (index > this.length) {
       throw Error("Index out of range in mproto.ListLazy");
     }
-    let cursor = new DecodeCursor(this.buffer, this.offset + index * this.itemDecoder.baseLength());
+    let cursor = new DecodeCursor(this.buffer, this.offset + index * this.itemDecoder.baseLength(this.format), this.format);
     return this.itemDecoder.decode(cursor);
   }
 }
@@ -75,13 +83,13 @@ export class ListLazyEncoder<T> implements Decoder<ListLazy<T>> {
     this.itemEncoder = itemEncoder;
   }
 
-  baseLength = () => 8;
+  baseLength = (format: WireFormat = WireFormat.Absolute) => 2 * offsetLength(format);
 
   decode(cursor: DecodeCursor): ListLazy<T> {
-    let length = cursor.buffer.getUint32(cursor.base(4), true);
+    let length = cursor.length();
     let index = cursor.scratch();
 
-    return new ListLazy(cursor.buffer, index, length, this.itemEncoder);
+    return new ListLazy(cursor.buffer, index, length, this.itemEncoder, cursor.format);
   }
 }
 
@@ -1,4 +1,4 @@
-import { DecodeCursor, Decoder, EncodeCursor, Encoder } from './index';
+import { DecodeCursor, Decoder, EncodeCursor, Encoder, WireFormat } from './index';
 
 export type Option<T> = T | null;
 
@@ -11,11 +11,11 @@ export class OptionEncoder<T> implements Encoder<Option<T>>, Decoder<Option<T>>
     this.someEncoder = someEncoder;
   }
 
-  baseLength = () => 1 + this.someEncoder.baseLength();
+  baseLength = (format: WireFormat = WireFormat.Absolute) => 1 + this.someEncoder.baseLength(format);
 
-  scratchLength(value: Option<T>): number {
+  scratchLength(value: Option<T>, format: WireFormat = WireFormat.Absolute): number {
     if (value !== null) {
-      return this.someEncoder.scratchLength(value);
+      return this.someEncoder.scratchLength(value, format);
     } else {
       return 0;
     }
@@ -27,6 +27,7 @@ export class OptionEncoder<T> implements Encoder<Option<T>>, Decoder<Option<T>>
       this.someEncoder.encode(cursor, value);
     } else {
       cursor.buffer.setUint8(cursor.base(1), 0);
+      cursor.base(this.someEncoder.baseLength(cursor.format));
     }
   }
 
@@ -34,6 +35,7 @@ export class OptionEncoder<T> implements Encoder<Option<T>>, Decoder<Option<T>>
     let variant = cursor.buffer.getUint8(cursor.base(1));
 
     if (variant == 0) {
+      cursor.base(this.someEncoder.baseLength(cursor.format));
       return null;
     } else if (variant == 1) {
       return this.someEncoder.decode(cursor);
@@ -52,12 +54,13 @@ export class OptionLazyDecoder<T> implements Decoder<Option<T>> {
     this.someDecoder = someDecoder;
   }
 
-  baseLength = () => 1 + this.someDecoder.baseLength();
+  baseLength = (format: WireFormat = WireFormat.Absolute) => 1 + this.someDecoder.baseLength(format);
 
   decode(cursor: DecodeCursor): Option<T> {
     let variant = cursor.buffer.getUint8(cursor.base(1));
 
     if (variant == 0) {
+      cursor.base(this.someDecoder.baseLength(cursor.format));
       return null;
     } else if (variant == 1) {
       return this.someDecoder.decode(cursor);
@@ -1,4 +1,4 @@
-import { DecodeCursor, Decoder, EncodeCursor, Encoder } from './index';
+import { DecodeCursor, Decoder, EncodeCursor, Encoder, WireFormat } from './index';
 
 export namespace Result {
   export class Ok<Ok, Err> {
@@ -45,13 +45,14 @@ export class ResultEncoder<Ok, Err> implements Encoder<Result<Ok, Err>>, Decoder
     this.errEncoder = errEncoder;
   }
 
-  baseLength = () => 1 + Math.max(this.okEncoder.baseLength(), this.errEncoder.baseLength());
+  baseLength = (format: WireFormat = WireFormat.Absolute) =>
+    1 + Math.max(this.okEncoder.baseLength(format), this.errEncoder.baseLength(format));
 
-  scratchLength(value: Result<Ok, Err>): number {
+  scratchLength(value: Result<Ok, Err>, format: WireFormat = WireFormat.Absolute): number {
     if (value instanceof Result.Ok) {
-      return this.okEncoder.scratchLength(value.ok);
+      return this.okEncoder.scratchLength(value.ok, format);
     } else if (value instanceof Result.Err) {
-      return this.errEncoder.scratchLength(value.err);
+      return this.errEncoder.scratchLength(value.err, format);
     } else {
       throw "Failed to encode Result - value is not a Result";
     }
@@ -61,9 +62,11 @@ export class ResultEncoder<Ok, Err> implements Encoder<Result<Ok, Err>>, Decoder
     if (value instanceof Result.Ok) {
       cursor.buffer.setUint8(cursor.base(1), 0);
       this.okEncoder.encode(cursor, value.ok);
+      cursor.base(this.baseLength(cursor.format) - 1 - this.okEncoder.baseLength(cursor.format));
     } else if (value instanceof Result.Err) {
       cursor.buffer.setUint8(cursor.base(1), 1);
       this.errEncoder.encode(cursor, value.err);
+      cursor.base(this.baseLength(cursor.format) - 1 - this.errEncoder.baseLength(cursor.format));
     } else {
       throw "Failed to encode Result - value is not a Result";
     }
@@ -73,9 +76,13 @@ export class ResultEncoder<Ok, Err> implements Encoder<Result<Ok, Err>>, Decoder
     let variant = cursor.buffer.getUint8(cursor.base(1));
 
     if (variant == 0) {
-      return new Result.Ok(this.okEncoder.decode(cursor));
+      let ok = this.okEncoder.decode(cursor);
+      cursor.base(this.baseLength(cursor.format) - 1 - this.okEncoder.baseLength(cursor.format));
+      return new Result.Ok(ok);
     } else if (variant == 1) {
-      return new Result.Err(this.errEncoder.decode(cursor));
+      let err = this.errEncoder.decode(cursor);
+      cursor.base(this.baseLength(cursor.format) - 1 - this.errEncoder.baseLength(cursor.format));
+      return new Result.Err(err);
     } else {
       throw "Failed to decode Result - invalid variant tag";
     }
@@ -94,15 +101,20 @@ export class ResultLazyDecoder<Ok, Err> implements Decoder<Result<Ok, Err>> {
     this.errDecoder = errDecoder;
   }
 
-  baseLength = () => 1 + Math.max(this.okDecoder.baseLength(), this.errDecoder.baseLength());
+  baseLength = (format: WireFormat = WireFormat.Absolute) =>
+    1 + Math.max(this.okDecoder.baseLength(format), this.errDecoder.baseLength(format));
 
   decode(cursor: DecodeCursor): Result<Ok, Err> {
     let variant = cursor.buffer.getUint8(cursor.base(1));
 
     if (variant == 0) {
-      return new Result.Ok(this.okDecoder.decode(cursor));
+      let ok = this.okDecoder.decode(cursor);
+      cursor.base(this.baseLength(cursor.format) - 1 - this.okDecoder.baseLength(cursor.format));
+      return new Result.Ok(ok);
     } else if (variant == 1) {
-      return new Result.Err(this.errDecoder.decode(cursor));
+      let err = this.errDecoder.decode(cursor);
+      cursor.base(this.baseLength(cursor.format) - 1 - this.errDecoder.baseLength(cursor.format));
+      return new Result.Err(err);
     } else {
       throw "Failed to decode Result - invalid variant tag";
     }
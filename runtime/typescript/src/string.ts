@@ -1,4 +1,4 @@
-import { DecodeCursor, Decoder, EncodeCursor, Encoder } from './index';
+import { DecodeCursor, Decoder, EncodeCursor, Encoder, WireFormat, offsetLength } from './index';
 
 const textEncoder = new TextEncoder();
 const textDecoder = new TextDecoder();
@@ -20,13 +20,13 @@ function stringLengthInBytes(s: string) {
 }
 
 export class StringEncoder implements Encoder<string>, Decoder<string> {
-  baseLength = () => 8;
+  baseLength = (format: WireFormat = WireFormat.Absolute) => 2 * offsetLength(format);
 
   scratchLength(value: string): number { return stringLengthInBytes(value); }
 
   encode(cursor: EncodeCursor, value: string) {
     let strLength = stringLengthInBytes(value);
-    cursor.buffer.setUint32(cursor.base(4), strLength, true);
+    cursor.length(strLength);
     let strScratchIndex = cursor.scratch(strLength);
     textEncoder.encodeInto(
       value,
@@ -35,7 +35,7 @@ export class StringEncoder implements Encoder<string>, Decoder<string> {
   }
 
   decode(cursor: DecodeCursor): string {
-    let length = cursor.buffer.getUint32(cursor.base(4), true);
+    let length = cursor.length();
     let index = cursor.scratch();
     return textDecoder.decode(
       new Uint8Array(cursor.buffer.buffer, cursor.buffer.byteOffset + index, length)
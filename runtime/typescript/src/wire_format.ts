@@ -0,0 +1,31 @@
+// How offsets to scratch space and lengths are written. Both ends must agree on it, as it isn't
+// recorded in the encoded bytes.
+export enum WireFormat {
+  // Version 1: offsets are from the start of the buffer.
+  Absolute = 1,
+  // Version 2: offsets are from the offset itself, wrapping around at 2^32, so that a value and
+  // its scratch space can be moved as one block without changing them.
+  Relative = 2,
+  // Version 3: like Absolute, but offsets and list and string lengths are 64 bits rather than 32,
+  // for messages of 4 GiB or more.
+  Wide = 3,
+}
+
+// The size in bytes of an offset or a length in the given format.
+export function offsetLength(format: WireFormat): number {
+  return format == WireFormat.Wide ? 8 : 4;
+}
+
+// The largest offset or length the given format can hold.
+export function maxLength(format: WireFormat): number {
+  return format == WireFormat.Wide ? Number.MAX_SAFE_INTEGER : 0xffffffff;
+}
+
+// The error for a value that doesn't fit in `maxLength(format)`. Only the 32-bit formats can
+// point to a wider one.
+export function tooLargeError(format: WireFormat, what: string): Error {
+  if (format == WireFormat.Wide) {
+    return Error(`mproto value too large: ${what} exceeds Number.MAX_SAFE_INTEGER`);
+  }
+  return Error(`mproto value too large: ${what} overflows u32, use WireFormat.Wide`);
+}
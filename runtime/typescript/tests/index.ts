@@ -7,6 +7,7 @@ const {
   ProtoBox, ProtoList, ProtoString, ProtoVoid,
   ProtoOption,
   ProtoResult, Result,
+  WireFormat,
 } = require('../dist/index');
 
 function testEncodeDecode(t, ty, v) {
@@ -127,6 +128,15 @@ test("encode option", t => {
   testEncodeDecode(t, ProtoOption(ProtoString), null);
 });
 
+test("option and result padding", t => {
+  t.plan(2);
+  // Items must be laid out at fixed strides, whichever variant they hold.
+  let buffer = encodeValue(ProtoList(ProtoOption(ProtoUint32)), [null, 7]);
+  t.deepEqual([...new Uint8Array(buffer)], [2, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 1, 7, 0, 0, 0]);
+  buffer = encodeValue(ProtoList(ProtoResult(ProtoUint8, ProtoUint32)), [new Result.Ok(3), new Result.Err(4)]);
+  t.deepEqual([...new Uint8Array(buffer)], [2, 0, 0, 0, 8, 0, 0, 0, 0, 3, 0, 0, 0, 1, 4, 0, 0, 0]);
+});
+
 test("encode result", t => {
   t.plan(4);
   testEncodeDecode(t, ProtoResult(ProtoUint32, ProtoString), new Result.Ok(42));
@@ -142,3 +152,39 @@ test("encode box", t => {
   testEncodeDecode(t, ProtoBox(ProtoResult(ProtoUint32, ProtoString)), new Result.Err("something bad happened"));
 });
 
+
+test("relative wire format", t => {
+  t.plan(3);
+  // Offsets are from the offset itself rather than the start of the buffer.
+  let buffer = encodeValue(ProtoString, "hi", WireFormat.Relative);
+  t.deepEqual([...new Uint8Array(buffer)], [2, 0, 0, 0, 4, 0, 0, 0, 104, 105]);
+
+  let ty = ProtoList(ProtoOption(ProtoBox(ProtoList(ProtoString))));
+  let value = [["a", "b"], null, [], ["c"]];
+  buffer = encodeValue(ty, value, WireFormat.Relative);
+  t.deepEqual(decodeValue(ty, buffer, 0, WireFormat.Relative), value);
+  // Only the offsets differ from the absolute format.
+  t.equal(buffer.byteLength, encodeValue(ty, value).byteLength);
+});
+
+test("wide wire format", t => {
+  t.plan(3);
+  // Offsets and lengths are 64 bits.
+  let buffer = encodeValue(ProtoString, "hi", WireFormat.Wide);
+  t.deepEqual([...new Uint8Array(buffer)], [2, 0, 0, 0, 0, 0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 0, 104, 105]);
+
+  let ty = ProtoList(ProtoOption(ProtoBox(ProtoList(ProtoString))));
+  let value = [["a", "b"], null, [], ["c"]];
+  buffer = encodeValue(ty, value, WireFormat.Wide);
+  t.deepEqual(decodeValue(ty, buffer, 0, WireFormat.Wide), value);
+  // Every list, string and box in the value has a bigger base area.
+  t.ok(buffer.byteLength > encodeValue(ty, value).byteLength);
+});
+
+test("too large for the wire format", t => {
+  t.plan(2);
+  // Only the lengths matter, nothing is allocated or encoded.
+  let tooLarge = length => ({ baseLength: () => 0, scratchLength: () => length, encode: () => {} });
+  t.throws(() => encodeValue(tooLarge(2 ** 32), null, WireFormat.Absolute), /overflows u32, use WireFormat\.Wide/);
+  t.throws(() => encodeValue(tooLarge(2 ** 53), null, WireFormat.Wide), /exceeds Number\.MAX_SAFE_INTEGER/);
+});